@@ -0,0 +1,322 @@
+//! Run archive files
+//!
+//! `run export` saves a `RunArchive` as a gzipped tarball that unpacks into
+//! one directory, readable without the CLI:
+//!
+//! ```text
+//! hotwired-run-a1b2c3d4/
+//!   manifest.json       format, version, run id, when and by what it was exported
+//!   run.json            run status and its list_runs entry
+//!   events.ndjson       the full conversation event log, oldest first
+//!   artifacts.json      artifacts, their version history and comment threads
+//!   artifacts/0/v1      content of each version, named in artifacts.json
+//! ```
+//!
+//! Archives are read entirely into memory and nothing is extracted, so
+//! entry names in a hostile archive can't write outside it.
+
+use crate::models::{ArchiveManifest, ArchivedArtifact, ConversationEvent, RunArchive};
+use crate::models::{RunStatus, RunSummary};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// `manifest.format` of every run archive
+pub const FORMAT: &str = "hotwired-run";
+/// Current archive layout
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("cannot access {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{} is not a valid run archive: {message}", path.display())]
+    Invalid { path: PathBuf, message: String },
+}
+
+/// Contents of `run.json`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunFile {
+    run: RunStatus,
+    summary: Option<RunSummary>,
+}
+
+/// Manifest for an archive of `run_id` created now.
+pub fn manifest(run_id: &str) -> ArchiveManifest {
+    ArchiveManifest {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        run_id: run_id.to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        cli_version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+/// Write `archive` to `path` as a `.tar.gz`.
+pub fn write(archive: &RunArchive, path: &Path) -> Result<(), ArchiveError> {
+    let io = |source| ArchiveError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let root = format!(
+        "hotwired-run-{}",
+        &archive.run.run_id[..archive.run.run_id.len().min(8)]
+    );
+
+    // Version contents go in their own files; artifacts.json names them
+    let mut contents = Vec::new();
+    let artifacts: Vec<ArchivedArtifact> = archive
+        .artifacts
+        .iter()
+        .enumerate()
+        .map(|(i, artifact)| {
+            let mut artifact = artifact.clone();
+            for version in &mut artifact.versions {
+                let file = format!("artifacts/{}/v{}", i, version.version);
+                contents.push((file.clone(), std::mem::take(&mut version.content)));
+                version.file = Some(file);
+            }
+            artifact
+        })
+        .collect();
+
+    let mut events = String::new();
+    for event in &archive.events {
+        events.push_str(&to_json(event, false));
+        events.push('\n');
+    }
+
+    let mut entries = vec![
+        (
+            "manifest.json".to_string(),
+            to_json(&archive.manifest, true),
+        ),
+        (
+            "run.json".to_string(),
+            to_json(
+                &RunFile {
+                    run: archive.run.clone(),
+                    summary: archive.summary.clone(),
+                },
+                true,
+            ),
+        ),
+        ("events.ndjson".to_string(), events),
+        ("artifacts.json".to_string(), to_json(&artifacts, true)),
+    ];
+    entries.extend(contents);
+
+    // Write next to `path` and rename, so a failed export leaves no
+    // truncated archive behind
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let written = write_tar(&tmp, &root, &entries).and_then(|()| std::fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    written.map_err(io)
+}
+
+fn write_tar(path: &Path, root: &str, entries: &[(String, String)]) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mtime = chrono::Utc::now().timestamp().max(0) as u64;
+    for (name, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        tar.append_data(&mut header, format!("{}/{}", root, name), data.as_bytes())?;
+    }
+    tar.into_inner()?.finish()?.sync_all()
+}
+
+/// Read an archive written by `write`.
+pub fn read(path: &Path) -> Result<RunArchive, ArchiveError> {
+    let invalid = |message: String| ArchiveError::Invalid {
+        path: path.to_path_buf(),
+        message,
+    };
+    let file = std::fs::File::open(path).map_err(|source| ArchiveError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    // Entry name (below the top directory) -> contents
+    let mut files: HashMap<String, String> = HashMap::new();
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let entries = tar.entries().map_err(|e| invalid(e.to_string()))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| invalid(e.to_string()))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry
+            .path()
+            .map_err(|e| invalid(e.to_string()))?
+            .to_string_lossy()
+            .into_owned();
+        let name = name.split_once('/').map_or(name.as_str(), |(_, rest)| rest);
+        let name = name.to_string();
+        let mut data = String::new();
+        entry
+            .read_to_string(&mut data)
+            .map_err(|e| invalid(format!("{}: {}", name, e)))?;
+        files.insert(name, data);
+    }
+
+    let mut take = |name: &str| {
+        files
+            .remove(name)
+            .ok_or_else(|| invalid(format!("missing {}", name)))
+    };
+
+    let manifest: ArchiveManifest = parse(path, "manifest.json", &take("manifest.json")?)?;
+    if manifest.format != FORMAT {
+        return Err(invalid(format!("unknown format '{}'", manifest.format)));
+    }
+    if manifest.version != VERSION {
+        return Err(invalid(format!(
+            "unsupported version {} (this CLI reads version {})",
+            manifest.version, VERSION
+        )));
+    }
+    let run: RunFile = parse(path, "run.json", &take("run.json")?)?;
+    let events = take("events.ndjson")?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse(path, "events.ndjson", line))
+        .collect::<Result<Vec<ConversationEvent>, _>>()?;
+    let mut artifacts: Vec<ArchivedArtifact> =
+        parse(path, "artifacts.json", &take("artifacts.json")?)?;
+    for artifact in &mut artifacts {
+        for version in &mut artifact.versions {
+            if let Some(file) = &version.file {
+                version.content = take(file)?;
+            }
+        }
+    }
+
+    Ok(RunArchive {
+        manifest,
+        run: run.run,
+        summary: run.summary,
+        events,
+        artifacts,
+    })
+}
+
+fn parse<T: serde::de::DeserializeOwned>(
+    path: &Path,
+    name: &str,
+    data: &str,
+) -> Result<T, ArchiveError> {
+    serde_json::from_str(data).map_err(|e| ArchiveError::Invalid {
+        path: path.to_path_buf(),
+        message: format!("{}: {}", name, e),
+    })
+}
+
+fn to_json<T: Serialize>(value: &T, pretty: bool) -> String {
+    let json = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    // Models always serialize; an empty document would fail `read` loudly
+    json.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ArchivedVersion;
+
+    fn sample() -> RunArchive {
+        let run: RunStatus = serde_json::from_value(serde_json::json!({
+            "runId": "a1b2c3d4-e5f6-7890-abcd-ef1234567890",
+            "status": "completed",
+        }))
+        .unwrap();
+        let event: ConversationEvent = serde_json::from_value(serde_json::json!({
+            "sequenceNum": 1, "eventType": "handoff", "to": "builder", "content": "go",
+        }))
+        .unwrap();
+        let version = |n: i64, content: &str| ArchivedVersion {
+            version: n,
+            timestamp: None,
+            lines_added: 1,
+            lines_removed: 0,
+            file: None,
+            content: content.to_string(),
+        };
+        RunArchive {
+            manifest: manifest(&run.run_id),
+            run,
+            summary: None,
+            events: vec![event],
+            artifacts: vec![ArchivedArtifact {
+                path: "docs/PLAN.md".to_string(),
+                title: Some("Plan".to_string()),
+                versions: vec![version(1, "# Plan\n"), version(2, "# Plan\n\nStep 1\n")],
+                comments: Vec::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.tar.gz");
+        write(&sample(), &path).unwrap();
+        assert!(!dir.path().join("run.tar.gz.tmp").exists());
+
+        let archive = read(&path).unwrap();
+        assert_eq!(archive.manifest.format, FORMAT);
+        assert_eq!(archive.run.status, "completed");
+        assert_eq!(archive.events.len(), 1);
+        assert_eq!(archive.events[0].extra["to"], "builder");
+        let versions = &archive.artifacts[0].versions;
+        assert_eq!(versions[1].content, "# Plan\n\nStep 1\n");
+        assert_eq!(versions[1].file.as_deref(), Some("artifacts/0/v2"));
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.tar.gz");
+        std::fs::write(&path, "not a tarball").unwrap();
+        assert!(matches!(read(&path), Err(ArchiveError::Invalid { .. })));
+        assert!(matches!(
+            read(&dir.path().join("missing.tar.gz")),
+            Err(ArchiveError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_failed_write_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("run.tar.gz");
+        assert!(matches!(
+            write(&sample(), &path),
+            Err(ArchiveError::Io { .. })
+        ));
+        assert!(!path.exists());
+
+        // An existing archive survives a failed overwrite
+        let path = dir.path().join("run.tar.gz");
+        std::fs::write(&path, "previous").unwrap();
+        std::fs::create_dir(dir.path().join("run.tar.gz.tmp")).unwrap();
+        assert!(write(&sample(), &path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "previous");
+    }
+}
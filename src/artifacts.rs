@@ -0,0 +1,202 @@
+//! Artifact operations on behalf of a session
+//!
+//! `Artifacts` binds a client to the run and role of a validated session, so
+//! callers pass paths and comment IDs while `runId` and authorship are filled
+//! in the same way the CLI does it. Local file checks happen here too: core
+//! can only version files that exist on disk.
+
+use crate::ipc::{ErrorCode, HotwiredClient, IpcError};
+use crate::models::{
+    AddCommentParams, ArtifactMoveParams, ArtifactMoveResult, ArtifactPathParams, ArtifactSummary,
+    ArtifactSyncResult, ArtifactVersion, ArtifactVersionContent, Comment, CommentRef,
+    CommentThread, GetCommentParams, GetVersionParams, ListCommentsParams, ReplyCommentParams,
+    ResolveCommentParams,
+};
+use crate::session::{validate_session, SessionState, ValidationError};
+use std::path::{Path, PathBuf};
+
+/// Failures of artifact operations
+#[derive(Debug, thiserror::Error)]
+pub enum ArtifactError {
+    /// The file to sync or move isn't on disk
+    #[error("file not found: {}", .0.display())]
+    FileNotFound(PathBuf),
+    /// Core has no artifact at this path; it must be synced first
+    #[error("{0}")]
+    NotSynced(String),
+    #[error(transparent)]
+    Ipc(#[from] IpcError),
+}
+
+/// Artifact API scoped to one session's run and role
+pub struct Artifacts<'a> {
+    client: &'a HotwiredClient,
+    session: SessionState,
+}
+
+impl<'a> Artifacts<'a> {
+    /// Act as `session` (usually from `validate_session`).
+    pub fn new(client: &'a HotwiredClient, session: SessionState) -> Self {
+        Self { client, session }
+    }
+
+    /// Validate the current terminal's session and act as it.
+    pub async fn for_current_session(client: &'a HotwiredClient) -> Result<Self, ValidationError> {
+        Ok(Self::new(client, validate_session(client).await?))
+    }
+
+    /// Session this handle acts as
+    pub fn session(&self) -> &SessionState {
+        &self.session
+    }
+
+    fn path_params(&self, path: &Path) -> ArtifactPathParams {
+        ArtifactPathParams {
+            run_id: self.session.run_id.clone(),
+            path: path_string(path),
+        }
+    }
+
+    /// All artifacts tracked in the run
+    pub async fn list(&self) -> Result<Vec<ArtifactSummary>, ArtifactError> {
+        Ok(self.client.artifact_list(&self.session.run_id).await?)
+    }
+
+    /// Register `path` as an artifact, or snapshot a new version of it.
+    pub async fn sync(&self, path: &Path) -> Result<ArtifactSyncResult, ArtifactError> {
+        if !path.exists() {
+            return Err(ArtifactError::FileNotFound(path.to_path_buf()));
+        }
+        Ok(self.client.artifact_sync(&self.path_params(path)).await?)
+    }
+
+    /// Move an artifact, keeping its comments.
+    ///
+    /// Core moves the file too unless `refs_only`, in which case the file
+    /// must already be at `new_path`.
+    pub async fn move_to(
+        &self,
+        old_path: &Path,
+        new_path: &Path,
+        refs_only: bool,
+    ) -> Result<ArtifactMoveResult, ArtifactError> {
+        let must_exist = if refs_only { new_path } else { old_path };
+        if !must_exist.exists() {
+            return Err(ArtifactError::FileNotFound(must_exist.to_path_buf()));
+        }
+
+        let params = ArtifactMoveParams {
+            run_id: self.session.run_id.clone(),
+            old_path: path_string(old_path),
+            new_path: path_string(new_path),
+            refs_only,
+        };
+        match self.client.artifact_move(&params).await {
+            Ok(result) => Ok(result),
+            Err(IpcError::Backend {
+                code: ErrorCode::NotFound,
+                message,
+            }) => Err(ArtifactError::NotSynced(message)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Comment on `target_text`, or reply in `reply_to`'s thread.
+    pub async fn add_comment(
+        &self,
+        path: &Path,
+        target_text: &str,
+        message: &str,
+        reply_to: Option<&str>,
+    ) -> Result<CommentRef, ArtifactError> {
+        let params = AddCommentParams {
+            run_id: self.session.run_id.clone(),
+            path: path_string(path),
+            target_text: target_text.to_string(),
+            comment: message.to_string(),
+            author: self.session.role_id.clone(),
+            parent_comment_id: reply_to.map(String::from),
+        };
+        Ok(self.client.artifact_add_comment(&params).await?)
+    }
+
+    /// Reply to a comment; path and anchor are inherited from it.
+    pub async fn reply(
+        &self,
+        comment_id: &str,
+        message: &str,
+    ) -> Result<CommentRef, ArtifactError> {
+        let params = ReplyCommentParams {
+            run_id: self.session.run_id.clone(),
+            comment_id: comment_id.to_string(),
+            message: message.to_string(),
+            author: self.session.role_id.clone(),
+        };
+        Ok(self.client.artifact_reply_comment(&params).await?)
+    }
+
+    /// A comment with its replies
+    pub async fn comment(&self, comment_id: &str) -> Result<CommentThread, ArtifactError> {
+        let params = GetCommentParams {
+            run_id: self.session.run_id.clone(),
+            comment_id: comment_id.to_string(),
+        };
+        Ok(self.client.artifact_get_comment(&params).await?)
+    }
+
+    /// Comments on `path`; `status_filter` is `open`, `resolved` or `all`.
+    pub async fn comments(
+        &self,
+        path: &Path,
+        status_filter: &str,
+    ) -> Result<Vec<Comment>, ArtifactError> {
+        let params = ListCommentsParams {
+            run_id: self.session.run_id.clone(),
+            path: path_string(path),
+            status_filter: status_filter.to_string(),
+        };
+        Ok(self.client.artifact_list_comments(&params).await?)
+    }
+
+    /// Resolve a comment, optionally leaving a closing reply.
+    pub async fn resolve(
+        &self,
+        comment_id: &str,
+        reply: Option<&str>,
+    ) -> Result<(), ArtifactError> {
+        let params = ResolveCommentParams {
+            run_id: self.session.run_id.clone(),
+            comment_id: comment_id.to_string(),
+            resolved_by: self.session.role_id.clone(),
+            reply: reply.map(String::from),
+        };
+        Ok(self.client.artifact_resolve_comment(&params).await?)
+    }
+
+    /// Version history of `path`
+    pub async fn versions(&self, path: &Path) -> Result<Vec<ArtifactVersion>, ArtifactError> {
+        Ok(self
+            .client
+            .artifact_list_versions(&self.path_params(path))
+            .await?)
+    }
+
+    /// Content of `path` at `version`
+    pub async fn version(
+        &self,
+        path: &Path,
+        version: u32,
+    ) -> Result<ArtifactVersionContent, ArtifactError> {
+        let params = GetVersionParams {
+            run_id: self.session.run_id.clone(),
+            path: path_string(path),
+            version,
+        };
+        Ok(self.client.artifact_get_version(&params).await?)
+    }
+}
+
+/// Paths go over the wire as the caller wrote them (usually project-relative).
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
@@ -0,0 +1,324 @@
+//! Recorded IPC sessions ("cassettes")
+//!
+//! `HOTWIRED_RECORD=path` saves every request a client makes, with the raw
+//! response (and any events pushed on a subscription), to a JSON file.
+//! `HOTWIRED_REPLAY=path` answers requests from such a file with no backend
+//! running, so a bug report or a wrapper script's test can reproduce real
+//! core behavior offline.
+//!
+//! Requests are stored without their token. During replay a request is
+//! answered by the first unused interaction with the same method and
+//! params; once those run out, the last match answers again.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "interactions": [
+//!     {
+//!       "request": {"method": "list_runs", "params": {}},
+//!       "response": {"success": true, "data": []}
+//!     }
+//!   ]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Record every exchange to this file
+pub const RECORD_ENV: &str = "HOTWIRED_RECORD";
+/// Answer every request from this file instead of a backend
+pub const REPLAY_ENV: &str = "HOTWIRED_REPLAY";
+
+const VERSION: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CassetteError {
+    #[error("cannot read cassette {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid cassette {}: {message}", path.display())]
+    Invalid { path: PathBuf, message: String },
+}
+
+/// One request and everything core sent back for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// `{"method", "params"}` as sent, minus the token
+    pub request: Value,
+    /// Response line, when it was valid JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    /// Response line verbatim, when it wasn't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    /// Lines pushed after the ack on a subscription
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<Value>,
+}
+
+impl Interaction {
+    /// The lines core wrote, in order.
+    fn lines(&self) -> Vec<String> {
+        let first = match (&self.response, &self.raw) {
+            (Some(response), _) => response.to_string(),
+            (None, Some(raw)) => raw.clone(),
+            (None, None) => String::new(),
+        };
+        std::iter::once(first)
+            .chain(self.events.iter().map(Value::to_string))
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct File {
+    version: u32,
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+/// A cassette being recorded or replayed
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    state: Mutex<State>,
+    /// A save already failed and was warned about
+    save_failed: AtomicBool,
+}
+
+struct State {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+impl Cassette {
+    /// Start an empty recording; `path` is (over)written after each exchange.
+    pub fn record(path: &Path) -> Self {
+        Self::new(path, Mode::Record, Vec::new())
+    }
+
+    /// Load a recording to answer requests from.
+    pub fn replay(path: &Path) -> Result<Self, CassetteError> {
+        let raw = std::fs::read_to_string(path).map_err(|source| CassetteError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let file: File = serde_json::from_str(&raw).map_err(|e| CassetteError::Invalid {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        if file.version != VERSION {
+            return Err(CassetteError::Invalid {
+                path: path.to_path_buf(),
+                message: format!("unsupported version {}", file.version),
+            });
+        }
+        Ok(Self::new(path, Mode::Replay, file.interactions))
+    }
+
+    fn new(path: &Path, mode: Mode, interactions: Vec<Interaction>) -> Self {
+        let used = vec![false; interactions.len()];
+        Self {
+            path: path.to_path_buf(),
+            mode,
+            state: Mutex::new(State { interactions, used }),
+            save_failed: AtomicBool::new(false),
+        }
+    }
+
+    /// Cassette requested by `HOTWIRED_REPLAY` or `HOTWIRED_RECORD` (replay
+    /// wins if both are set), if any.
+    pub fn from_env() -> Result<Option<Self>, CassetteError> {
+        let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+        if let Some(path) = var(REPLAY_ENV) {
+            return Self::replay(Path::new(&path)).map(Some);
+        }
+        Ok(var(RECORD_ENV).map(|path| Self::record(Path::new(&path))))
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.mode == Mode::Replay
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Lines to answer the request line `wire` with, if the cassette has them.
+    pub fn answer(&self, wire: &str) -> Option<Vec<String>> {
+        let request = request_key(wire);
+        let mut state = self.state.lock().ok()?;
+        let matching: Vec<usize> = (0..state.interactions.len())
+            .filter(|&i| state.interactions[i].request == request)
+            .collect();
+        let index = matching
+            .iter()
+            .copied()
+            .find(|&i| !state.used[i])
+            .or_else(|| matching.last().copied())?;
+        state.used[index] = true;
+        Some(state.interactions[index].lines())
+    }
+
+    /// Record the response line core sent for the request line `wire`.
+    /// Returns the interaction's index, for `record_event`.
+    pub fn record_response(&self, wire: &str, line: &str) -> usize {
+        let line = line.trim_end();
+        let (response, raw) = match serde_json::from_str(line) {
+            Ok(value) => (Some(value), None),
+            Err(_) => (None, Some(line.to_string())),
+        };
+        let index = match self.state.lock() {
+            Ok(mut state) => {
+                state.interactions.push(Interaction {
+                    request: request_key(wire),
+                    response,
+                    raw,
+                    events: Vec::new(),
+                });
+                state.used.push(true);
+                state.interactions.len() - 1
+            }
+            Err(_) => return 0,
+        };
+        self.save();
+        index
+    }
+
+    /// Record a line pushed on the subscription recorded at `index`.
+    pub fn record_event(&self, index: usize, line: &str) {
+        let Ok(event) = serde_json::from_str(line.trim_end()) else {
+            return;
+        };
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(interaction) = state.interactions.get_mut(index) {
+                interaction.events.push(event);
+            }
+        }
+        self.save();
+    }
+
+    /// Write the recording so far. A command may exit at any point, so this
+    /// runs after every exchange; failures are warned about once rather than
+    /// breaking the command being recorded. Created `0600`, since it holds
+    /// whole runs' content.
+    fn save(&self) {
+        let Ok(state) = self.state.lock() else {
+            return;
+        };
+        let file = File {
+            version: VERSION,
+            interactions: state.interactions.clone(),
+        };
+        let written = serde_json::to_string_pretty(&file)
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                std::fs::OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .mode(0o600)
+                    .open(&self.path)?
+                    .write_all((json + "\n").as_bytes())
+            });
+        if let Err(e) = written {
+            if !self.save_failed.swap(true, Ordering::Relaxed) {
+                eprintln!(
+                    "warning: cannot write cassette {}: {}",
+                    self.path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// The request as matched and stored: method and params, without the token.
+fn request_key(wire: &str) -> Value {
+    let mut request: Value = serde_json::from_str(wire).unwrap_or(Value::Null);
+    if let Some(object) = request.as_object_mut() {
+        object.remove("token");
+        object.remove("id");
+    }
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_record_then_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+
+        let recorder = Cassette::record(&path);
+        let wire = r#"{"method":"list_runs","params":{},"token":"secret"}"#;
+        recorder.record_response(wire, "{\"success\":true,\"data\":[1]}\n");
+        recorder.record_response(wire, "{\"success\":true,\"data\":[2]}\n");
+        let sub = recorder.record_response(
+            r#"{"method":"subscribe_conversation_events","params":{"runId":"r"}}"#,
+            "{\"success\":true}",
+        );
+        recorder.record_event(sub, "{\"sequenceNum\":1}\n");
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains("secret"));
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let player = Cassette::replay(&path).unwrap();
+        // Token differences don't matter when matching
+        let replayed = r#"{"method":"list_runs","params":{},"token":"other"}"#;
+        assert_eq!(
+            player.answer(replayed).unwrap(),
+            vec!["{\"data\":[1],\"success\":true}"]
+        );
+        assert_eq!(
+            player.answer(replayed).unwrap(),
+            vec!["{\"data\":[2],\"success\":true}"]
+        );
+        // Exhausted: the last match answers again
+        assert_eq!(
+            player.answer(replayed).unwrap(),
+            vec!["{\"data\":[2],\"success\":true}"]
+        );
+        assert_eq!(
+            player
+                .answer(r#"{"method":"subscribe_conversation_events","params":{"runId":"r"}}"#)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(player
+            .answer(r#"{"method":"list_runs","params":{"status":"x"}}"#)
+            .is_none());
+    }
+
+    #[test]
+    fn test_malformed_responses_replay_verbatim() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        Cassette::record(&path).record_response(r#"{"method":"ping","params":{}}"#, "not json");
+
+        let player = Cassette::replay(&path).unwrap();
+        assert_eq!(
+            player.answer(r#"{"method":"ping","params":{}}"#).unwrap(),
+            vec!["not json"]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Raw socket method escape hatch
+//!
+//! `hotwired api <method>` sends arbitrary params to any core method and
+//! prints the whole `SocketResponse`, so scripts can use core features
+//! before the CLI grows a subcommand for them. No capability check is made:
+//! an unknown method is core's to reject.
+
+use super::{exit_code, handle_error, validate, OutputFormat};
+use crate::ipc::{ErrorCode, HotwiredClient};
+use serde_json::Value;
+use std::io::Read;
+
+pub async fn run(
+    client: &HotwiredClient,
+    method: &str,
+    params: Option<&str>,
+    inject_session: bool,
+    output: OutputFormat,
+) {
+    let mut params = match params {
+        Some(source) => parse_params(source),
+        None => Value::Object(Default::default()),
+    };
+
+    if inject_session {
+        let Some(object) = params.as_object_mut() else {
+            eprintln!("error: --inject-session needs --params to be a JSON object");
+            std::process::exit(exit_code::USAGE);
+        };
+        let state = validate::require_session(client).await;
+        // Explicit params win over the injected ones
+        object.entry("runId").or_insert(Value::String(state.run_id));
+        object
+            .entry("source")
+            .or_insert(Value::String(state.role_id));
+    }
+
+    let response = match client.request(method, params).await {
+        Ok(response) => response,
+        Err(e) => handle_error(e),
+    };
+
+    // Always JSON: the response shape is whatever core sent
+    match output {
+        OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string(&response).unwrap_or_default())
+        }
+        _ => println!(
+            "{}",
+            serde_json::to_string_pretty(&response).unwrap_or_default()
+        ),
+    }
+
+    if !response.success {
+        let code = response
+            .code
+            .as_deref()
+            .map(ErrorCode::parse)
+            .unwrap_or(ErrorCode::Unknown);
+        std::process::exit(exit_code::for_error_code(code));
+    }
+}
+
+/// `--params` value: inline JSON, `@path` to read a file, or `-` for stdin.
+fn parse_params(source: &str) -> Value {
+    let (raw, origin) = match source {
+        "-" => {
+            let mut raw = String::new();
+            if let Err(e) = std::io::stdin().read_to_string(&mut raw) {
+                eprintln!("error: cannot read params from stdin: {}", e);
+                std::process::exit(exit_code::FAILURE);
+            }
+            (raw, "stdin".to_string())
+        }
+        _ => match source.strip_prefix('@') {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(raw) => (raw, path.to_string()),
+                Err(e) => {
+                    eprintln!("error: cannot read {}: {}", path, e);
+                    std::process::exit(exit_code::NOT_FOUND);
+                }
+            },
+            None => (source.to_string(), "--params".to_string()),
+        },
+    };
+
+    serde_json::from_str(&raw).unwrap_or_else(|e| {
+        eprintln!("error: {} is not valid JSON: {}", origin, e);
+        std::process::exit(exit_code::USAGE);
+    })
+}
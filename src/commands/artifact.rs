@@ -3,30 +3,79 @@
 //! Simplified artifact handling - replaces 14+ MCP tools with 8 CLI commands.
 //! See Issue #13 and docs/features/COMMENT_ANCHORING.md for background.
 
-use super::{format_timestamp, handle_error, validate};
+use super::{exit_code, format_timestamp, handle_error, validate, OutputFormat};
 use crate::ipc::HotwiredClient;
+use crate::models::{ArtifactMoveResult, ArtifactSyncResult, ArtifactVersionContent, Comment};
+use hotwired_client::{ArtifactError, Artifacts};
+use serde::Serialize;
 use std::path::Path;
 
-/// List all tracked artifacts in the current run
-pub async fn list(client: &HotwiredClient) {
-    let state = validate::require_session(client).await;
-
-    match client
-        .request(
-            "artifact_list",
-            serde_json::json!({ "runId": state.run_id }),
-        )
-        .await
-    {
-        Ok(response) if response.success => {
-            let artifacts = response
-                .data
-                .as_ref()
-                .and_then(|d| d.get("artifacts"))
-                .and_then(|a| a.as_array())
-                .cloned()
-                .unwrap_or_default();
+/// Structured result of `artifact sync`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Synced<'a> {
+    path: &'a str,
+    #[serde(flatten)]
+    result: &'a ArtifactSyncResult,
+}
+
+/// Structured result of `artifact mv`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Moved<'a> {
+    old_path: &'a str,
+    new_path: &'a str,
+    #[serde(flatten)]
+    result: &'a ArtifactMoveResult,
+}
+
+/// Structured result of comment add/reply/resolve
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommentChange<'a> {
+    comment_id: &'a str,
+    /// Top-level comment this reply belongs to
+    parent_comment_id: Option<&'a str>,
+    resolved: bool,
+}
+
+/// Structured result of `artifact version`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionSnapshot<'a> {
+    path: &'a str,
+    version: u32,
+    #[serde(flatten)]
+    snapshot: &'a ArtifactVersionContent,
+}
 
+/// Artifact API for this terminal's session, or print why not and exit
+async fn artifacts(client: &HotwiredClient) -> Artifacts<'_> {
+    Artifacts::new(client, validate::require_session(client).await)
+}
+
+/// Print an artifact failure and exit with its code
+fn handle_artifact_error(e: ArtifactError) -> ! {
+    match e {
+        ArtifactError::FileNotFound(path) => {
+            eprintln!("error: file not found: {}", path.display());
+            std::process::exit(exit_code::NOT_FOUND);
+        }
+        ArtifactError::NotSynced(message) => {
+            eprintln!("error: {}", message);
+            std::process::exit(exit_code::NOT_FOUND);
+        }
+        ArtifactError::Ipc(e) => handle_error(e),
+    }
+}
+
+/// List all tracked artifacts in the current run
+pub async fn list(client: &HotwiredClient, output: OutputFormat) {
+    match artifacts(client).await.list().await {
+        Ok(artifacts) => {
+            if output.print_structured_list(&artifacts) {
+                return;
+            }
             if artifacts.is_empty() {
                 println!("No tracked artifacts.");
                 return;
@@ -37,11 +86,7 @@ pub async fn list(client: &HotwiredClient) {
                 "PATH", "STATUS", "COMMENTS", "VERSIONS", "TITLE"
             );
             for a in &artifacts {
-                let path = a.get("path").and_then(|v| v.as_str()).unwrap_or("-");
-                let status = a.get("status").and_then(|v| v.as_str()).unwrap_or("?");
-                let comments = a.get("commentCount").and_then(|v| v.as_i64()).unwrap_or(0);
-                let versions = a.get("versionCount").and_then(|v| v.as_i64()).unwrap_or(0);
-                let title = a.get("title").and_then(|v| v.as_str()).unwrap_or("-");
+                let title = a.title.as_deref().unwrap_or("-");
 
                 // Truncate title if too long
                 let title_display = if title.len() > 20 {
@@ -51,70 +96,36 @@ pub async fn list(client: &HotwiredClient) {
                 };
 
                 // Highlight missing status
-                let status_display = match status {
+                let status_display = match a.status.as_str() {
                     "ok" => "ok",
                     "missing" => "MISSING",
-                    _ => status,
+                    other => other,
                 };
 
                 println!(
                     "{:<30} {:<8} {:<8} {:<8} {:<20}",
-                    path, status_display, comments, versions, title_display
+                    a.path, status_display, a.comment_count, a.version_count, title_display
                 );
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown".into())
-            );
-            std::process::exit(1);
-        }
-        Err(e) => handle_error(e),
+        Err(e) => handle_artifact_error(e),
     }
 }
 
 /// Sync a file (register new or update existing)
-pub async fn sync(client: &HotwiredClient, path: &Path) {
-    let state = validate::require_session(client).await;
-
-    // Check file exists
-    if !path.exists() {
-        eprintln!("error: file not found: {}", path.display());
-        std::process::exit(1);
-    }
+pub async fn sync(client: &HotwiredClient, path: &Path, output: OutputFormat) {
+    match artifacts(client).await.sync(path).await {
+        Ok(result) => {
+            if output.print_structured(&Synced {
+                path: &path.to_string_lossy(),
+                result: &result,
+            }) {
+                return;
+            }
+            let title = result.title.as_deref().unwrap_or("Untitled");
+            let version = result.version.unwrap_or(1);
 
-    match client
-        .request(
-            "artifact_sync",
-            serde_json::json!({
-                "runId": state.run_id,
-                "path": path.to_string_lossy(),
-            }),
-        )
-        .await
-    {
-        Ok(response) if response.success => {
-            let data = response.data.unwrap_or_default();
-            let status = data
-                .get("status")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown");
-            let title = data
-                .get("title")
-                .and_then(|v| v.as_str())
-                .unwrap_or("Untitled");
-            let version = data.get("version").and_then(|v| v.as_i64()).unwrap_or(1);
-            let relocated = data
-                .get("commentsRelocated")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
-            let orphaned = data
-                .get("commentsOrphaned")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
-
-            match status {
+            match result.status.as_str() {
                 "registered" => {
                     println!("Artifact registered: {}", path.display());
                     println!("  Title: {}", title);
@@ -124,21 +135,17 @@ pub async fn sync(client: &HotwiredClient, path: &Path) {
                     println!("Artifact synced: {}", path.display());
                     println!("  Title: {}", title);
                     println!("  Version: {}", version);
-                    if relocated > 0 || orphaned > 0 {
-                        println!("  {} comments relocated, {} orphaned", relocated, orphaned);
+                    if result.comments_relocated > 0 || result.comments_orphaned > 0 {
+                        println!(
+                            "  {} comments relocated, {} orphaned",
+                            result.comments_relocated, result.comments_orphaned
+                        );
                     }
                 }
-                _ => println!("Status: {}", status),
+                other => println!("Status: {}", other),
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown".into())
-            );
-            std::process::exit(1);
-        }
-        Err(e) => handle_error(e),
+        Err(e) => handle_artifact_error(e),
     }
 }
 
@@ -148,50 +155,22 @@ pub async fn move_artifact(
     old_path: &Path,
     new_path: &Path,
     refs_only: bool,
+    output: OutputFormat,
 ) {
-    let state = validate::require_session(client).await;
-
-    // Validation
-    if refs_only {
-        // refs_only mode: new file must exist
-        if !new_path.exists() {
-            eprintln!("error: new file not found: {}", new_path.display());
-            eprintln!("When using --refs-only, the file must already exist at the new location.");
-            std::process::exit(1);
-        }
-    } else {
-        // Normal mode: old file must exist
-        if !old_path.exists() {
-            eprintln!("error: source file not found: {}", old_path.display());
-            eprintln!("Use --refs-only if the file was already moved.");
-            std::process::exit(1);
-        }
-    }
-
-    match client
-        .request(
-            "artifact_move",
-            serde_json::json!({
-                "runId": state.run_id,
-                "oldPath": old_path.to_string_lossy(),
-                "newPath": new_path.to_string_lossy(),
-                "refsOnly": refs_only,
-            }),
-        )
+    match artifacts(client)
+        .await
+        .move_to(old_path, new_path, refs_only)
         .await
     {
-        Ok(response) if response.success => {
-            let data = response.data.unwrap_or_default();
-            let preserved = data
-                .get("commentsPreserved")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
-            let file_moved = data
-                .get("fileMoved")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false);
-
-            if file_moved {
+        Ok(result) => {
+            if output.print_structured(&Moved {
+                old_path: &old_path.to_string_lossy(),
+                new_path: &new_path.to_string_lossy(),
+                result: &result,
+            }) {
+                return;
+            }
+            if result.file_moved {
                 println!(
                     "File moved: {} → {}",
                     old_path.display(),
@@ -203,24 +182,27 @@ pub async fn move_artifact(
                 old_path.display(),
                 new_path.display()
             );
-            println!("  {} comments preserved", preserved);
+            println!("  {} comments preserved", result.comments_preserved);
         }
-        Ok(response) => {
-            // Backend should return specific errors:
-            // - "artifact_not_found" if old_path not in artifacts table
-            // - "file_not_found" if refs_only but new_path doesn't exist
-            let err = response.error.unwrap_or_else(|| "unknown".into());
-            if err.contains("not found") || err.contains("not tracked") {
-                eprintln!("error: {}", err);
-                eprintln!();
-                eprintln!("The artifact must be synced first. Run:");
-                eprintln!("  hotwired-cli artifact sync {}", old_path.display());
+        Err(ArtifactError::FileNotFound(path)) => {
+            eprintln!("error: file not found: {}", path.display());
+            if refs_only {
+                eprintln!(
+                    "When using --refs-only, the file must already exist at the new location."
+                );
             } else {
-                eprintln!("error: {}", err);
+                eprintln!("Use --refs-only if the file was already moved.");
             }
-            std::process::exit(1);
+            std::process::exit(exit_code::NOT_FOUND);
         }
-        Err(e) => handle_error(e),
+        Err(ArtifactError::NotSynced(message)) => {
+            eprintln!("error: {}", message);
+            eprintln!();
+            eprintln!("The artifact must be synced first. Run:");
+            eprintln!("  hotwired-cli artifact sync {}", old_path.display());
+            std::process::exit(exit_code::NOT_FOUND);
+        }
+        Err(e) => handle_artifact_error(e),
     }
 }
 
@@ -231,98 +213,66 @@ pub async fn add_comment(
     target_text: &str,
     message: &str,
     reply_to: Option<&str>,
+    output: OutputFormat,
 ) {
-    let state = validate::require_session(client).await;
-
-    let mut params = serde_json::json!({
-        "runId": state.run_id,
-        "path": path.to_string_lossy(),
-        "targetText": target_text,
-        "comment": message,
-        "author": state.role_id,
-    });
-
-    if let Some(parent_id) = reply_to {
-        params
-            .as_object_mut()
-            .unwrap()
-            .insert("parentCommentId".to_string(), serde_json::json!(parent_id));
-    }
-
-    match client.request("artifact_add_comment", params).await {
-        Ok(response) if response.success => {
-            let data = response.data.unwrap_or_default();
-            let comment_id = data
-                .get("commentId")
-                .and_then(|v| v.as_str())
-                .unwrap_or("?");
+    match artifacts(client)
+        .await
+        .add_comment(path, target_text, message, reply_to)
+        .await
+    {
+        Ok(created) => {
+            if output.print_structured(&CommentChange {
+                comment_id: &created.comment_id,
+                parent_comment_id: reply_to,
+                resolved: false,
+            }) {
+                return;
+            }
             if let Some(parent_id) = reply_to {
-                println!("Reply added: {} (in thread of {})", comment_id, parent_id);
+                println!(
+                    "Reply added: {} (in thread of {})",
+                    created.comment_id, parent_id
+                );
             } else {
-                println!("Comment added: {}", comment_id);
+                println!("Comment added: {}", created.comment_id);
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown".into())
-            );
-            std::process::exit(1);
-        }
-        Err(e) => handle_error(e),
+        Err(e) => handle_artifact_error(e),
     }
 }
 
 /// List comments on an artifact
-pub async fn list_comments(client: &HotwiredClient, path: &Path, status_filter: &str) {
-    let state = validate::require_session(client).await;
-
-    match client
-        .request(
-            "artifact_list_comments",
-            serde_json::json!({
-                "runId": state.run_id,
-                "path": path.to_string_lossy(),
-                "statusFilter": status_filter,
-            }),
-        )
-        .await
-    {
-        Ok(response) if response.success => {
-            let comments = response
-                .data
-                .as_ref()
-                .and_then(|d| d.get("comments"))
-                .and_then(|c| c.as_array())
-                .cloned()
-                .unwrap_or_default();
-
+pub async fn list_comments(
+    client: &HotwiredClient,
+    path: &Path,
+    status_filter: &str,
+    output: OutputFormat,
+) {
+    match artifacts(client).await.comments(path, status_filter).await {
+        Ok(comments) => {
+            if output.print_structured_list(&comments) {
+                return;
+            }
             if comments.is_empty() {
                 println!("No comments.");
                 return;
             }
 
             // Separate top-level comments from replies (thread_id present = reply)
-            let mut top_level: Vec<&serde_json::Value> = Vec::new();
-            let mut replies: std::collections::HashMap<String, Vec<&serde_json::Value>> =
+            let mut top_level: Vec<&Comment> = Vec::new();
+            let mut replies: std::collections::HashMap<&str, Vec<&Comment>> =
                 std::collections::HashMap::new();
 
             for c in &comments {
-                let thread_id = c.get("threadId").and_then(|v| v.as_str());
-                if let Some(tid) = thread_id {
-                    replies.entry(tid.to_string()).or_default().push(c);
+                if let Some(tid) = c.thread_id.as_deref() {
+                    replies.entry(tid).or_default().push(c);
                 } else {
                     top_level.push(c);
                 }
             }
 
             for c in &top_level {
-                let id = c.get("commentId").and_then(|v| v.as_str()).unwrap_or("?");
-                let target = c.get("targetText").and_then(|v| v.as_str()).unwrap_or("");
-                let msg = c.get("comment").and_then(|v| v.as_str()).unwrap_or("");
-                let status = c.get("status").and_then(|v| v.as_str()).unwrap_or("?");
-                let author = c.get("author").and_then(|v| v.as_str()).unwrap_or("?");
-
+                let target = c.target_text.as_deref().unwrap_or("");
                 let target_preview = if target.len() > 30 {
                     format!("{}...", &target[..30])
                 } else {
@@ -331,210 +281,146 @@ pub async fn list_comments(client: &HotwiredClient, path: &Path, status_filter:
 
                 println!(
                     "[{}] \"{}\" - {}  ({}, {})",
-                    id, target_preview, msg, author, status
+                    c.comment_id,
+                    target_preview,
+                    c.comment,
+                    c.author.as_deref().unwrap_or("?"),
+                    c.status.as_deref().unwrap_or("?")
                 );
 
                 // Print threaded replies indented
-                if let Some(thread_replies) = replies.get(id) {
+                if let Some(thread_replies) = replies.get(c.comment_id.as_str()) {
                     for r in thread_replies {
-                        let rid = r.get("commentId").and_then(|v| v.as_str()).unwrap_or("?");
-                        let rmsg = r.get("comment").and_then(|v| v.as_str()).unwrap_or("");
-                        let rauthor = r.get("author").and_then(|v| v.as_str()).unwrap_or("?");
-
-                        let rmsg_preview = if rmsg.len() > 60 {
-                            format!("{}...", &rmsg[..57])
+                        let rmsg_preview = if r.comment.len() > 60 {
+                            format!("{}...", &r.comment[..57])
                         } else {
-                            rmsg.to_string()
+                            r.comment.clone()
                         };
 
-                        println!("  \u{21b3} [{}] {}: {}", rid, rauthor, rmsg_preview);
+                        println!(
+                            "  \u{21b3} [{}] {}: {}",
+                            r.comment_id,
+                            r.author.as_deref().unwrap_or("?"),
+                            rmsg_preview
+                        );
                     }
                 }
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown".into())
-            );
-            std::process::exit(1);
-        }
-        Err(e) => handle_error(e),
+        Err(e) => handle_artifact_error(e),
     }
 }
 
 /// Show a specific comment and its thread
-pub async fn show_comment(client: &HotwiredClient, comment_id: &str) {
-    let state = validate::require_session(client).await;
-
-    match client
-        .request(
-            "artifact_get_comment",
-            serde_json::json!({
-                "runId": state.run_id,
-                "commentId": comment_id,
-            }),
-        )
-        .await
-    {
-        Ok(response) if response.success => {
-            let data = response.data.unwrap_or_default();
-            let comment = data.get("comment");
-            let replies = data
-                .get("replies")
-                .and_then(|r| r.as_array())
-                .cloned()
-                .unwrap_or_default();
-
-            if let Some(c) = comment {
-                let id = c.get("commentId").and_then(|v| v.as_str()).unwrap_or("?");
-                let target = c.get("targetText").and_then(|v| v.as_str()).unwrap_or("");
-                let msg = c.get("comment").and_then(|v| v.as_str()).unwrap_or("");
-                let status = c.get("status").and_then(|v| v.as_str()).unwrap_or("?");
-                let author = c.get("author").and_then(|v| v.as_str()).unwrap_or("?");
-                let thread_id = c.get("threadId").and_then(|v| v.as_str());
-                let created = c.get("createdAt").and_then(|v| v.as_str()).unwrap_or("-");
-
-                println!("Comment:  {}", id);
-                println!("Author:   {}", author);
-                println!("Status:   {}", status);
-                println!("Created:  {}", format_timestamp(created));
-                if let Some(tid) = thread_id {
+pub async fn show_comment(client: &HotwiredClient, comment_id: &str, output: OutputFormat) {
+    match artifacts(client).await.comment(comment_id).await {
+        Ok(thread) => {
+            if thread.comment.is_some() && output.print_structured(&thread) {
+                return;
+            }
+            if let Some(c) = &thread.comment {
+                let target = c.target_text.as_deref().unwrap_or("");
+
+                println!("Comment:  {}", c.comment_id);
+                println!("Author:   {}", c.author.as_deref().unwrap_or("?"));
+                println!("Status:   {}", c.status.as_deref().unwrap_or("?"));
+                println!(
+                    "Created:  {}",
+                    format_timestamp(c.created_at.as_deref().unwrap_or("-"))
+                );
+                if let Some(tid) = &c.thread_id {
                     println!("Thread:   {} (this is a reply)", tid);
                 }
                 if !target.is_empty() {
                     println!("On text:  \"{}\"", target);
                 }
                 println!();
-                println!("{}", msg);
+                println!("{}", c.comment);
 
-                if !replies.is_empty() {
+                if !thread.replies.is_empty() {
                     println!();
-                    println!("--- Replies ({}) ---", replies.len());
-                    for r in &replies {
-                        let rid = r.get("commentId").and_then(|v| v.as_str()).unwrap_or("?");
-                        let rmsg = r.get("comment").and_then(|v| v.as_str()).unwrap_or("");
-                        let rauthor = r.get("author").and_then(|v| v.as_str()).unwrap_or("?");
-                        let rcreated = r.get("createdAt").and_then(|v| v.as_str()).unwrap_or("-");
-
+                    println!("--- Replies ({}) ---", thread.replies.len());
+                    for r in &thread.replies {
                         println!();
-                        println!("  [{}] {} ({})", rid, rauthor, format_timestamp(rcreated));
+                        println!(
+                            "  [{}] {} ({})",
+                            r.comment_id,
+                            r.author.as_deref().unwrap_or("?"),
+                            format_timestamp(r.created_at.as_deref().unwrap_or("-"))
+                        );
                         // Indent each line of the reply message
-                        for line in rmsg.lines() {
+                        for line in r.comment.lines() {
                             println!("  {}", line);
                         }
                     }
                 }
             } else {
                 eprintln!("error: No comment data in response");
-                std::process::exit(1);
+                std::process::exit(exit_code::PROTOCOL_ERROR);
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown".into())
-            );
-            std::process::exit(1);
-        }
-        Err(e) => handle_error(e),
+        Err(e) => handle_artifact_error(e),
     }
 }
 
 /// Reply to an existing comment (inherits path/selection from parent)
-pub async fn reply_comment(client: &HotwiredClient, comment_id: &str, message: &str) {
-    let state = validate::require_session(client).await;
-
-    match client
-        .request(
-            "artifact_reply_comment",
-            serde_json::json!({
-                "runId": state.run_id,
-                "commentId": comment_id,
-                "message": message,
-                "author": state.role_id,
-            }),
-        )
-        .await
-    {
-        Ok(response) if response.success => {
-            let data = response.data.unwrap_or_default();
-            let reply_id = data
-                .get("commentId")
-                .and_then(|v| v.as_str())
-                .unwrap_or("?");
-            println!("Reply added: {} (in thread of {})", reply_id, comment_id);
-        }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown".into())
+pub async fn reply_comment(
+    client: &HotwiredClient,
+    comment_id: &str,
+    message: &str,
+    output: OutputFormat,
+) {
+    match artifacts(client).await.reply(comment_id, message).await {
+        Ok(created) => {
+            if output.print_structured(&CommentChange {
+                comment_id: &created.comment_id,
+                parent_comment_id: Some(comment_id),
+                resolved: false,
+            }) {
+                return;
+            }
+            println!(
+                "Reply added: {} (in thread of {})",
+                created.comment_id, comment_id
             );
-            std::process::exit(1);
         }
-        Err(e) => handle_error(e),
+        Err(e) => handle_artifact_error(e),
     }
 }
 
 /// Resolve a comment
-pub async fn resolve(client: &HotwiredClient, comment_id: &str, reply: Option<&str>) {
-    let state = validate::require_session(client).await;
-
-    let mut params = serde_json::json!({
-        "runId": state.run_id,
-        "commentId": comment_id,
-        "resolvedBy": state.role_id,
-    });
-
-    if let Some(reply_msg) = reply {
-        params
-            .as_object_mut()
-            .unwrap()
-            .insert("reply".to_string(), serde_json::json!(reply_msg));
-    }
-
-    match client.request("artifact_resolve_comment", params).await {
-        Ok(response) if response.success => {
+pub async fn resolve(
+    client: &HotwiredClient,
+    comment_id: &str,
+    reply: Option<&str>,
+    output: OutputFormat,
+) {
+    match artifacts(client).await.resolve(comment_id, reply).await {
+        Ok(()) => {
+            if output.print_structured(&CommentChange {
+                comment_id,
+                parent_comment_id: None,
+                resolved: true,
+            }) {
+                return;
+            }
             if reply.is_some() {
                 println!("Reply added and comment resolved: {}", comment_id);
             } else {
                 println!("Comment resolved: {}", comment_id);
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown".into())
-            );
-            std::process::exit(1);
-        }
-        Err(e) => handle_error(e),
+        Err(e) => handle_artifact_error(e),
     }
 }
 
 /// List all versions of an artifact
-pub async fn list_versions(client: &HotwiredClient, path: &Path) {
-    let state = validate::require_session(client).await;
-
-    match client
-        .request(
-            "artifact_list_versions",
-            serde_json::json!({
-                "runId": state.run_id,
-                "path": path.to_string_lossy(),
-            }),
-        )
-        .await
-    {
-        Ok(response) if response.success => {
-            let versions = response
-                .data
-                .as_ref()
-                .and_then(|d| d.get("versions"))
-                .and_then(|v| v.as_array())
-                .cloned()
-                .unwrap_or_default();
-
+pub async fn list_versions(client: &HotwiredClient, path: &Path, output: OutputFormat) {
+    match artifacts(client).await.versions(path).await {
+        Ok(versions) => {
+            if output.print_structured_list(&versions) {
+                return;
+            }
             if versions.is_empty() {
                 println!("No versions found. Run `artifact sync` first.");
                 return;
@@ -542,77 +428,49 @@ pub async fn list_versions(client: &HotwiredClient, path: &Path) {
 
             println!("{:<8} {:<20} CHANGES", "VERSION", "TIMESTAMP");
             for v in &versions {
-                let version = v.get("version").and_then(|x| x.as_i64()).unwrap_or(0);
-                let timestamp = v.get("timestamp").and_then(|x| x.as_str()).unwrap_or("-");
-                let added = v.get("linesAdded").and_then(|x| x.as_i64()).unwrap_or(0);
-                let removed = v.get("linesRemoved").and_then(|x| x.as_i64()).unwrap_or(0);
-
-                let changes = if version == 1 {
+                let changes = if v.version == 1 {
                     "(initial)".to_string()
                 } else {
-                    format!("+{} -{} lines", added, removed)
+                    format!("+{} -{} lines", v.lines_added, v.lines_removed)
                 };
 
                 println!(
                     "{:<8} {:<20} {}",
-                    version,
-                    format_timestamp(timestamp),
+                    v.version,
+                    format_timestamp(v.timestamp.as_deref().unwrap_or("-")),
                     changes
                 );
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown".into())
-            );
-            std::process::exit(1);
-        }
-        Err(e) => handle_error(e),
+        Err(e) => handle_artifact_error(e),
     }
 }
 
 /// Show content of a specific version
-pub async fn get_version(client: &HotwiredClient, path: &Path, version: u32) {
-    let state = validate::require_session(client).await;
-
-    match client
-        .request(
-            "artifact_get_version",
-            serde_json::json!({
-                "runId": state.run_id,
-                "path": path.to_string_lossy(),
-                "version": version,
-            }),
-        )
-        .await
-    {
-        Ok(response) if response.success => {
-            let data = response.data.unwrap_or_default();
-            let title = data
-                .get("title")
-                .and_then(|v| v.as_str())
-                .unwrap_or("Untitled");
-            let timestamp = data
-                .get("timestamp")
-                .and_then(|v| v.as_str())
-                .unwrap_or("-");
-            let content = data.get("content").and_then(|v| v.as_str()).unwrap_or("");
-
-            println!("# {} (version {})", title, version);
-            println!("# Synced: {}", format_timestamp(timestamp));
+pub async fn get_version(client: &HotwiredClient, path: &Path, version: u32, output: OutputFormat) {
+    match artifacts(client).await.version(path, version).await {
+        Ok(snapshot) => {
+            if output.print_structured(&VersionSnapshot {
+                path: &path.to_string_lossy(),
+                version,
+                snapshot: &snapshot,
+            }) {
+                return;
+            }
+            println!(
+                "# {} (version {})",
+                snapshot.title.as_deref().unwrap_or("Untitled"),
+                version
+            );
+            println!(
+                "# Synced: {}",
+                format_timestamp(snapshot.timestamp.as_deref().unwrap_or("-"))
+            );
             println!("# {}", "-".repeat(60));
             println!();
-            println!("{}", content);
-        }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown".into())
-            );
-            std::process::exit(1);
+            println!("{}", snapshot.content);
         }
-        Err(e) => handle_error(e),
+        Err(e) => handle_artifact_error(e),
     }
 }
 
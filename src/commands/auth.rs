@@ -1,52 +1,347 @@
-use crate::ipc::{HotwiredClient, IpcError};
+use super::{exit_code, handle_error, OutputFormat};
+use crate::config::{self, Context};
+use crate::ipc::{self, ErrorCode, HotwiredClient, IpcError};
+use serde::Serialize;
+use std::io::{IsTerminal, Read, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
 
-pub async fn status(client: &HotwiredClient) {
-    let socket_display = client.socket_path().to_string();
+/// Structured result of `auth status`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthStatus {
+    /// `running`, `not_responding`, `not_running` or `connection_failed`
+    backend: &'static str,
+    backend_version: Option<String>,
+    socket: String,
+    socket_exists: bool,
+    /// `configured`, `empty` or `not_configured`
+    auth_token: &'static str,
+    /// `env $VAR` or `file PATH`
+    token_source: String,
+    /// Token file is readable by group or other users
+    token_exposed: bool,
+}
 
-    match client.health_check().await {
-        Ok(response) if response.success => {
-            let version = response
-                .data
-                .as_ref()
-                .and_then(|d| d.get("version"))
-                .and_then(|v| v.as_str());
-
-            match version {
-                Some(v) => println!("Backend:    running (v{})", v),
-                None => println!("Backend:    running"),
-            }
+/// Structured result of `auth token set` / `auth logout`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenFileChange {
+    token_file: String,
+    action: &'static str,
+}
+
+/// Structured result of `auth verify`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Verification {
+    accepted: bool,
+    token_source: String,
+    error: Option<String>,
+}
+
+/// Where this invocation's token comes from
+pub enum TokenSource {
+    /// An environment variable: `HOTWIRED_TOKEN` or the context's `token-env`
+    Env(String),
+    /// The context's `token-file`, or `~/.hotwired/auth_token`
+    File(PathBuf),
+}
+
+impl TokenSource {
+    pub fn resolve(context: &Context) -> Self {
+        if ipc::token_from_env().is_some() {
+            return TokenSource::Env(ipc::TOKEN_ENV.to_string());
         }
-        Ok(_) => {
-            println!("Backend:    not responding");
+        if let Some(var) = &context.token_env {
+            return TokenSource::Env(var.clone());
         }
-        Err(IpcError::NotConnected(_)) => {
-            println!("Backend:    not running");
+        TokenSource::File(token_file(context).unwrap_or_default())
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            TokenSource::Env(var) => format!("env ${}", var),
+            TokenSource::File(path) => format!("file {}", path.display()),
+        }
+    }
+
+    /// `configured`, `empty` or `not_configured`
+    pub fn state(&self) -> &'static str {
+        let token = match self {
+            TokenSource::Env(var) => std::env::var(var).ok(),
+            TokenSource::File(path) if path.exists() => {
+                Some(std::fs::read_to_string(path).unwrap_or_default())
+            }
+            TokenSource::File(_) => None,
+        };
+        match token {
+            Some(token) if !token.trim().is_empty() => "configured",
+            Some(_) => "empty",
+            None => "not_configured",
+        }
+    }
+}
+
+/// The file `token set`/`logout` manage; `None` when the context reads its
+/// token from an environment variable instead.
+fn token_file(context: &Context) -> Option<PathBuf> {
+    if context.token_env.is_some() {
+        return None;
+    }
+    match &context.token_file {
+        Some(file) => Some(PathBuf::from(config::expand_home(file))),
+        None => ipc::auth_token_path(),
+    }
+}
+
+fn require_token_file(context: &Context) -> PathBuf {
+    match token_file(context) {
+        Some(path) => path,
+        None => {
+            eprintln!(
+                "error: the active context reads its token from ${}, not a file",
+                context.token_env.as_deref().unwrap_or_default()
+            );
+            eprintln!("Set that variable instead, or give the context a token-file.");
+            std::process::exit(exit_code::FAILURE);
         }
-        Err(_) => {
-            println!("Backend:    connection failed");
+    }
+}
+
+/// Permission bits of `path` if group or other users can read or write it.
+pub fn exposed_mode(path: &Path) -> Option<u32> {
+    let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    (mode & 0o077 != 0).then_some(mode)
+}
+
+fn note_env_override() {
+    if ipc::token_from_env().is_some() {
+        eprintln!(
+            "note: ${} is set and overrides the token file in this shell",
+            ipc::TOKEN_ENV
+        );
+    }
+}
+
+pub async fn status(client: &HotwiredClient, context: &Context, output: OutputFormat) {
+    let socket_display = client.socket_path().to_string();
+
+    let (backend, backend_version) = match client.health_check().await {
+        Ok(ping) => ("running", ping.version),
+        Err(IpcError::Backend { .. }) | Err(IpcError::InvalidResponse(_)) => {
+            ("not_responding", None)
         }
+        Err(IpcError::NotConnected(_)) => ("not_running", None),
+        Err(_) => ("connection_failed", None),
+    };
+
+    let source = TokenSource::resolve(context);
+    let auth_token = source.state();
+    let token_exposed = match &source {
+        TokenSource::File(path) => exposed_mode(path).is_some(),
+        TokenSource::Env(_) => false,
+    };
+
+    if let (true, TokenSource::File(path)) = (token_exposed, &source) {
+        // stderr, so the warning never corrupts structured output
+        eprintln!(
+            "warning: {} is readable by other users; run: chmod 600 {}",
+            path.display(),
+            path.display()
+        );
+    }
+
+    let status = AuthStatus {
+        backend,
+        backend_version,
+        socket_exists: std::path::Path::new(&socket_display).exists(),
+        socket: socket_display,
+        auth_token,
+        token_source: source.describe(),
+        token_exposed,
+    };
+    if output.print_structured(&status) {
+        return;
+    }
+
+    match (status.backend, &status.backend_version) {
+        ("running", Some(v)) => println!("Backend:    running (v{})", v),
+        ("running", None) => println!("Backend:    running"),
+        (other, _) => println!("Backend:    {}", other.replace('_', " ")),
     }
 
-    if std::path::Path::new(&socket_display).exists() {
-        println!("Socket:     {}", socket_display);
+    if status.socket_exists {
+        println!("Socket:     {}", status.socket);
     } else {
-        println!("Socket:     {} (not found)", socket_display);
+        println!("Socket:     {} (not found)", status.socket);
     }
 
-    let token_path = dirs::home_dir()
-        .map(|h| h.join(".hotwired").join("auth_token"))
-        .unwrap_or_default();
+    println!(
+        "Auth token: {} ({})",
+        status.auth_token.replace('_', " "),
+        status.token_source
+    );
+}
 
-    if token_path.exists() {
-        match std::fs::read_to_string(&token_path) {
-            Ok(content) if !content.trim().is_empty() => {
-                println!("Auth token: configured");
-            }
-            _ => {
-                println!("Auth token: empty");
+/// `auth token set`: store the token read from stdin.
+pub fn set_token(context: &Context, output: OutputFormat) {
+    let path = require_token_file(context);
+
+    let mut stdin = std::io::stdin();
+    let mut raw = String::new();
+    let read = if stdin.is_terminal() {
+        eprint!("Paste token and press Enter: ");
+        read_line_hidden(&stdin, &mut raw)
+    } else {
+        stdin.read_to_string(&mut raw)
+    };
+    if let Err(e) = read {
+        eprintln!("error: cannot read token from stdin: {}", e);
+        std::process::exit(exit_code::FAILURE);
+    }
+
+    let token = raw.trim();
+    if token.is_empty() {
+        eprintln!("error: no token on stdin");
+        std::process::exit(exit_code::INVALID_PARAMS);
+    }
+    if let Err(e) = write_private(&path, token) {
+        eprintln!("error: cannot write {}: {}", path.display(), e);
+        std::process::exit(exit_code::FAILURE);
+    }
+    note_env_override();
+
+    let change = TokenFileChange {
+        token_file: path.display().to_string(),
+        action: "saved",
+    };
+    if output.print_structured(&change) {
+        return;
+    }
+    println!("Token saved to {}", change.token_file);
+}
+
+/// `read_line` with terminal echo turned off, so the secret isn't shown.
+fn read_line_hidden(stdin: &std::io::Stdin, buf: &mut String) -> std::io::Result<usize> {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = stdin.as_raw_fd();
+    // SAFETY: termios is plain data, filled in by tcgetattr before use
+    let mut original: libc::termios = unsafe { std::mem::zeroed() };
+    // SAFETY: fd is stdin, open for the life of the process
+    if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+        return stdin.read_line(buf);
+    }
+    let mut hidden = original;
+    hidden.c_lflag &= !libc::ECHO;
+    // SAFETY: as above; `hidden` is a valid termios copied from the terminal
+    unsafe { libc::tcsetattr(fd, libc::TCSAFLUSH, &hidden) };
+
+    let read = stdin.read_line(buf);
+
+    // SAFETY: restores the settings read above
+    unsafe { libc::tcsetattr(fd, libc::TCSAFLUSH, &original) };
+    // The Enter keypress wasn't echoed either
+    eprintln!();
+    read
+}
+
+/// Write `contents` to `path` readable only by the owner, creating the
+/// parent directory (0700) if needed. The file is replaced atomically so a
+/// concurrent reader never sees a partial token.
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        if !dir.exists() {
+            std::fs::create_dir_all(dir)?;
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+    }
+
+    let tmp = path.with_extension("tmp");
+    let _ = std::fs::remove_file(&tmp);
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&tmp)?;
+    file.write_all(contents.as_bytes())?;
+    file.write_all(b"\n")?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)
+}
+
+/// `auth logout`: delete the token file.
+pub fn logout(context: &Context, output: OutputFormat) {
+    let path = require_token_file(context);
+
+    let action = match std::fs::remove_file(&path) {
+        Ok(()) => "removed",
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "absent",
+        Err(e) => {
+            eprintln!("error: cannot remove {}: {}", path.display(), e);
+            std::process::exit(exit_code::FAILURE);
+        }
+    };
+    note_env_override();
+
+    let change = TokenFileChange {
+        token_file: path.display().to_string(),
+        action,
+    };
+    if output.print_structured(&change) {
+        return;
+    }
+    match action {
+        "removed" => println!("Removed {}", change.token_file),
+        _ => println!("No token at {}", change.token_file),
+    }
+}
+
+/// `auth verify`: make an authenticated request and report whether core
+/// accepted the token.
+pub async fn verify(client: &HotwiredClient, context: &Context, output: OutputFormat) {
+    let token_source = TokenSource::resolve(context).describe();
+
+    // A core that doesn't enforce auth would accept the request anyway
+    if !client.has_auth_token() {
+        let verification = Verification {
+            accepted: false,
+            token_source,
+            error: Some("no token configured".to_string()),
+        };
+        if !output.print_structured(&verification) {
+            println!("No token configured ({})", verification.token_source);
+            println!();
+            println!("Pipe one to: hotwired auth token set");
+        }
+        std::process::exit(exit_code::PERMISSION_DENIED);
+    }
+
+    let error = match client.list_runs().await {
+        Ok(_) => None,
+        Err(IpcError::Backend {
+            code: ErrorCode::Unauthorized | ErrorCode::PermissionDenied,
+            message,
+        }) => Some(message),
+        Err(e) => handle_error(e),
+    };
+
+    let verification = Verification {
+        accepted: error.is_none(),
+        token_source,
+        error,
+    };
+    if !output.print_structured(&verification) {
+        match &verification.error {
+            None => println!("Token accepted ({})", verification.token_source),
+            Some(message) => {
+                println!("Token rejected ({})", verification.token_source);
+                println!("  {}", message);
             }
         }
-    } else {
-        println!("Auth token: not configured");
+    }
+    if !verification.accepted {
+        std::process::exit(exit_code::PERMISSION_DENIED);
     }
 }
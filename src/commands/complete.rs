@@ -2,42 +2,32 @@
 //!
 //! The `complete` command signals that the assigned work is done.
 
-use super::{handle_error, validate};
+use super::{handle_error, validate, OutputFormat};
 use crate::ipc::HotwiredClient;
+use crate::models::TaskCompleteParams;
 
-pub async fn run(client: &HotwiredClient, outcome: Option<String>) {
+pub async fn run(client: &HotwiredClient, outcome: Option<String>, output: OutputFormat) {
     // Validate session first
     let state = validate::require_session(client).await;
 
     match client
-        .request(
-            "task_complete",
-            serde_json::json!({
-                "runId": state.run_id,
-                "taskDescription": "Task completed",
-                "source": state.role_id,
-                "outcome": outcome.unwrap_or_else(|| "Completed".to_string()),
-            }),
-        )
+        .task_complete(&TaskCompleteParams {
+            run_id: state.run_id,
+            task_description: "Task completed".to_string(),
+            source: state.role_id,
+            outcome: outcome.unwrap_or_else(|| "Completed".to_string()),
+        })
         .await
     {
-        Ok(response) if response.success => {
+        Ok(result) => {
+            if output.print_structured(&result.clone().unwrap_or_default()) {
+                return;
+            }
             println!("Task marked complete.");
-            if let Some(data) = response.data {
-                if let Some(next) = data.get("nextAction").and_then(|v| v.as_str()) {
-                    println!("Next: {}", next);
-                }
+            if let Some(next) = result.and_then(|r| r.next_action) {
+                println!("Next: {}", next);
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response
-                    .error
-                    .unwrap_or_else(|| "failed to complete".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
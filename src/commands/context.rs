@@ -0,0 +1,274 @@
+//! Named backend contexts from `~/.hotwired/config.toml`
+//!
+//! `resolve` picks the context an invocation runs under (`--context`, then
+//! `HOTWIRED_CONTEXT`, then `current-context`) and applies it to the client
+//! and command defaults. `context list/use/show` inspect and switch them.
+
+use super::{exit_code, OutputFormat};
+use crate::config::{self, Config, ConfigError, Context, Defaults};
+use crate::ipc::{self, HotwiredClient};
+use serde::Serialize;
+use std::time::Duration;
+
+/// The context this invocation runs under (default when none is selected)
+pub struct Resolved {
+    pub name: Option<String>,
+    pub context: Context,
+}
+
+impl Resolved {
+    /// Client for the context; `--socket-path` overrides its socket and
+    /// `HOTWIRED_TOKEN` its token source.
+    ///
+    /// An unreadable token source is fatal unless `lenient`, for commands
+    /// that report on or repair the token; those then send no token.
+    pub fn client(&self, socket_path: Option<String>, lenient: bool) -> HotwiredClient {
+        let client = HotwiredClient::new(socket_path.or_else(|| self.context.socket_path()));
+        if ipc::token_from_env().is_some() {
+            return client;
+        }
+        match self.context.read_token() {
+            Ok(Some(token)) => client.with_auth_token(Some(token)),
+            Ok(None) => client,
+            Err(_) if lenient => client.with_auth_token(None),
+            Err(e) => handle_config_error(e),
+        }
+    }
+
+    /// `--output`, else the context default, else table.
+    pub fn output(&self, flag: Option<OutputFormat>) -> OutputFormat {
+        if let Some(output) = flag {
+            return output;
+        }
+        match self.context.defaults.output.as_deref() {
+            Some(name) => <OutputFormat as clap::ValueEnum>::from_str(name, true)
+                .unwrap_or_else(|_| {
+                    eprintln!(
+                        "error: context '{}' has invalid default output '{}' (expected table, json or ndjson)",
+                        self.name.as_deref().unwrap_or("-"),
+                        name
+                    );
+                    std::process::exit(exit_code::FAILURE);
+                }),
+            None => OutputFormat::default(),
+        }
+    }
+
+    /// `--timeout`, else `HOTWIRED_TIMEOUT` (already applied by the client),
+    /// else the context default. An invalid flag or default is a usage error.
+    pub fn timeout(&self, flag: Option<f64>) -> Option<Option<Duration>> {
+        if let Some(secs) = flag {
+            return Some(ipc::parse_timeout_secs(secs).unwrap_or_else(|e| {
+                eprintln!("error: --timeout: {}", e);
+                std::process::exit(exit_code::USAGE);
+            }));
+        }
+        if std::env::var_os("HOTWIRED_TIMEOUT").is_some() {
+            return None;
+        }
+        let secs = self.context.defaults.timeout?;
+        Some(ipc::parse_timeout_secs(secs).unwrap_or_else(|e| {
+            eprintln!(
+                "error: context '{}' has {}",
+                self.name.as_deref().unwrap_or("-"),
+                e
+            );
+            std::process::exit(exit_code::USAGE);
+        }))
+    }
+}
+
+/// Load the config and select the active context, exiting on errors.
+pub fn resolve(explicit: Option<&str>) -> Resolved {
+    try_resolve(explicit).unwrap_or_else(|e| handle_config_error(e))
+}
+
+/// Like `resolve`, for the `internal` hooks, which must never fail: a broken
+/// config or unknown context is warned about and the default context used.
+pub fn resolve_or_default(explicit: Option<&str>) -> Resolved {
+    try_resolve(explicit).unwrap_or_else(|e| {
+        eprintln!("warning: {}; using the default context", e);
+        Resolved {
+            name: None,
+            context: Context::default(),
+        }
+    })
+}
+
+fn try_resolve(explicit: Option<&str>) -> Result<Resolved, ConfigError> {
+    let config = Config::load()?;
+    Ok(match config.active(explicit)? {
+        Some((name, context)) => Resolved {
+            name: Some(name),
+            context: context.clone(),
+        },
+        None => Resolved {
+            name: None,
+            context: Context::default(),
+        },
+    })
+}
+
+fn load() -> Config {
+    Config::load().unwrap_or_else(|e| handle_config_error(e))
+}
+
+fn handle_config_error(e: ConfigError) -> ! {
+    eprintln!("error: {}", e);
+    let code = match e {
+        ConfigError::UnknownContext { .. } => {
+            eprintln!("Run `hotwired context list` to see configured contexts.");
+            exit_code::NOT_FOUND
+        }
+        _ => exit_code::FAILURE,
+    };
+    std::process::exit(code);
+}
+
+// =============================================================================
+// context list / use / show
+// =============================================================================
+
+/// Structured view of one context (never includes the token itself)
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContextView<'a> {
+    name: Option<&'a str>,
+    active: bool,
+    socket: String,
+    /// `env $VAR`, `file PATH`, or `default (PATH)`
+    token_source: String,
+    project: Option<String>,
+    defaults: &'a Defaults,
+}
+
+fn view<'a>(name: Option<&'a str>, context: &'a Context, active: bool) -> ContextView<'a> {
+    let default_socket = || HotwiredClient::new(None).socket_path().to_string();
+    let token_source = match (&context.token_env, &context.token_file) {
+        (Some(var), _) => format!("env ${}", var),
+        (None, Some(file)) => format!("file {}", config::expand_home(file)),
+        (None, None) => format!(
+            "default ({})",
+            ipc::auth_token_path().unwrap_or_default().display()
+        ),
+    };
+    ContextView {
+        name,
+        active,
+        socket: context.socket_path().unwrap_or_else(default_socket),
+        token_source,
+        project: context
+            .project_dir()
+            .map(|p| p.to_string_lossy().to_string()),
+        defaults: &context.defaults,
+    }
+}
+
+pub fn list(explicit: Option<&str>, output: OutputFormat) {
+    let config = load();
+    let active = config.active_name(explicit);
+
+    let views: Vec<ContextView> = config
+        .contexts
+        .iter()
+        .map(|(name, ctx)| view(Some(name), ctx, active.as_deref() == Some(name)))
+        .collect();
+    if output.print_structured_list(&views) {
+        return;
+    }
+
+    if views.is_empty() {
+        println!("No contexts configured.");
+        println!();
+        println!(
+            "Add one to {}:",
+            Config::path().unwrap_or_default().display()
+        );
+        println!();
+        println!("  current-context = \"dev\"");
+        println!();
+        println!("  [contexts.dev]");
+        println!("  socket = \"~/.hotwired-dev/hotwired.sock\"");
+        return;
+    }
+
+    println!("  {:<16} {:<44} PROJECT", "NAME", "SOCKET");
+    for v in &views {
+        println!(
+            "{} {:<16} {:<44} {}",
+            if v.active { "*" } else { " " },
+            v.name.unwrap_or("-"),
+            v.socket,
+            v.project.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+pub fn use_context(name: &str, output: OutputFormat) {
+    let config = load();
+    let Some(path) = Config::path() else {
+        eprintln!("error: cannot determine home directory");
+        std::process::exit(exit_code::FAILURE);
+    };
+    if let Err(e) = config.set_current(&path, name) {
+        handle_config_error(e);
+    }
+
+    if output.print_structured(&serde_json::json!({ "currentContext": name })) {
+        return;
+    }
+    println!("Switched to context '{}'.", name);
+    if let Ok(env) = std::env::var(config::CONTEXT_ENV) {
+        if !env.is_empty() && env != name {
+            eprintln!(
+                "note: ${} is set to '{}', which overrides current-context in this shell",
+                config::CONTEXT_ENV,
+                env
+            );
+        }
+    }
+}
+
+pub fn show(name: Option<&str>, explicit: Option<&str>, output: OutputFormat) {
+    let config = load();
+    let active = config.active_name(explicit);
+    let name = name.map(String::from).or_else(|| active.clone());
+
+    let context = match &name {
+        Some(name) => config
+            .get(name)
+            .unwrap_or_else(|e| handle_config_error(e))
+            .clone(),
+        None => Context::default(),
+    };
+    let v = view(name.as_deref(), &context, name == active);
+    if output.print_structured(&v) {
+        return;
+    }
+
+    match v.name {
+        Some(name) if v.active => println!("Context:  {} (active)", name),
+        Some(name) => println!("Context:  {}", name),
+        None => println!("Context:  none (built-in defaults)"),
+    }
+    println!("Socket:   {}", v.socket);
+    println!("Token:    {}", v.token_source);
+    println!("Project:  {}", v.project.as_deref().unwrap_or("-"));
+
+    let d = v.defaults;
+    let mut defaults = Vec::new();
+    if let Some(output) = &d.output {
+        defaults.push(format!("output={}", output));
+    }
+    if let Some(timeout) = d.timeout {
+        defaults.push(format!("timeout={}", timeout));
+    }
+    if let Some(playbook) = &d.playbook {
+        defaults.push(format!("playbook={}", playbook));
+    }
+    if defaults.is_empty() {
+        println!("Defaults: -");
+    } else {
+        println!("Defaults: {}", defaults.join(" "));
+    }
+}
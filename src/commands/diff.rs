@@ -0,0 +1,292 @@
+//! Side-by-side comparison of two runs
+//!
+//! `run diff` is for judging playbooks against each other: rerun the same
+//! intent under two playbooks, then compare what each run went through (see
+//! `stats`) and what it produced. Artifacts both runs have at the same path
+//! are compared by their latest versions as a unified diff.
+
+use super::run::{resolve_id, short_id, summary_of};
+use super::stats::{self, StatsReport};
+use super::time::format_duration;
+use super::{handle_error, OutputFormat};
+use crate::ipc::{HotwiredClient, IpcError};
+use crate::models::{ArtifactPathParams, GetVersionParams, RunSummary};
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use similar::TextDiff;
+use std::collections::BTreeMap;
+
+/// Structured result of `run diff`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunDiff {
+    a: RunSide,
+    b: RunSide,
+    artifacts: Vec<ArtifactDiff>,
+}
+
+/// What one of the compared runs went through; durations in seconds
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunSide {
+    run_id: String,
+    status: String,
+    playbook: Option<String>,
+    /// Phases in the order they were reached
+    phases: Vec<String>,
+    duration_secs: i64,
+    handoffs: usize,
+    impediments: usize,
+    blocked_secs: i64,
+    completions: usize,
+    artifact_versions: i64,
+}
+
+impl RunSide {
+    fn new(run: &RunSummary, stats: &StatsReport) -> Self {
+        Self {
+            run_id: run.id.clone(),
+            status: run.status.clone(),
+            playbook: run
+                .template_name
+                .clone()
+                .or_else(|| run.template_id.clone()),
+            phases: stats.phases.iter().map(|p| p.phase.clone()).collect(),
+            duration_secs: stats.duration_secs,
+            handoffs: stats.handoffs.iter().map(|h| h.count).sum(),
+            impediments: stats.impediments.iter().map(|i| i.count).sum(),
+            blocked_secs: stats.impediments.iter().map(|i| i.blocked_secs).sum(),
+            completions: stats.completions.values().sum(),
+            artifact_versions: stats.artifact_versions,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ArtifactChange {
+    Same,
+    Changed,
+    OnlyInA,
+    OnlyInB,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactDiff {
+    path: String,
+    change: ArtifactChange,
+    /// Latest version in each run
+    a_version: Option<i64>,
+    b_version: Option<i64>,
+    /// Unified diff of the latest versions, for changed artifacts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diff: Option<String>,
+}
+
+pub async fn diff(client: &HotwiredClient, a: &str, b: &str, output: OutputFormat) {
+    let a_id = resolve_id(client, a).await;
+    let b_id = resolve_id(client, b).await;
+    let result = match compare(client, &a_id, &b_id).await {
+        Ok(result) => result,
+        Err(e) => handle_error(e),
+    };
+
+    if output.print_structured(&result) {
+        return;
+    }
+    print_diff(&result);
+}
+
+async fn compare(client: &HotwiredClient, a_id: &str, b_id: &str) -> Result<RunDiff, IpcError> {
+    let runs = client.list_runs().await?;
+    let a_run = summary_of(client, runs.clone(), a_id).await?;
+    let b_run = summary_of(client, runs, b_id).await?;
+    let now = Utc::now();
+    let a_stats = stats::report_for(client, &a_run, now).await?;
+    let b_stats = stats::report_for(client, &b_run, now).await?;
+
+    let a_latest = latest_versions(client, a_id).await?;
+    let b_latest = latest_versions(client, b_id).await?;
+    let mut paths: Vec<&String> = a_latest.keys().chain(b_latest.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut artifacts = Vec::with_capacity(paths.len());
+    for path in paths {
+        let a_version = a_latest.get(path).copied().flatten();
+        let b_version = b_latest.get(path).copied().flatten();
+        let (change, diff) = match (a_latest.contains_key(path), b_latest.contains_key(path)) {
+            (true, false) => (ArtifactChange::OnlyInA, None),
+            (false, _) => (ArtifactChange::OnlyInB, None),
+            (true, true) => {
+                let old = content(client, a_id, path, a_version).await?;
+                let new = content(client, b_id, path, b_version).await?;
+                let label = |id: &str, version: Option<i64>| match version {
+                    Some(v) => format!("{}/{} (v{})", short_id(id), path, v),
+                    None => format!("{}/{}", short_id(id), path),
+                };
+                match unified(&old, &new, &label(a_id, a_version), &label(b_id, b_version)) {
+                    Some(diff) => (ArtifactChange::Changed, Some(diff)),
+                    None => (ArtifactChange::Same, None),
+                }
+            }
+        };
+        artifacts.push(ArtifactDiff {
+            path: path.clone(),
+            change,
+            a_version,
+            b_version,
+            diff,
+        });
+    }
+
+    Ok(RunDiff {
+        a: RunSide::new(&a_run, &a_stats),
+        b: RunSide::new(&b_run, &b_stats),
+        artifacts,
+    })
+}
+
+/// Path -> latest version of every artifact of a run. `None` when core has
+/// no versions recorded.
+async fn latest_versions(
+    client: &HotwiredClient,
+    run_id: &str,
+) -> Result<BTreeMap<String, Option<i64>>, IpcError> {
+    let mut latest = BTreeMap::new();
+    for artifact in client.artifact_list(run_id).await? {
+        let versions = client
+            .artifact_list_versions(&ArtifactPathParams {
+                run_id: run_id.to_string(),
+                path: artifact.path.clone(),
+            })
+            .await?;
+        latest.insert(artifact.path, versions.iter().map(|v| v.version).max());
+    }
+    Ok(latest)
+}
+
+/// Content of `version` of an artifact; empty when it has none.
+async fn content(
+    client: &HotwiredClient,
+    run_id: &str,
+    path: &str,
+    version: Option<i64>,
+) -> Result<String, IpcError> {
+    let Some(version) = version else {
+        return Ok(String::new());
+    };
+    let content = client
+        .artifact_get_version(&GetVersionParams {
+            run_id: run_id.to_string(),
+            path: path.to_string(),
+            version: version.max(0) as u32,
+        })
+        .await?;
+    Ok(content.content)
+}
+
+/// Unified diff from `old` to `new`, or `None` when they're the same.
+fn unified(old: &str, new: &str, old_label: &str, new_label: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    Some(
+        TextDiff::from_lines(old, new)
+            .unified_diff()
+            .header(old_label, new_label)
+            .to_string(),
+    )
+}
+
+fn print_diff(result: &RunDiff) {
+    let (a, b) = (&result.a, &result.b);
+    let row = |label: &str, a: String, b: String| println!("{:<18} {:<30} {}", label, a, b);
+    let secs = |s: i64| format_duration(Duration::seconds(s));
+
+    row(
+        "",
+        short_id(&a.run_id).to_string(),
+        short_id(&b.run_id).to_string(),
+    );
+    let playbook = |side: &RunSide| side.playbook.clone().unwrap_or_else(|| "-".to_string());
+    row("Playbook", playbook(a), playbook(b));
+    row("Status", a.status.clone(), b.status.clone());
+    let phases = |side: &RunSide| {
+        if side.phases.is_empty() {
+            "-".to_string()
+        } else {
+            side.phases.join(" > ")
+        }
+    };
+    row("Phases", phases(a), phases(b));
+    row("Duration", secs(a.duration_secs), secs(b.duration_secs));
+    row("Handoffs", a.handoffs.to_string(), b.handoffs.to_string());
+    let impediments = |side: &RunSide| {
+        if side.impediments == 0 {
+            "0".to_string()
+        } else {
+            format!("{} ({} blocked)", side.impediments, secs(side.blocked_secs))
+        }
+    };
+    row("Impediments", impediments(a), impediments(b));
+    row(
+        "Completed tasks",
+        a.completions.to_string(),
+        b.completions.to_string(),
+    );
+    row(
+        "Artifact versions",
+        a.artifact_versions.to_string(),
+        b.artifact_versions.to_string(),
+    );
+
+    println!();
+    println!("Artifacts:");
+    if result.artifacts.is_empty() {
+        println!("  -");
+    }
+    let version = |v: Option<i64>| v.map_or("-".to_string(), |v| format!("v{}", v));
+    for artifact in &result.artifacts {
+        let change = match artifact.change {
+            ArtifactChange::Same => "same".to_string(),
+            ArtifactChange::Changed => "changed".to_string(),
+            ArtifactChange::OnlyInA => format!("only in {}", short_id(&a.run_id)),
+            ArtifactChange::OnlyInB => format!("only in {}", short_id(&b.run_id)),
+        };
+        println!(
+            "  {:<40} {:>5} {:>5}  {}",
+            artifact.path,
+            version(artifact.a_version),
+            version(artifact.b_version),
+            change
+        );
+    }
+
+    for diff in result.artifacts.iter().filter_map(|a| a.diff.as_deref()) {
+        println!();
+        print!("{}", diff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_of_changed_content() {
+        assert_eq!(unified("same\n", "same\n", "a", "b"), None);
+        let diff = unified(
+            "# Plan\nStep 1\n",
+            "# Plan\nStep 2\n",
+            "a/PLAN.md",
+            "b/PLAN.md",
+        )
+        .unwrap();
+        assert_eq!(
+            diff,
+            "--- a/PLAN.md\n+++ b/PLAN.md\n@@ -1,2 +1,2 @@\n # Plan\n-Step 1\n+Step 2\n"
+        );
+    }
+}
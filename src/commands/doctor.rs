@@ -0,0 +1,519 @@
+//! End-to-end diagnostics
+//!
+//! `doctor` walks the same chain a workflow command depends on — backend,
+//! versions, terminal identity, session registration, run attachment, auth
+//! token and Claude Code hooks — and prints a concrete fix for every link
+//! that's broken. Later checks are skipped when an earlier one makes them
+//! meaningless (no terminal means no session to look up).
+
+use super::auth::{self, TokenSource};
+use super::run::short_id;
+use super::{exit_code, OutputFormat};
+use crate::config::Context;
+use crate::ipc::{self, ErrorCode, HotwiredClient, IpcError};
+use crate::models::SessionStateParams;
+use hotwired_client::session::TERMINAL_RUN_STATUSES;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Hooks that must be installed for session tracking to work
+const REQUIRED_HOOKS: &[(&str, &str)] = &[
+    ("SessionStart", "internal session-start"),
+    ("SessionEnd", "internal session-end"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Ok,
+    Warn,
+    Fail,
+    Skip,
+}
+
+/// One diagnostic result
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Check {
+    name: &'static str,
+    outcome: Outcome,
+    detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn skip(name: &'static str, reason: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome: Outcome::Skip,
+            detail: reason.into(),
+            fix: None,
+        }
+    }
+}
+
+/// Structured result of `doctor`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Report {
+    healthy: bool,
+    checks: Vec<Check>,
+}
+
+pub async fn run(client: &HotwiredClient, context: &Context, output: OutputFormat) {
+    let mut checks = Vec::new();
+
+    // Backend and versions
+    let backend_up = match client.health_check().await {
+        Ok(ping) => {
+            checks.push(Check::ok(
+                "backend",
+                format!(
+                    "running (v{}) at {}",
+                    ping.version.as_deref().unwrap_or("?"),
+                    client.socket_path()
+                ),
+            ));
+            true
+        }
+        Err(e) => {
+            checks.push(backend_failure(client, &e));
+            false
+        }
+    };
+    checks.push(if backend_up {
+        check_versions(client).await
+    } else {
+        Check::skip("versions", "backend not reachable")
+    });
+
+    // Terminal identity, registration, attachment
+    let terminal = client.terminal();
+    checks.push(match &terminal {
+        Some(t) => Check::ok("terminal", format!("{} ({})", t.session_key, t.multiplexer)),
+        None => Check::fail(
+            "terminal",
+            "no Zellij, tmux or WezTerm session detected",
+            "Run inside a Hotwired-managed terminal, or pass --session <NAME> / set HOTWIRED_SESSION",
+        ),
+    });
+
+    match (&terminal, backend_up) {
+        (Some(t), true) => check_session(client, &t.session_key, &mut checks).await,
+        (None, _) => {
+            checks.push(Check::skip("registered", "no terminal session"));
+            checks.push(Check::skip("run", "no terminal session"));
+        }
+        (_, false) => {
+            checks.push(Check::skip("registered", "backend not reachable"));
+            checks.push(Check::skip("run", "backend not reachable"));
+        }
+    }
+
+    // Local configuration
+    checks.push(check_token(context));
+    checks.push(check_hooks(&project_dir()));
+
+    let healthy = checks.iter().all(|c| c.outcome != Outcome::Fail);
+    let report = Report { healthy, checks };
+
+    if !output.print_structured(&report) {
+        for check in &report.checks {
+            let label = match check.outcome {
+                Outcome::Ok => "ok  ",
+                Outcome::Warn => "WARN",
+                Outcome::Fail => "FAIL",
+                Outcome::Skip => "skip",
+            };
+            println!("[{}] {:<11} {}", label, check.name, check.detail);
+            if let Some(fix) = &check.fix {
+                for (i, line) in fix.lines().enumerate() {
+                    let label = if i == 0 { "fix:" } else { "" };
+                    println!("              {:<4} {}", label, line);
+                }
+            }
+        }
+        println!();
+        if healthy {
+            println!("All checks passed.");
+        } else {
+            println!("Some checks failed; apply the fixes above and re-run `hotwired doctor`.");
+        }
+    }
+
+    if !healthy {
+        std::process::exit(exit_code::FAILURE);
+    }
+}
+
+fn backend_failure(client: &HotwiredClient, e: &IpcError) -> Check {
+    match e {
+        IpcError::NotConnected(_) => Check::fail(
+            "backend",
+            format!("not running (no socket at {})", client.socket_path()),
+            "Start the Hotwired desktop app, or point --socket-path at its socket",
+        ),
+        IpcError::UntrustedSocket { .. } => Check::fail(
+            "backend",
+            e.to_string(),
+            "Remove the socket if you didn't create it; pass --allow-untrusted-socket only if core deliberately runs as another user",
+        ),
+        IpcError::Timeout(_) => Check::fail(
+            "backend",
+            e.to_string(),
+            "Restart the Hotwired desktop app; hotwired-core accepted the connection but never answered",
+        ),
+        _ => Check::fail(
+            "backend",
+            e.to_string(),
+            "Restart the Hotwired desktop app; if this persists, remove the stale socket and relaunch",
+        ),
+    }
+}
+
+async fn check_versions(client: &HotwiredClient) -> Check {
+    let cli = env!("CARGO_PKG_VERSION");
+    match client.capabilities().await {
+        Ok(caps) => {
+            let core = caps.core_version.as_deref().unwrap_or("?");
+            let detail = format!(
+                "cli {} (protocol v{}), core {} (protocol v{})",
+                cli,
+                ipc::PROTOCOL_VERSION,
+                core,
+                caps.protocol_version
+            );
+            if caps.protocol_version < ipc::PROTOCOL_VERSION {
+                Check::warn(
+                    "versions",
+                    detail,
+                    "Update the Hotwired desktop app; newer commands (e.g. `inbox --watch` push) fall back or fail",
+                )
+            } else if caps.protocol_version > ipc::PROTOCOL_VERSION {
+                Check::warn(
+                    "versions",
+                    detail,
+                    "Update the CLI: npm install -g @hotwired-sh/hotwired-cli",
+                )
+            } else {
+                Check::ok("versions", detail)
+            }
+        }
+        Err(e) => Check::fail(
+            "versions",
+            format!("handshake failed: {}", e),
+            "Restart the Hotwired desktop app",
+        ),
+    }
+}
+
+/// `registered` and `run` checks for an identified terminal
+async fn check_session(client: &HotwiredClient, session_key: &str, checks: &mut Vec<Check>) {
+    let state = match client
+        .get_session_state(&SessionStateParams {
+            zellij_session: session_key.to_string(),
+        })
+        .await
+    {
+        Ok(Some(state)) => state,
+        Ok(None)
+        | Err(IpcError::Backend {
+            code: ErrorCode::NotFound | ErrorCode::Unknown,
+            ..
+        }) => {
+            checks.push(Check::fail(
+                "registered",
+                format!("hotwired-core doesn't know session '{}'", session_key),
+                format!(
+                    "Start Claude Code in this terminal with the Hotwired hooks installed (see `hooks`),\n\
+                     or register it by hand: hotwired --session {} session register -p <PROJECT_DIR>",
+                    session_key
+                ),
+            ));
+            checks.push(Check::skip("run", "session not registered"));
+            return;
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                "registered",
+                e.to_string(),
+                "Check the backend, then re-run doctor",
+            ));
+            checks.push(Check::skip("run", "session state unavailable"));
+            return;
+        }
+    };
+    checks.push(Check::ok(
+        "registered",
+        format!("session '{}' is registered", session_key),
+    ));
+
+    let Some(run_id) = state.attached_run_id.filter(|id| !id.is_empty()) else {
+        checks.push(Check::fail(
+            "run",
+            "session is not attached to a run",
+            "Join a run: hotwired pair <RUN_ID>\nor start one: hotwired hotwire --intent \"...\"",
+        ));
+        return;
+    };
+    let role = state.role_id.unwrap_or_else(|| "unknown".into());
+
+    // Prefer the run's own status; session state can lag behind
+    let status = match client.get_run_status(&run_id).await {
+        Ok(run) => run.status,
+        Err(_) => state.run_status.unwrap_or_else(|| "unknown".into()),
+    };
+    let detail = format!(
+        "attached to {} as {} (status: {})",
+        short_id(&run_id),
+        role,
+        status
+    );
+    checks.push(if TERMINAL_RUN_STATUSES.contains(&status.as_str()) {
+        Check::fail(
+            "run",
+            detail,
+            "The run has ended; join an active one with: hotwired pair <RUN_ID>",
+        )
+    } else {
+        Check::ok("run", detail)
+    });
+}
+
+/// The token this invocation would send, from the same source `auth status`
+/// reports.
+fn check_token(context: &Context) -> Check {
+    let source = TokenSource::resolve(context);
+    let described = source.describe();
+    match (&source, source.state()) {
+        (TokenSource::File(path), _) if path.as_os_str().is_empty() => Check::warn(
+            "token",
+            "cannot locate home directory",
+            "Set $HOME so the CLI can find ~/.hotwired/auth_token",
+        ),
+        (TokenSource::Env(var), "not_configured") => Check::warn(
+            "token",
+            format!("${} is not set", var),
+            format!(
+                "Export {} in this shell, or give the context a token-file",
+                var
+            ),
+        ),
+        (TokenSource::Env(var), "empty") => Check::warn(
+            "token",
+            format!("${} is empty", var),
+            format!("Set {} to the token from the Hotwired desktop app", var),
+        ),
+        (TokenSource::Env(_), _) => Check::ok("token", described),
+        (TokenSource::File(path), "not_configured") => Check::warn(
+            "token",
+            format!("no token at {}", path.display()),
+            "Sign in from the Hotwired desktop app, or pipe a token to `hotwired auth token set`",
+        ),
+        (TokenSource::File(path), state) => {
+            if let Some(mode) = auth::exposed_mode(path) {
+                return Check::fail(
+                    "token",
+                    format!(
+                        "{} is readable by other users (mode {:o})",
+                        path.display(),
+                        mode
+                    ),
+                    format!("chmod 600 {}", path.display()),
+                );
+            }
+            if state == "empty" {
+                return Check::warn(
+                    "token",
+                    format!("{} is empty", path.display()),
+                    "Sign in again from the Hotwired desktop app",
+                );
+            }
+            Check::ok("token", described)
+        }
+    }
+}
+
+/// Project Claude Code runs hooks for
+fn project_dir() -> PathBuf {
+    std::env::var("CLAUDE_PROJECT_DIR")
+        .map(PathBuf::from)
+        .or_else(|_| std::env::current_dir())
+        .unwrap_or_default()
+}
+
+/// Settings files Claude Code reads hooks from for `project`, most specific first
+fn hook_sources(project: &Path) -> Vec<PathBuf> {
+    let mut sources = Vec::new();
+    for dir in project.ancestors() {
+        sources.push(dir.join(".claude").join("settings.local.json"));
+        sources.push(dir.join(".claude").join("settings.json"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        sources.push(home.join(".claude").join("settings.json"));
+        collect_plugin_hooks(&home.join(".claude").join("plugins"), 4, &mut sources);
+    }
+    sources.retain(|p| p.is_file());
+    sources.dedup();
+    sources
+}
+
+/// Plugins ship their hooks as `hooks/hooks.json` somewhere under the plugins dir.
+fn collect_plugin_hooks(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() && depth > 0 {
+            collect_plugin_hooks(&path, depth - 1, out);
+        } else if path.file_name().is_some_and(|n| n == "hooks.json") {
+            out.push(path);
+        }
+    }
+}
+
+/// `(event, command)` for every command hook in a settings/hooks.json document
+fn hook_commands(settings: &Value) -> Vec<(String, String)> {
+    let Some(hooks) = settings.get("hooks").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let mut commands = Vec::new();
+    for (event, matchers) in hooks {
+        for matcher in matchers.as_array().into_iter().flatten() {
+            for hook in matcher
+                .get("hooks")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(cmd) = hook.get("command").and_then(Value::as_str) {
+                    commands.push((event.clone(), cmd.to_string()));
+                }
+            }
+        }
+    }
+    commands
+}
+
+/// Required hook events with no hotwired command installed
+fn missing_hooks(commands: &[(String, String)]) -> Vec<&'static str> {
+    REQUIRED_HOOKS
+        .iter()
+        .filter(|(event, needle)| {
+            !commands
+                .iter()
+                .any(|(e, cmd)| e == event && cmd.contains(needle))
+        })
+        .map(|(event, _)| *event)
+        .collect()
+}
+
+fn check_hooks(project: &Path) -> Check {
+    let sources = hook_sources(project);
+    let commands: Vec<(String, String)> = sources
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|raw| serde_json::from_str::<Value>(&raw).ok())
+        .flat_map(|settings| hook_commands(&settings))
+        .collect();
+
+    let missing = missing_hooks(&commands);
+    if missing.is_empty() {
+        return Check::ok(
+            "hooks",
+            format!("session hooks installed for {}", project.display()),
+        );
+    }
+
+    let snippet = missing
+        .iter()
+        .map(|event| {
+            let (_, cmd) = REQUIRED_HOOKS.iter().find(|(e, _)| e == event).unwrap();
+            format!(
+                "  \"{}\": [{{\"hooks\": [{{\"type\": \"command\", \"command\": \"hotwired {}\"}}]}}]",
+                event, cmd
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+    Check::fail(
+        "hooks",
+        format!(
+            "missing {} hook(s) for {}",
+            missing.join(", "),
+            project.display()
+        ),
+        format!(
+            "Add to {} under \"hooks\":\n{}",
+            project.join(".claude").join("settings.json").display(),
+            snippet
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hook_commands_flattens_matchers() {
+        let settings = json!({"hooks": {
+            "SessionStart": [{"hooks": [{"type": "command", "command": "hotwired internal session-start"}]}],
+            "Stop": [{"matcher": "", "hooks": [
+                {"type": "command", "command": "hotwired internal hook-event stop"},
+                {"type": "command", "command": "say done"},
+            ]}],
+        }});
+        let commands = hook_commands(&settings);
+        assert_eq!(commands.len(), 3);
+        assert_eq!(missing_hooks(&commands), vec!["SessionEnd"]);
+    }
+
+    #[test]
+    fn test_missing_hooks_requires_matching_event() {
+        // Right command under the wrong event doesn't count
+        let commands = vec![
+            (
+                "Stop".to_string(),
+                "hotwired internal session-start".to_string(),
+            ),
+            (
+                "SessionEnd".to_string(),
+                "hotwired internal session-end".to_string(),
+            ),
+        ];
+        assert_eq!(missing_hooks(&commands), vec!["SessionStart"]);
+        assert!(hook_commands(&json!({})).is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! Process exit codes
+//!
+//! Scripts driving agents branch on *why* a command failed, so every failure
+//! class gets its own stable code. Keep this table in sync with `EXIT_CODES_HELP`.
+
+use crate::ipc::{ErrorCode, IpcError};
+
+/// Unclassified failure
+pub const FAILURE: i32 = 1;
+/// Invalid command-line usage (mostly emitted by clap itself)
+pub const USAGE: i32 = 2;
+/// Backend not running or the socket refused the connection
+pub const NOT_CONNECTED: i32 = 3;
+/// Backend didn't answer within `--timeout`
+pub const TIMEOUT: i32 = 4;
+/// Run, session, artifact, comment or file doesn't exist
+pub const NOT_FOUND: i32 = 5;
+/// Short ID prefix matches more than one item
+pub const AMBIGUOUS: i32 = 6;
+/// Core rejected the caller (bad token, role lacks permission), or the
+/// socket isn't trusted with the token
+pub const PERMISSION_DENIED: i32 = 7;
+/// Change conflicts with the current state (e.g. already exists)
+pub const CONFLICT: i32 = 8;
+/// Operation not valid in the run's current state
+pub const INVALID_STATE: i32 = 9;
+/// Core rejected the request parameters
+pub const INVALID_PARAMS: i32 = 10;
+/// Core is too old for this command
+pub const UNSUPPORTED: i32 = 11;
+/// Backend returned something the CLI couldn't parse
+pub const PROTOCOL_ERROR: i32 = 12;
+
+/// Not running inside a terminal session the CLI can identify
+pub const NO_SESSION: i32 = 20;
+/// Session isn't registered with Hotwired
+pub const SESSION_NOT_REGISTERED: i32 = 21;
+/// Session isn't attached to a run
+pub const NOT_ATTACHED: i32 = 22;
+/// Attached run is completed, failed, cancelled or archived
+pub const RUN_NOT_ACTIVE: i32 = 23;
+
+/// Shown at the bottom of `--help`
+pub const EXIT_CODES_HELP: &str = "\
+Exit codes:
+   0  success
+   1  unclassified failure
+   2  invalid command-line usage
+   3  backend not running / connection failed
+   4  backend timed out
+   5  not found
+   6  ambiguous ID prefix
+   7  permission denied / unauthorized
+   8  conflict
+   9  invalid state for this operation
+  10  invalid parameters
+  11  backend too old for this command
+  12  unparseable backend response
+  20  not in a terminal session
+  21  session not registered
+  22  session not attached to a run
+  23  attached run is no longer active";
+
+/// Exit code for a backend error class
+pub fn for_error_code(code: ErrorCode) -> i32 {
+    match code {
+        ErrorCode::NotFound => NOT_FOUND,
+        ErrorCode::Ambiguous => AMBIGUOUS,
+        ErrorCode::PermissionDenied | ErrorCode::Unauthorized => PERMISSION_DENIED,
+        ErrorCode::Conflict => CONFLICT,
+        ErrorCode::InvalidState => INVALID_STATE,
+        ErrorCode::InvalidParams => INVALID_PARAMS,
+        ErrorCode::UnknownMethod => UNSUPPORTED,
+        ErrorCode::Internal | ErrorCode::Unknown => FAILURE,
+    }
+}
+
+/// Exit code for any IPC failure
+pub fn for_ipc_error(e: &IpcError) -> i32 {
+    match e {
+        IpcError::NotConnected(_) | IpcError::ConnectionFailed(_) => NOT_CONNECTED,
+        IpcError::Timeout(_) => TIMEOUT,
+        IpcError::RequestFailed(_) => FAILURE,
+        IpcError::InvalidResponse(_) => PROTOCOL_ERROR,
+        IpcError::Backend { code, .. } => for_error_code(*code),
+        IpcError::Unsupported { .. } => UNSUPPORTED,
+        IpcError::UntrustedSocket { .. } => PERMISSION_DENIED,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_codes_map_to_distinct_exit_codes() {
+        let codes = [
+            ErrorCode::NotFound,
+            ErrorCode::Ambiguous,
+            ErrorCode::PermissionDenied,
+            ErrorCode::Conflict,
+            ErrorCode::InvalidState,
+            ErrorCode::InvalidParams,
+        ];
+        let mut exits: Vec<i32> = codes.iter().map(|c| for_error_code(*c)).collect();
+        exits.sort();
+        exits.dedup();
+        assert_eq!(exits.len(), codes.len());
+    }
+
+    #[test]
+    fn test_unclassified_backend_error_is_generic_failure() {
+        let err = IpcError::Backend {
+            code: ErrorCode::Unknown,
+            message: "boom".into(),
+        };
+        assert_eq!(for_ipc_error(&err), FAILURE);
+    }
+}
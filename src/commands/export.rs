@@ -0,0 +1,197 @@
+//! Export and import complete runs
+//!
+//! `run export` collects everything core holds for a run (status, the full
+//! event log, every artifact version and every comment thread) into one
+//! archive file, see `archive`. `run import` hands such an archive to a core,
+//! possibly on another machine, to recreate the run.
+
+use super::run::{resolve_id, short_id};
+use super::{exit_code, handle_error, timeline, OutputFormat};
+use crate::archive::{self, ArchiveError};
+use crate::ipc::{HotwiredClient, IpcError};
+use crate::models::{
+    ArchivedArtifact, ArchivedVersion, ArtifactPathParams, CommentThread, GetCommentParams,
+    GetVersionParams, ImportRunParams, ListCommentsParams, RunArchive,
+};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Structured result of `run export` and `run import`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveSummary {
+    run_id: String,
+    path: PathBuf,
+    /// Id of the run as imported, when `run import` got a new one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    imported_as: Option<String>,
+    events: usize,
+    artifacts: usize,
+    versions: usize,
+    comment_threads: usize,
+}
+
+impl ArchiveSummary {
+    fn new(archive: &RunArchive, path: &Path) -> Self {
+        Self {
+            run_id: archive.run.run_id.clone(),
+            path: path.to_path_buf(),
+            imported_as: None,
+            events: archive.events.len(),
+            artifacts: archive.artifacts.len(),
+            versions: archive.artifacts.iter().map(|a| a.versions.len()).sum(),
+            comment_threads: archive.artifacts.iter().map(|a| a.comments.len()).sum(),
+        }
+    }
+
+    /// "42 events, 3 artifacts (7 versions), 5 comment threads"
+    fn contents(&self) -> String {
+        format!(
+            "{} events, {} artifacts ({} versions), {} comment threads",
+            self.events, self.artifacts, self.versions, self.comment_threads
+        )
+    }
+}
+
+pub async fn export(
+    client: &HotwiredClient,
+    id: &str,
+    path: Option<PathBuf>,
+    output: OutputFormat,
+) {
+    let full_id = resolve_id(client, id).await;
+    let archive = match collect(client, &full_id).await {
+        Ok(archive) => archive,
+        Err(e) => handle_error(e),
+    };
+
+    let path = path.unwrap_or_else(|| PathBuf::from(format!("run-{}.tar.gz", short_id(&full_id))));
+    if let Err(e) = archive::write(&archive, &path) {
+        handle_archive_error(e);
+    }
+
+    let summary = ArchiveSummary::new(&archive, &path);
+    if output.print_structured(&summary) {
+        return;
+    }
+    println!("Exported run {} to {}", short_id(&full_id), path.display());
+    println!("  {}", summary.contents());
+}
+
+pub async fn import(client: &HotwiredClient, path: &Path, output: OutputFormat) {
+    let archive = match archive::read(path) {
+        Ok(archive) => archive,
+        Err(e) => handle_archive_error(e),
+    };
+
+    let result = match client
+        .import_run(&ImportRunParams { archive: &archive })
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => handle_error(e),
+    };
+
+    let mut summary = ArchiveSummary::new(&archive, path);
+    summary.imported_as = Some(result.run_id.clone());
+    if output.print_structured(&summary) {
+        return;
+    }
+    if result.run_id == archive.run.run_id {
+        println!("Imported run {}", short_id(&result.run_id));
+    } else {
+        println!(
+            "Imported run {} as {}",
+            short_id(&archive.run.run_id),
+            short_id(&result.run_id)
+        );
+    }
+    println!("  {}", summary.contents());
+}
+
+/// Gather everything core holds for `run_id`.
+async fn collect(client: &HotwiredClient, run_id: &str) -> Result<RunArchive, IpcError> {
+    let run = client.get_run_status(run_id).await?;
+    let summary = client
+        .list_runs()
+        .await?
+        .into_iter()
+        .find(|r| r.id == run_id);
+    let events = timeline::fetch_all_events(client, run_id).await?;
+
+    let mut artifacts = Vec::new();
+    for artifact in client.artifact_list(run_id).await? {
+        let path_params = ArtifactPathParams {
+            run_id: run_id.to_string(),
+            path: artifact.path.clone(),
+        };
+
+        let mut history = client.artifact_list_versions(&path_params).await?;
+        history.sort_by_key(|v| v.version);
+        let mut versions = Vec::with_capacity(history.len());
+        for version in history {
+            let content = client
+                .artifact_get_version(&GetVersionParams {
+                    run_id: run_id.to_string(),
+                    path: artifact.path.clone(),
+                    version: version.version.max(0) as u32,
+                })
+                .await?;
+            versions.push(ArchivedVersion {
+                version: version.version,
+                timestamp: version.timestamp.or(content.timestamp),
+                lines_added: version.lines_added,
+                lines_removed: version.lines_removed,
+                file: None,
+                content: content.content,
+            });
+        }
+
+        let comments = client
+            .artifact_list_comments(&ListCommentsParams {
+                run_id: run_id.to_string(),
+                path: artifact.path.clone(),
+                status_filter: "all".to_string(),
+            })
+            .await?;
+        let mut threads: Vec<CommentThread> = Vec::new();
+        // Replies come back with their thread; fetch each thread once
+        for comment in comments.iter().filter(|c| c.thread_id.is_none()) {
+            threads.push(
+                client
+                    .artifact_get_comment(&GetCommentParams {
+                        run_id: run_id.to_string(),
+                        comment_id: comment.comment_id.clone(),
+                    })
+                    .await?,
+            );
+        }
+
+        artifacts.push(ArchivedArtifact {
+            path: artifact.path,
+            title: artifact.title,
+            versions,
+            comments: threads,
+        });
+    }
+
+    Ok(RunArchive {
+        manifest: archive::manifest(run_id),
+        run,
+        summary,
+        events,
+        artifacts,
+    })
+}
+
+fn handle_archive_error(e: ArchiveError) -> ! {
+    eprintln!("error: {}", e);
+    let code = match &e {
+        ArchiveError::Io { source, .. } if source.kind() == std::io::ErrorKind::NotFound => {
+            exit_code::NOT_FOUND
+        }
+        ArchiveError::Io { .. } => exit_code::FAILURE,
+        ArchiveError::Invalid { .. } => exit_code::INVALID_PARAMS,
+    };
+    std::process::exit(code);
+}
@@ -0,0 +1,295 @@
+//! Filters, sorting and paging for `run list` and `session list`
+//!
+//! Core returns everything; the CLI narrows it down. Sessions have no phase,
+//! playbook or creation time of their own, so those filters (and
+//! `--sort created`) apply to the run a session is attached to.
+
+use super::time;
+use crate::config;
+use crate::models::{ActiveSession, RunSummary};
+use chrono::{DateTime, Utc};
+use hotwired_client::session::TERMINAL_RUN_STATUSES;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Newest first
+    Created,
+    /// Runs: live statuses first, then alphabetically; newest first within
+    /// each. Sessions: alphabetically
+    Status,
+}
+
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct ListFilters {
+    /// Only these statuses (repeat or comma-separate: --status active,blocked)
+    #[arg(long, value_delimiter = ',')]
+    pub status: Vec<String>,
+
+    /// Only runs in this phase
+    #[arg(long)]
+    pub phase: Option<String>,
+
+    /// Only runs of this playbook (case-insensitive substring of its name)
+    #[arg(long)]
+    pub playbook: Option<String>,
+
+    /// Only runs in this project directory or below it
+    #[arg(long, value_parser = parse_project)]
+    pub project: Option<PathBuf>,
+
+    /// Only runs created at/after this date, timestamp or age (7d, 12h)
+    #[arg(long, value_parser = time::parse_instant, value_name = "WHEN")]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only runs created before this date, timestamp or age
+    #[arg(long, value_parser = time::parse_instant, value_name = "WHEN")]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Sort order (runs default to created)
+    #[arg(long, value_enum)]
+    pub sort: Option<SortKey>,
+
+    /// Show at most this many
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Skip this many (after filtering and sorting)
+    #[arg(long, default_value_t = 0)]
+    pub offset: usize,
+}
+
+/// Absolute form of a `--project` argument, so `--project .` works.
+fn parse_project(s: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(config::expand_home(s));
+    let path = if path.is_absolute() {
+        path
+    } else {
+        std::env::current_dir()
+            .map_err(|e| e.to_string())?
+            .join(path)
+    };
+    Ok(path.canonicalize().unwrap_or(path))
+}
+
+fn eq_ignore_case(a: Option<&str>, b: &str) -> bool {
+    a.is_some_and(|a| a.eq_ignore_ascii_case(b))
+}
+
+fn created(run: &RunSummary) -> Option<DateTime<Utc>> {
+    run.created_at.as_deref().and_then(time::parse_timestamp)
+}
+
+fn in_project(dir: Option<&str>, project: &Path) -> bool {
+    dir.is_some_and(|d| Path::new(d).starts_with(project))
+}
+
+impl ListFilters {
+    /// Whether any filter needs the attached run of a session.
+    pub fn needs_runs(&self) -> bool {
+        self.phase.is_some()
+            || self.playbook.is_some()
+            || self.since.is_some()
+            || self.until.is_some()
+            || self.sort.is_some()
+    }
+
+    pub fn matches_run(&self, run: &RunSummary) -> bool {
+        (self.status.is_empty()
+            || self
+                .status
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(&run.status)))
+            && self.matches_run_details(run)
+            && self
+                .project
+                .as_deref()
+                .is_none_or(|p| in_project(run.project_path.as_deref(), p))
+    }
+
+    /// Phase, playbook and creation time
+    fn matches_run_details(&self, run: &RunSummary) -> bool {
+        let phase = self
+            .phase
+            .as_deref()
+            .is_none_or(|p| eq_ignore_case(run.phase.as_deref(), p));
+        let playbook = self.playbook.as_deref().is_none_or(|p| {
+            let p = p.to_lowercase();
+            [&run.template_name, &run.template_id].iter().any(|name| {
+                name.as_deref()
+                    .is_some_and(|n| n.to_lowercase().contains(&p))
+            })
+        });
+        let window = match (self.since, self.until) {
+            (None, None) => true,
+            (since, until) => created(run)
+                .is_some_and(|c| since.is_none_or(|s| c >= s) && until.is_none_or(|u| c < u)),
+        };
+        phase && playbook && window
+    }
+
+    /// `run` is the session's attached run, if it has one.
+    pub fn matches_session(&self, session: &ActiveSession, run: Option<&RunSummary>) -> bool {
+        let status = self.status.is_empty()
+            || self
+                .status
+                .iter()
+                .any(|s| eq_ignore_case(session.session_status.as_deref(), s));
+        let project = self
+            .project
+            .as_deref()
+            .is_none_or(|p| in_project(session.project_dir.as_deref(), p));
+        let run_filtered = self.phase.is_some()
+            || self.playbook.is_some()
+            || self.since.is_some()
+            || self.until.is_some();
+        let run = !run_filtered || run.is_some_and(|r| self.matches_run_details(r));
+        status && project && run
+    }
+
+    /// Sort runs (default: newest first), then apply offset and limit.
+    pub fn apply_to_runs(&self, mut runs: Vec<RunSummary>) -> Vec<RunSummary> {
+        runs.retain(|r| self.matches_run(r));
+        let newest_first = |a: &RunSummary, b: &RunSummary| created(b).cmp(&created(a));
+        match self.sort.unwrap_or(SortKey::Created) {
+            SortKey::Created => runs.sort_by(newest_first),
+            SortKey::Status => runs.sort_by(|a, b| {
+                status_order(&a.status, &b.status).then_with(|| newest_first(a, b))
+            }),
+        }
+        self.page(runs)
+    }
+
+    /// Filter sessions, sorting only when asked (core's order otherwise).
+    pub fn apply_to_sessions(
+        &self,
+        sessions: Vec<ActiveSession>,
+        runs: &[RunSummary],
+    ) -> Vec<ActiveSession> {
+        let run_of = |s: &ActiveSession| {
+            s.attached_run_id
+                .as_deref()
+                .and_then(|id| runs.iter().find(|r| r.id == id))
+        };
+        let mut sessions: Vec<ActiveSession> = sessions
+            .into_iter()
+            .filter(|s| self.matches_session(s, run_of(s)))
+            .collect();
+        match self.sort {
+            // Sessions without a run sort last
+            Some(SortKey::Created) => {
+                sessions.sort_by_key(|s| std::cmp::Reverse(run_of(s).and_then(created)))
+            }
+            // Session states aren't run states, so there's no live/finished split
+            Some(SortKey::Status) => {
+                sessions.sort_by(|a, b| a.session_status.cmp(&b.session_status))
+            }
+            None => {}
+        }
+        self.page(sessions)
+    }
+
+    fn page<T>(&self, items: Vec<T>) -> Vec<T> {
+        items
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+/// Live statuses before finished ones, then alphabetical.
+fn status_order(a: &str, b: &str) -> Ordering {
+    let finished = |s: &str| TERMINAL_RUN_STATUSES.contains(&s);
+    finished(a).cmp(&finished(b)).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: &str, status: &str, created_at: &str) -> RunSummary {
+        RunSummary {
+            id: id.to_string(),
+            status: status.to_string(),
+            phase: Some("executing".to_string()),
+            template_name: Some("Plan > Build".to_string()),
+            template_id: None,
+            project_path: Some("/work/app".to_string()),
+            created_at: Some(created_at.to_string()),
+        }
+    }
+
+    fn ids(runs: &[RunSummary]) -> Vec<&str> {
+        runs.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    fn runs() -> Vec<RunSummary> {
+        vec![
+            run("old", "completed", "2024-01-01T00:00:00Z"),
+            run("new", "active", "2024-03-01T00:00:00Z"),
+            run("mid", "blocked", "2024-02-01T00:00:00Z"),
+        ]
+    }
+
+    #[test]
+    fn test_runs_default_to_newest_first() {
+        let filters = ListFilters::default();
+        assert_eq!(ids(&filters.apply_to_runs(runs())), ["new", "mid", "old"]);
+    }
+
+    #[test]
+    fn test_status_sort_puts_finished_runs_last() {
+        let filters = ListFilters {
+            sort: Some(SortKey::Status),
+            ..Default::default()
+        };
+        assert_eq!(ids(&filters.apply_to_runs(runs())), ["new", "mid", "old"]);
+    }
+
+    #[test]
+    fn test_filters_and_paging() {
+        let filters = ListFilters {
+            status: vec!["ACTIVE".into(), "blocked".into()],
+            playbook: Some("plan".into()),
+            project: Some(PathBuf::from("/work")),
+            since: time::parse_instant("2024-01-15").ok(),
+            ..Default::default()
+        };
+        assert_eq!(ids(&filters.apply_to_runs(runs())), ["new", "mid"]);
+
+        let page = ListFilters {
+            offset: 1,
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(ids(&page.apply_to_runs(runs())), ["mid"]);
+
+        let elsewhere = ListFilters {
+            project: Some(PathBuf::from("/work/ap")),
+            ..Default::default()
+        };
+        assert!(elsewhere.apply_to_runs(runs()).is_empty());
+    }
+
+    #[test]
+    fn test_status_sort_orders_sessions_alphabetically() {
+        let sessions: Vec<ActiveSession> = ["idle", "completed", "active"]
+            .iter()
+            .map(|status| {
+                serde_json::from_value(serde_json::json!({
+                    "sessionName": status, "sessionStatus": status,
+                }))
+                .unwrap()
+            })
+            .collect();
+        let filters = ListFilters {
+            sort: Some(SortKey::Status),
+            ..Default::default()
+        };
+        let sorted = filters.apply_to_sessions(sessions, &[]);
+        let names: Vec<&str> = sorted.iter().map(|s| s.session_name.as_str()).collect();
+        assert_eq!(names, ["active", "completed", "idle"]);
+    }
+}
@@ -3,8 +3,9 @@
 //! The `hotwire` command initializes a new Hotwired workflow. This is one of the
 //! few commands that does NOT require an existing session - it creates one.
 
-use super::handle_error;
+use super::{handle_error, OutputFormat};
 use crate::ipc::HotwiredClient;
+use crate::models::HotwireParams;
 use std::path::PathBuf;
 
 pub async fn run(
@@ -12,12 +13,14 @@ pub async fn run(
     playbook: Option<String>,
     intent: Option<String>,
     project: Option<PathBuf>,
+    output: OutputFormat,
 ) {
     // hotwire does NOT require existing session - it creates one
-    let zellij_session = std::env::var("ZELLIJ_SESSION_NAME").ok();
+    let terminal = client.terminal();
 
-    if zellij_session.is_none() {
-        eprintln!("WARNING: Not running in a Zellij session.");
+    if terminal.is_none() {
+        // Warnings go to stderr, so they never corrupt structured output
+        eprintln!("WARNING: Not running in a Zellij, tmux or WezTerm session.");
         eprintln!("The run will start but this terminal won't be attached.");
         eprintln!();
     }
@@ -27,60 +30,41 @@ pub async fn run(
         .map(|p| p.to_string_lossy().to_string());
 
     match client
-        .request(
-            "hotwire",
-            serde_json::json!({
-                "zellijSession": zellij_session,
-                "projectPath": project_path,
-                "suggestedPlaybook": playbook,
-                "intent": intent,
-            }),
-        )
+        .hotwire(&HotwireParams {
+            zellij_session: terminal.map(|t| t.session_key),
+            project_path,
+            suggested_playbook: playbook,
+            intent,
+        })
         .await
     {
-        Ok(response) if response.success => {
-            let data = response.data.as_ref().unwrap();
-            let status = data
-                .get("status")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown");
-
-            match status {
+        Ok(result) => {
+            if output.print_structured(&result) {
+                return;
+            }
+            match result.status.as_str() {
                 "started" => {
-                    let run_id = data.get("runId").and_then(|v| v.as_str()).unwrap_or("-");
-                    let role = data.get("role").and_then(|v| v.as_str()).unwrap_or("-");
-                    let protocol = data.get("protocol").and_then(|v| v.as_str()).unwrap_or("");
-
-                    println!("Run started: {}", run_id);
-                    println!("Your role: {}", role);
+                    println!("Run started: {}", result.run_id.as_deref().unwrap_or("-"));
+                    println!("Your role: {}", result.role.as_deref().unwrap_or("-"));
                     println!();
-                    println!("{}", protocol);
+                    println!("{}", result.protocol.as_deref().unwrap_or(""));
                 }
                 "needs_confirmation" => {
-                    let pending_id = data
-                        .get("pendingRunId")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("-");
+                    let pending_id = result.pending_run_id.as_deref().unwrap_or("-");
                     println!("Run pending confirmation: {}", pending_id);
                     println!();
                     println!("Please confirm the run in the Hotwired app.");
                     println!("Once confirmed, run: hotwired pair {}", pending_id);
                 }
-                _ => {
-                    println!("Unexpected status: {}", status);
-                    if let Some(d) = response.data {
-                        println!("{}", serde_json::to_string_pretty(&d).unwrap_or_default());
-                    }
+                other => {
+                    println!("Unexpected status: {}", other);
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&result).unwrap_or_default()
+                    );
                 }
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown error".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
@@ -3,32 +3,50 @@
 //! The `impediment` command signals that you're stuck and need help.
 //! The `resolve` command clears impediments and unblocks the run.
 
-use super::{handle_error, validate};
+use super::{handle_error, validate, OutputFormat};
 use crate::ipc::HotwiredClient;
+use crate::models::{ReportImpedimentParams, ResolveImpedimentsParams};
+use serde::Serialize;
+
+/// Structured result of `impediment`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Reported<'a> {
+    run_id: &'a str,
+    impediment_type: &'a str,
+    description: &'a str,
+    suggestion: Option<&'a str>,
+}
 
 pub async fn run(
     client: &HotwiredClient,
     description: &str,
     impediment_type: &str,
     suggestion: Option<String>,
+    output: OutputFormat,
 ) {
     // Validate session first
     let state = validate::require_session(client).await;
 
     match client
-        .request(
-            "report_impediment",
-            serde_json::json!({
-                "runId": state.run_id,
-                "source": state.role_id,
-                "impedimentType": impediment_type,
-                "description": description,
-                "suggestion": suggestion,
-            }),
-        )
+        .report_impediment(&ReportImpedimentParams {
+            run_id: state.run_id.clone(),
+            source: state.role_id,
+            impediment_type: impediment_type.to_string(),
+            description: description.to_string(),
+            suggestion: suggestion.clone(),
+        })
         .await
     {
-        Ok(response) if response.success => {
+        Ok(()) => {
+            if output.print_structured(&Reported {
+                run_id: &state.run_id,
+                impediment_type,
+                description,
+                suggestion: suggestion.as_deref(),
+            }) {
+                return;
+            }
             println!("Impediment reported.");
             println!();
             println!("Type: {}", impediment_type);
@@ -40,47 +58,31 @@ pub async fn run(
             println!("The human operator has been notified.");
             println!("To self-resolve when unblocked: hotwired resolve \"<reason>\"");
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "failed to report".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
 
-pub async fn resolve(client: &HotwiredClient, message: &str) {
+pub async fn resolve(client: &HotwiredClient, message: &str, output: OutputFormat) {
     let state = validate::require_session(client).await;
 
     match client
-        .request(
-            "resolve_run_impediments",
-            serde_json::json!({
-                "runId": state.run_id,
-                "source": state.role_id,
-                "message": message,
-            }),
-        )
+        .resolve_run_impediments(&ResolveImpedimentsParams {
+            run_id: state.run_id,
+            source: state.role_id,
+            message: message.to_string(),
+        })
         .await
     {
-        Ok(response) if response.success => {
-            let msg = response
-                .data
-                .as_ref()
-                .and_then(|d| d.get("message"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("Impediments resolved.");
+        Ok(result) => {
+            let result = result.unwrap_or_default();
+            if output.print_structured(&result) {
+                return;
+            }
+            let msg = result
+                .message
+                .unwrap_or_else(|| "Impediments resolved.".to_string());
             println!("{}", msg);
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "failed to resolve".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
@@ -5,11 +5,16 @@
 //! all IPC errors are silently ignored to avoid blocking Claude.
 
 use crate::ipc::HotwiredClient;
+use crate::models::{DeregisterSessionParams, HookEventParams, RegisterSessionParams};
+use std::time::Duration;
 use tokio::io::AsyncReadExt;
 
+/// Timeout for each hook IPC call; hooks ignore `--timeout` so they stay fast.
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Read stdin with a timeout, returning empty string on failure or timeout.
 async fn read_stdin() -> String {
-    let result = tokio::time::timeout(std::time::Duration::from_millis(500), async {
+    let result = tokio::time::timeout(Duration::from_millis(500), async {
         let mut buf = String::new();
         let mut stdin = tokio::io::stdin();
         stdin.read_to_string(&mut buf).await.ok();
@@ -33,19 +38,16 @@ async fn read_stdin_json() -> serde_json::Value {
 /// Handle a generic hook event (Stop, PreCompact, Notification, SubagentStart, etc.)
 pub async fn hook_event(client: &HotwiredClient, event_name: &str) {
     let payload = read_stdin_json().await;
-    let zellij_session = std::env::var("ZELLIJ_SESSION_NAME").ok();
+    let session_key = client.terminal().map(|t| t.session_key);
     let project_dir = std::env::var("CLAUDE_PROJECT_DIR").ok();
 
     let _ = client
-        .request(
-            "hook_event",
-            serde_json::json!({
-                "eventName": event_name,
-                "zellijSession": zellij_session,
-                "projectDir": project_dir,
-                "payload": payload,
-            }),
-        )
+        .hook_event(&HookEventParams {
+            event_name: event_name.to_string(),
+            zellij_session: session_key,
+            project_dir,
+            payload,
+        })
         .await;
 }
 
@@ -55,38 +57,31 @@ pub async fn hook_event(client: &HotwiredClient, event_name: &str) {
 /// 1. `register_session` - updates session state in DB, broadcasts `session:registered`
 /// 2. `hook_event` - broadcasts `hook:session_start` for telemetry logging
 pub async fn session_start(client: &HotwiredClient) {
-    let zellij_session = std::env::var("ZELLIJ_SESSION_NAME").unwrap_or_default();
+    // Skip if not in a terminal we can identify
+    let Some(terminal) = client.terminal() else {
+        return;
+    };
+    let session_key = terminal.session_key;
     let project_dir = std::env::var("CLAUDE_PROJECT_DIR")
         .or_else(|_| std::env::current_dir().map(|p| p.to_string_lossy().to_string()))
         .unwrap_or_default();
 
-    // Skip if not in a Zellij session
-    if zellij_session.is_empty() {
-        return;
-    }
-
     // Register session (existing IPC method)
     let _ = client
-        .request(
-            "register_session",
-            serde_json::json!({
-                "sessionName": zellij_session,
-                "projectDir": project_dir,
-            }),
-        )
+        .register_session(&RegisterSessionParams {
+            session_name: session_key.clone(),
+            project_dir: project_dir.clone(),
+        })
         .await;
 
     // Also fire hook event for telemetry logging
     let _ = client
-        .request(
-            "hook_event",
-            serde_json::json!({
-                "eventName": "session_start",
-                "zellijSession": zellij_session,
-                "projectDir": project_dir,
-                "payload": {},
-            }),
-        )
+        .hook_event(&HookEventParams {
+            event_name: "session_start".to_string(),
+            zellij_session: Some(session_key),
+            project_dir: Some(project_dir),
+            payload: serde_json::json!({}),
+        })
         .await;
 }
 
@@ -96,32 +91,26 @@ pub async fn session_start(client: &HotwiredClient) {
 /// 1. `deregister_session` - removes session from DB, broadcasts `session:deregistered`
 /// 2. `hook_event` - broadcasts `hook:session_end` for telemetry logging
 pub async fn session_end(client: &HotwiredClient) {
-    let zellij_session = std::env::var("ZELLIJ_SESSION_NAME").unwrap_or_default();
-
-    // Skip if not in a Zellij session
-    if zellij_session.is_empty() {
+    // Skip if not in a terminal we can identify
+    let Some(terminal) = client.terminal() else {
         return;
-    }
+    };
+    let session_key = terminal.session_key;
 
     // Deregister session (existing IPC method)
     let _ = client
-        .request(
-            "deregister_session",
-            serde_json::json!({
-                "sessionName": zellij_session,
-            }),
-        )
+        .deregister_session(&DeregisterSessionParams {
+            session_name: session_key.clone(),
+        })
         .await;
 
     // Also fire hook event for telemetry logging
     let _ = client
-        .request(
-            "hook_event",
-            serde_json::json!({
-                "eventName": "session_end",
-                "zellijSession": zellij_session,
-                "payload": {},
-            }),
-        )
+        .hook_event(&HookEventParams {
+            event_name: "session_end".to_string(),
+            zellij_session: Some(session_key),
+            project_dir: None,
+            payload: serde_json::json!({}),
+        })
         .await;
 }
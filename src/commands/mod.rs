@@ -1,7 +1,19 @@
+pub mod api;
 pub mod auth;
+pub mod context;
+pub mod diff;
+pub mod doctor;
+pub mod exit_code;
+pub mod export;
+pub mod filter;
 pub mod internal;
+pub mod output;
 pub mod run;
 pub mod session;
+pub mod stats;
+pub mod time;
+pub mod timeline;
+pub mod transcript;
 pub mod validate;
 
 // Workflow commands
@@ -17,31 +29,53 @@ pub mod status;
 // Artifact commands
 pub mod artifact;
 
+use crate::cassette::Cassette;
 use crate::ipc::{HotwiredClient, IpcError};
+use crate::trace::Tracer;
+pub use output::OutputFormat;
+use std::path::Path;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub async fn print_version(socket_path: Option<String>) {
-    let client = HotwiredClient::new(socket_path);
-    let core_version = match client.health_check().await {
-        Ok(response) if response.success => response
-            .data
-            .as_ref()
-            .and_then(|d| d.get("version"))
-            .and_then(|v| v.as_str())
-            .map(String::from),
-        _ => None,
-    };
-
-    match core_version {
-        Some(v) => println!("hotwired-cli {} (core {})", VERSION, v),
-        None => println!(
+pub async fn print_version(client: &HotwiredClient) {
+    match client.capabilities().await {
+        Ok(caps) => println!(
+            "hotwired-cli {} (core {}, protocol v{})",
+            VERSION,
+            caps.core_version.as_deref().unwrap_or("unknown"),
+            caps.protocol_version
+        ),
+        Err(_) => println!(
             "hotwired-cli {} (not connected - is Hotwired.sh desktop app running?)",
             VERSION
         ),
     }
 }
 
+/// Tracer for `--trace`/`--trace-file`, else the environment's.
+///
+/// An unwritable trace file is reported but never fails the command.
+pub fn tracer(flag: bool, file: Option<&Path>) -> Option<Tracer> {
+    let tracer = match file {
+        Some(path) => Tracer::to_file(path).map(Some),
+        None if flag => Ok(Some(Tracer::stderr())),
+        None => Tracer::from_env(),
+    };
+    tracer.unwrap_or_else(|e| {
+        eprintln!("warning: tracing disabled, cannot open trace file: {}", e);
+        None
+    })
+}
+
+/// Cassette from `HOTWIRED_RECORD`/`HOTWIRED_REPLAY`; an unreadable replay
+/// file is fatal, since the command has nothing else to talk to.
+pub fn cassette() -> Option<Cassette> {
+    Cassette::from_env().unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(exit_code::FAILURE);
+    })
+}
+
 pub fn format_timestamp(ts: &str) -> String {
     ts.replace('T', " ").trim_end_matches('Z').to_string()
 }
@@ -60,11 +94,23 @@ pub fn handle_error(e: IpcError) -> ! {
         IpcError::NotConnected(_) => {
             eprintln!("error: not connected - is Hotwired.sh desktop app running?");
         }
+        IpcError::Timeout(_) => {
+            eprintln!("error: {}", e);
+            eprintln!("Is hotwired-core hung? Raise the limit with --timeout or HOTWIRED_TIMEOUT.");
+        }
+        IpcError::Unsupported { .. } => {
+            eprintln!("error: {}", e);
+            eprintln!("Update the Hotwired desktop app to use this command.");
+        }
+        IpcError::UntrustedSocket { .. } => {
+            eprintln!("error: {}", e);
+            eprintln!("If core deliberately runs as another user, pass --allow-untrusted-socket.");
+        }
         _ => {
             eprintln!("error: {}", e);
         }
     }
-    std::process::exit(1);
+    std::process::exit(exit_code::for_ipc_error(&e));
 }
 
 #[cfg(test)]
@@ -96,10 +142,16 @@ mod tests {
 }
 
 /// Tests for IPC parameter serialization
-/// Verifies that CLI sends camelCase field names matching hotwired-core expectations
+/// Verifies that the typed params in `models` serialize to the camelCase field
+/// names hotwired-core expects
 #[cfg(test)]
 mod ipc_params_tests {
-    use serde_json::json;
+    use crate::models::*;
+    use serde::Serialize;
+
+    fn to_json<T: Serialize>(params: &T) -> serde_json::Value {
+        serde_json::to_value(params).unwrap()
+    }
 
     /// Helper to verify JSON has expected camelCase keys
     fn assert_has_camel_case_key(json: &serde_json::Value, key: &str) {
@@ -122,11 +174,11 @@ mod ipc_params_tests {
 
     #[test]
     fn test_hotwire_params_are_camel_case() {
-        let params = json!({
-            "zellijSession": "test-session",
-            "projectPath": "/path/to/project",
-            "suggestedPlaybook": "plan-build",
-            "intent": "test intent",
+        let params = to_json(&HotwireParams {
+            zellij_session: Some("test-session".into()),
+            project_path: Some("/path/to/project".into()),
+            suggested_playbook: Some("plan-build".into()),
+            intent: Some("test intent".into()),
         });
 
         assert_has_camel_case_key(&params, "zellijSession");
@@ -139,11 +191,11 @@ mod ipc_params_tests {
 
     #[test]
     fn test_pair_params_are_camel_case() {
-        let params = json!({
-            "zellijSession": "test-session",
-            "projectPath": "/path/to/project",
-            "runId": "abc123",
-            "roleId": "worker-1",
+        let params = to_json(&PairParams {
+            zellij_session: Some("test-session".into()),
+            project_path: Some("/path/to/project".into()),
+            run_id: "abc123".into(),
+            role_id: Some("worker-1".into()),
         });
 
         assert_has_camel_case_key(&params, "zellijSession");
@@ -158,11 +210,11 @@ mod ipc_params_tests {
 
     #[test]
     fn test_complete_params_are_camel_case() {
-        let params = json!({
-            "runId": "abc123",
-            "taskDescription": "Task completed",
-            "source": "strategist",
-            "outcome": "Completed",
+        let params = to_json(&TaskCompleteParams {
+            run_id: "abc123".into(),
+            task_description: "Task completed".into(),
+            source: "strategist".into(),
+            outcome: "Completed".into(),
         });
 
         assert_has_camel_case_key(&params, "runId");
@@ -173,12 +225,12 @@ mod ipc_params_tests {
 
     #[test]
     fn test_impediment_params_are_camel_case() {
-        let params = json!({
-            "runId": "abc123",
-            "source": "strategist",
-            "impedimentType": "technical",
-            "description": "Cannot connect to database",
-            "suggestion": "Check credentials",
+        let params = to_json(&ReportImpedimentParams {
+            run_id: "abc123".into(),
+            source: "strategist".into(),
+            impediment_type: "technical".into(),
+            description: "Cannot connect to database".into(),
+            suggestion: Some("Check credentials".into()),
         });
 
         assert_has_camel_case_key(&params, "runId");
@@ -191,12 +243,12 @@ mod ipc_params_tests {
 
     #[test]
     fn test_send_params_are_camel_case() {
-        let params = json!({
-            "runId": "abc123",
-            "to": "implementer",
-            "summary": "Please implement X",
-            "details": "Detailed instructions",
-            "source": "strategist",
+        let params = to_json(&HandoffParams {
+            run_id: "abc123".into(),
+            to: "implementer".into(),
+            summary: "Please implement X".into(),
+            details: "Detailed instructions".into(),
+            source: "strategist".into(),
         });
 
         assert_has_camel_case_key(&params, "runId");
@@ -205,9 +257,10 @@ mod ipc_params_tests {
 
     #[test]
     fn test_inbox_params_are_camel_case() {
-        let params = json!({
-            "runId": "abc123",
-            "sinceSequence": 10,
+        let params = to_json(&ConversationEventsParams {
+            run_id: "abc123".into(),
+            since_sequence: Some(10),
+            limit: 20,
         });
 
         assert_has_camel_case_key(&params, "runId");
@@ -218,8 +271,8 @@ mod ipc_params_tests {
 
     #[test]
     fn test_status_params_are_camel_case() {
-        let params = json!({
-            "runId": "abc123",
+        let params = to_json(&RunIdParams {
+            run_id: "abc123".into(),
         });
 
         assert_has_camel_case_key(&params, "runId");
@@ -229,16 +282,18 @@ mod ipc_params_tests {
     #[test]
     fn test_artifact_params_are_camel_case() {
         // artifact list
-        let list_params = json!({ "runId": "abc123" });
+        let list_params = to_json(&RunIdParams {
+            run_id: "abc123".into(),
+        });
         assert_has_camel_case_key(&list_params, "runId");
         assert_no_snake_case_key(&list_params, "run_id");
 
         // artifact move
-        let move_params = json!({
-            "runId": "abc123",
-            "oldPath": "docs/old.md",
-            "newPath": "docs/new.md",
-            "refsOnly": false,
+        let move_params = to_json(&ArtifactMoveParams {
+            run_id: "abc123".into(),
+            old_path: "docs/old.md".into(),
+            new_path: "docs/new.md".into(),
+            refs_only: false,
         });
         assert_has_camel_case_key(&move_params, "oldPath");
         assert_has_camel_case_key(&move_params, "newPath");
@@ -248,42 +303,48 @@ mod ipc_params_tests {
         assert_no_snake_case_key(&move_params, "refs_only");
 
         // artifact add-comment
-        let comment_params = json!({
-            "runId": "abc123",
-            "path": "docs/spec.md",
-            "targetText": "some text",
-            "comment": "This needs clarification",
-            "author": "strategist",
+        let comment_params = to_json(&AddCommentParams {
+            run_id: "abc123".into(),
+            path: "docs/spec.md".into(),
+            target_text: "some text".into(),
+            comment: "This needs clarification".into(),
+            author: "strategist".into(),
+            parent_comment_id: Some("cmt_abc".into()),
         });
         assert_has_camel_case_key(&comment_params, "targetText");
+        assert_has_camel_case_key(&comment_params, "parentCommentId");
         assert_no_snake_case_key(&comment_params, "target_text");
+        assert_no_snake_case_key(&comment_params, "parent_comment_id");
 
         // artifact list-comments
-        let list_comments_params = json!({
-            "runId": "abc123",
-            "path": "docs/spec.md",
-            "statusFilter": "open",
+        let list_comments_params = to_json(&ListCommentsParams {
+            run_id: "abc123".into(),
+            path: "docs/spec.md".into(),
+            status_filter: "open".into(),
         });
         assert_has_camel_case_key(&list_comments_params, "statusFilter");
         assert_no_snake_case_key(&list_comments_params, "status_filter");
 
         // artifact resolve
-        let resolve_params = json!({
-            "runId": "abc123",
-            "commentId": "comment-456",
-            "resolvedBy": "strategist",
+        let resolve_params = to_json(&ResolveCommentParams {
+            run_id: "abc123".into(),
+            comment_id: "comment-456".into(),
+            resolved_by: "strategist".into(),
+            reply: None,
         });
         assert_has_camel_case_key(&resolve_params, "commentId");
         assert_has_camel_case_key(&resolve_params, "resolvedBy");
         assert_no_snake_case_key(&resolve_params, "comment_id");
         assert_no_snake_case_key(&resolve_params, "resolved_by");
+        // Optional reply is omitted entirely rather than sent as null
+        assert_no_snake_case_key(&resolve_params, "reply");
     }
 
     #[test]
     fn test_session_params_are_camel_case() {
-        let register_params = json!({
-            "sessionName": "claude-main",
-            "projectDir": "/path/to/project",
+        let register_params = to_json(&RegisterSessionParams {
+            session_name: "claude-main".into(),
+            project_dir: "/path/to/project".into(),
         });
         assert_has_camel_case_key(&register_params, "sessionName");
         assert_has_camel_case_key(&register_params, "projectDir");
@@ -293,8 +354,8 @@ mod ipc_params_tests {
 
     #[test]
     fn test_validate_params_are_camel_case() {
-        let params = json!({
-            "zellijSession": "test-session",
+        let params = to_json(&SessionStateParams {
+            zellij_session: "test-session".into(),
         });
         assert_has_camel_case_key(&params, "zellijSession");
         assert_no_snake_case_key(&params, "zellij_session");
@@ -302,11 +363,11 @@ mod ipc_params_tests {
 
     #[test]
     fn test_hook_event_params_are_camel_case() {
-        let params = json!({
-            "eventName": "stop",
-            "zellijSession": "test-session",
-            "projectDir": "/path/to/project",
-            "payload": {},
+        let params = to_json(&HookEventParams {
+            event_name: "stop".into(),
+            zellij_session: Some("test-session".into()),
+            project_dir: Some("/path/to/project".into()),
+            payload: serde_json::json!({}),
         });
 
         assert_has_camel_case_key(&params, "eventName");
@@ -320,9 +381,9 @@ mod ipc_params_tests {
     #[test]
     fn test_internal_session_start_params_are_camel_case() {
         // register_session call
-        let register_params = json!({
-            "sessionName": "test-session",
-            "projectDir": "/path/to/project",
+        let register_params = to_json(&RegisterSessionParams {
+            session_name: "test-session".into(),
+            project_dir: "/path/to/project".into(),
         });
         assert_has_camel_case_key(&register_params, "sessionName");
         assert_has_camel_case_key(&register_params, "projectDir");
@@ -330,11 +391,11 @@ mod ipc_params_tests {
         assert_no_snake_case_key(&register_params, "project_dir");
 
         // hook_event call for session_start telemetry
-        let hook_params = json!({
-            "eventName": "session_start",
-            "zellijSession": "test-session",
-            "projectDir": "/path/to/project",
-            "payload": {},
+        let hook_params = to_json(&HookEventParams {
+            event_name: "session_start".into(),
+            zellij_session: Some("test-session".into()),
+            project_dir: Some("/path/to/project".into()),
+            payload: serde_json::json!({}),
         });
         assert_has_camel_case_key(&hook_params, "eventName");
         assert_has_camel_case_key(&hook_params, "zellijSession");
@@ -345,21 +406,23 @@ mod ipc_params_tests {
     #[test]
     fn test_internal_session_end_params_are_camel_case() {
         // deregister_session call
-        let deregister_params = json!({
-            "sessionName": "test-session",
+        let deregister_params = to_json(&DeregisterSessionParams {
+            session_name: "test-session".into(),
         });
         assert_has_camel_case_key(&deregister_params, "sessionName");
         assert_no_snake_case_key(&deregister_params, "session_name");
 
         // hook_event call for session_end telemetry
-        let hook_params = json!({
-            "eventName": "session_end",
-            "zellijSession": "test-session",
-            "payload": {},
+        let hook_params = to_json(&HookEventParams {
+            event_name: "session_end".into(),
+            zellij_session: Some("test-session".into()),
+            project_dir: None,
+            payload: serde_json::json!({}),
         });
         assert_has_camel_case_key(&hook_params, "eventName");
         assert_has_camel_case_key(&hook_params, "zellijSession");
         assert_no_snake_case_key(&hook_params, "event_name");
         assert_no_snake_case_key(&hook_params, "zellij_session");
+        assert_no_snake_case_key(&hook_params, "projectDir");
     }
 }
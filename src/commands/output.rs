@@ -0,0 +1,88 @@
+//! Output formats for command results
+//!
+//! Tables and prose are for humans; `json` and `ndjson` emit the same typed
+//! structures the commands render, so scripts never have to scrape columns.
+//! Errors always go to stderr as text, with the exit codes in `exit_code`.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables and text
+    #[default]
+    Table,
+    /// One pretty-printed JSON document
+    Json,
+    /// One compact JSON document per line (lists emit one line per item)
+    Ndjson,
+}
+
+impl OutputFormat {
+    /// Print `value` for json/ndjson output.
+    ///
+    /// Returns `false` for table output, meaning the caller should render it.
+    pub fn print_structured<T: Serialize>(self, value: &T) -> bool {
+        match self {
+            OutputFormat::Table => false,
+            OutputFormat::Json => {
+                println!("{}", to_json(value, true));
+                true
+            }
+            OutputFormat::Ndjson => {
+                println!("{}", to_json(value, false));
+                true
+            }
+        }
+    }
+
+    /// Like `print_structured`, but ndjson emits one line per item.
+    pub fn print_structured_list<T: Serialize>(self, items: &[T]) -> bool {
+        match self {
+            OutputFormat::Ndjson => {
+                for item in items {
+                    println!("{}", to_json(item, false));
+                }
+                true
+            }
+            _ => self.print_structured(&items),
+        }
+    }
+
+    /// Print a single item of an open-ended stream (e.g. `inbox --watch`).
+    ///
+    /// A stream can't be one JSON document, so json falls back to one
+    /// compact document per line.
+    pub fn print_stream_item<T: Serialize>(self, item: &T) -> bool {
+        match self {
+            OutputFormat::Table => false,
+            _ => OutputFormat::Ndjson.print_structured(item),
+        }
+    }
+}
+
+fn to_json<T: Serialize>(value: &T, pretty: bool) -> String {
+    let result = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    result.unwrap_or_else(|e| format!("{{\"error\":\"serialization failed: {}\"}}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_defers_to_caller() {
+        assert!(!OutputFormat::Table.print_structured(&serde_json::json!({"a": 1})));
+        assert!(!OutputFormat::Table.print_structured_list(&[1, 2]));
+    }
+
+    #[test]
+    fn test_to_json_compact_is_single_line() {
+        let value = serde_json::json!({"runId": "abc", "agents": ["a", "b"]});
+        assert!(!to_json(&value, false).contains('\n'));
+        assert!(to_json(&value, true).contains('\n'));
+    }
+}
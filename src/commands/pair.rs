@@ -3,50 +3,47 @@
 //! The `pair` command attaches this terminal to an existing run. This is one of the
 //! few commands that does NOT require an existing session - it creates the attachment.
 
-use super::handle_error;
+use super::{handle_error, validate, OutputFormat};
 use crate::ipc::HotwiredClient;
+use crate::models::{PairParams, PairResult};
+use serde::Serialize;
 
-pub async fn run(client: &HotwiredClient, run_id: &str, role: Option<&str>) {
+/// Structured result of `pair`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Joined<'a> {
+    run_id: &'a str,
+    #[serde(flatten)]
+    result: &'a PairResult,
+}
+
+pub async fn run(client: &HotwiredClient, run_id: &str, role: Option<&str>, output: OutputFormat) {
     // pair does NOT require existing session - it creates the attachment
-    let zellij_session = std::env::var("ZELLIJ_SESSION_NAME").ok();
+    let terminal = validate::require_terminal(client);
     let project_path = std::env::current_dir()
         .ok()
         .map(|p| p.to_string_lossy().to_string());
 
-    if zellij_session.is_none() {
-        eprintln!("ERROR: Not running in a Zellij session.");
-        eprintln!("The hotwired CLI must be run from within a Hotwired-managed terminal.");
-        std::process::exit(1);
-    }
-
     match client
-        .request(
-            "pair",
-            serde_json::json!({
-                "zellijSession": zellij_session,
-                "projectPath": project_path,
-                "runId": run_id,
-                "roleId": role,
-            }),
-        )
+        .pair(&PairParams {
+            zellij_session: Some(terminal.session_key),
+            project_path,
+            run_id: run_id.to_string(),
+            role_id: role.map(String::from),
+        })
         .await
     {
-        Ok(response) if response.success => {
-            let data = response.data.as_ref().unwrap();
-            let role = data.get("role").and_then(|v| v.as_str()).unwrap_or("-");
-            let protocol = data.get("protocol").and_then(|v| v.as_str()).unwrap_or("");
-
+        Ok(result) => {
+            if output.print_structured(&Joined {
+                run_id,
+                result: &result,
+            }) {
+                return;
+            }
             println!("Joined run: {}", run_id);
-            println!("Your role: {}", role);
+            println!("Your role: {}", result.role);
             println!();
-            println!("{}", protocol);
-        }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown error".into())
-            );
-            std::process::exit(1);
+            println!("{}", result.protocol.as_deref().unwrap_or(""));
         }
         Err(e) => handle_error(e),
     }
@@ -4,64 +4,48 @@
 //! This is useful when an agent's context has been compacted or it needs to
 //! re-read its instructions.
 
-use super::{handle_error, validate};
+use super::{handle_error, validate, OutputFormat};
 use crate::ipc::HotwiredClient;
+use crate::models::ProtocolParams;
 
-pub async fn run(client: &HotwiredClient) {
+pub async fn run(client: &HotwiredClient, output: OutputFormat) {
     let state = validate::require_session(client).await;
 
     match client
-        .request(
-            "get_protocol",
-            serde_json::json!({
-                "runId": state.run_id,
-                "role": state.role_id,
-            }),
-        )
+        .get_protocol(&ProtocolParams {
+            run_id: state.run_id,
+            role: state.role_id,
+        })
         .await
     {
-        Ok(response) if response.success => {
-            let data = response.data.unwrap_or_default();
-
-            let run_id = data.get("runId").and_then(|v| v.as_str()).unwrap_or("-");
-            let template = data
-                .get("templateName")
-                .and_then(|v| v.as_str())
-                .unwrap_or("-");
-            let playbook_protocol = data
-                .get("playbookProtocol")
-                .and_then(|v| v.as_str())
+        Ok(info) => {
+            if output.print_structured(&info) {
+                return;
+            }
+            let template = info.template_name.as_deref().unwrap_or("-");
+            let playbook_protocol = info
+                .playbook_protocol
+                .as_deref()
                 .unwrap_or("(No protocol instructions available)");
-            let role_protocol = data.get("roleProtocol").and_then(|v| v.as_str());
-            let init_condition = data.get("initializationCondition").and_then(|v| v.as_str());
 
             // Format capabilities
-            let capabilities_section = data
-                .get("capabilities")
-                .and_then(|v| v.as_object())
-                .and_then(|caps| {
-                    let can_resolve = caps
-                        .get("canResolveImpediments")
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(false);
-                    if can_resolve {
-                        Some("- Can resolve impediments: You can use `hotwired impediment` to resolve blockers raised by other agents")
-                    } else {
-                        None
-                    }
-                });
+            let capabilities_section = info
+                .capabilities
+                .as_ref()
+                .filter(|caps| caps.can_resolve_impediments)
+                .map(|_| "- Can resolve impediments: You can use `hotwired impediment` to resolve blockers raised by other agents");
 
             // Print formatted protocol (matches hotwired-mcp format_protocol_response)
             println!("# Hotwired Workflow Protocol");
             println!();
-            println!("**Run ID:** {}", run_id);
+            println!("**Run ID:** {}", info.run_id);
             println!("**Playbook:** {}", template);
             println!();
             println!("## Protocol Instructions");
             println!();
             println!("{}", playbook_protocol);
 
-            if let Some(rp) = role_protocol {
+            if let Some(rp) = &info.role_protocol {
                 if !rp.is_empty() {
                     println!();
                     println!("## Your Role Instructions");
@@ -77,22 +61,13 @@ pub async fn run(client: &HotwiredClient) {
                 println!("{}", cap);
             }
 
-            if let Some(ic) = init_condition {
+            if let Some(ic) = &info.initialization_condition {
                 println!();
                 println!("## Initialization Condition");
                 println!();
                 println!("{}", ic);
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response
-                    .error
-                    .unwrap_or_else(|| "failed to get protocol".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
@@ -1,61 +1,201 @@
-use super::{format_timestamp, handle_error};
-use crate::ipc::HotwiredClient;
+use super::filter::ListFilters;
+use super::timeline::{self, TimelineEntry};
+use super::{exit_code, format_timestamp, handle_error, OutputFormat};
+use crate::ipc::{HotwiredClient, IpcError};
+use crate::models::{ArtifactSummary, RunStatus, RunSummary, RunTransition, UpdateRunStateParams};
+use chrono::{DateTime, Duration, Utc};
+use hotwired_client::session::TERMINAL_RUN_STATUSES;
+use serde::Serialize;
+use std::io::IsTerminal;
 
-async fn resolve_id(client: &HotwiredClient, short_id: &str) -> String {
-    // Full UUIDs (with or without dashes) pass through directly
-    if short_id.len() >= 32 {
+/// Structured result of `run show --timeline`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunWithTimeline<'a> {
+    #[serde(flatten)]
+    run: &'a RunStatus,
+    timeline: &'a [TimelineEntry],
+}
+
+/// A run `run rm`/`run prune` would delete, with what goes with it
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DoomedRun {
+    id: String,
+    status: String,
+    template_name: Option<String>,
+    created_at: Option<String>,
+    /// Counts are `None` when core couldn't list the run's artifacts
+    artifacts: Option<i64>,
+    versions: Option<i64>,
+    comments: Option<i64>,
+}
+
+/// Structured result of `run rm` and `run prune`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Removal<'a> {
+    dry_run: bool,
+    runs: &'a [DoomedRun],
+    removed: &'a [String],
+}
+
+/// Structured result of `run cancel|pause|resume|archive`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransitionedRun {
+    run_id: String,
+    action: RunTransition,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+/// Full UUIDs (with or without dashes) name a run even when `list_runs`
+/// doesn't return it, so they're used as given when it doesn't.
+fn is_full_id(id: &str) -> bool {
+    id.len() >= 32
+}
+
+/// A full UUID already in core's dashed form needs no `list_runs` lookup.
+fn is_dashed_full_id(id: &str) -> bool {
+    is_full_id(id) && id.contains('-')
+}
+
+/// The runs `prefix` may refer to: the run with exactly that ID (dashes
+/// optional), otherwise every run whose ID starts with it.
+fn matching<'a>(runs: &'a [RunSummary], prefix: &str) -> Vec<&'a RunSummary> {
+    let exact: Vec<&RunSummary> = runs
+        .iter()
+        .filter(|r| r.id == prefix || r.id.replace('-', "") == prefix)
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+    runs.iter().filter(|r| r.id.starts_with(prefix)).collect()
+}
+
+/// The one run `prefix` refers to, or the exit code after saying why not.
+fn single<'a>(runs: &'a [RunSummary], prefix: &str) -> Result<&'a RunSummary, i32> {
+    match matching(runs, prefix).as_slice() {
+        [run] => Ok(run),
+        [] => {
+            eprintln!("error: no run matching '{}'", prefix);
+            Err(exit_code::NOT_FOUND)
+        }
+        _ => {
+            eprintln!("error: ambiguous run id '{}', be more specific", prefix);
+            Err(exit_code::AMBIGUOUS)
+        }
+    }
+}
+
+pub async fn resolve_id(client: &HotwiredClient, short_id: &str) -> String {
+    if is_dashed_full_id(short_id) {
         return short_id.to_string();
     }
 
-    match client.request("list_runs", serde_json::json!({})).await {
-        Ok(response) if response.success => {
-            let runs = response
-                .data
-                .as_ref()
-                .and_then(|d| d.as_array())
-                .cloned()
-                .unwrap_or_default();
+    let runs = match client.list_runs().await {
+        Ok(runs) => runs,
+        Err(e) => handle_error(e),
+    };
+    if is_full_id(short_id) && matching(&runs, short_id).is_empty() {
+        return short_id.to_string();
+    }
+    match single(&runs, short_id) {
+        Ok(run) => run.id.clone(),
+        Err(code) => std::process::exit(code),
+    }
+}
 
-            let matches: Vec<String> = runs
-                .iter()
-                .filter_map(|r| r.get("id").and_then(|v| v.as_str()).map(String::from))
-                .filter(|id| id.starts_with(short_id))
-                .collect();
-
-            match matches.len() {
-                0 => {
-                    eprintln!("error: no run matching '{}'", short_id);
-                    std::process::exit(1);
-                }
-                1 => matches.into_iter().next().unwrap(),
-                _ => {
-                    eprintln!("error: ambiguous run id '{}', be more specific", short_id);
-                    std::process::exit(1);
+/// The run `short_id` refers to among already fetched `runs`, or exit
+/// saying why not.
+pub async fn resolve_in(
+    client: &HotwiredClient,
+    runs: Vec<RunSummary>,
+    short_id: &str,
+) -> RunSummary {
+    if is_full_id(short_id) {
+        return match summary_of(client, runs, short_id).await {
+            Ok(run) => run,
+            Err(e) => handle_error(e),
+        };
+    }
+    match single(&runs, short_id) {
+        Ok(run) => run.clone(),
+        Err(code) => std::process::exit(code),
+    }
+}
+
+/// Resolve every prefix against one `list_runs`, reporting all bad ones
+/// before exiting so nothing is touched on a typo.
+async fn resolve_runs(client: &HotwiredClient, ids: &[String]) -> Vec<RunSummary> {
+    let runs = match client.list_runs().await {
+        Ok(runs) => runs,
+        Err(e) => handle_error(e),
+    };
+
+    let mut resolved: Vec<RunSummary> = Vec::new();
+    let mut failure = None;
+    for short_id in ids {
+        let run = if is_full_id(short_id) {
+            match summary_of(client, runs.clone(), short_id).await {
+                Ok(run) => run,
+                Err(e) => handle_error(e),
+            }
+        } else {
+            match single(&runs, short_id) {
+                Ok(run) => run.clone(),
+                Err(code) => {
+                    failure.get_or_insert(code);
+                    continue;
                 }
             }
+        };
+        if !resolved.iter().any(|r| r.id == run.id) {
+            resolved.push(run);
         }
-        Ok(_) => {
-            eprintln!("error: failed to resolve run id");
-            std::process::exit(1);
-        }
-        Err(e) => handle_error(e),
     }
+
+    if let Some(code) = failure {
+        std::process::exit(code);
+    }
+    resolved
 }
 
-fn short_id(id: &str) -> &str {
-    &id[..id.len().min(8)]
+/// The `list_runs` entry of `run_id` (dashes optional) among `runs`. A full
+/// ID core doesn't list is described by its status alone.
+pub async fn summary_of(
+    client: &HotwiredClient,
+    runs: Vec<RunSummary>,
+    run_id: &str,
+) -> Result<RunSummary, IpcError> {
+    if let [summary] = matching(&runs, run_id).as_slice() {
+        return Ok((*summary).clone());
+    }
+    let status = client.get_run_status(run_id).await?;
+    Ok(RunSummary {
+        id: status.run_id,
+        status: status.status,
+        phase: status.phase,
+        template_name: status.template_name,
+        template_id: None,
+        project_path: None,
+        created_at: None,
+    })
 }
 
-pub async fn list(client: &HotwiredClient) {
-    match client.request("list_runs", serde_json::json!({})).await {
-        Ok(response) if response.success => {
-            let runs = response
-                .data
-                .as_ref()
-                .and_then(|d| d.as_array())
-                .cloned()
-                .unwrap_or_default();
+pub fn short_id(id: &str) -> &str {
+    &id[..id.len().min(8)]
+}
 
+pub async fn list(client: &HotwiredClient, filters: &ListFilters, output: OutputFormat) {
+    match client.list_runs().await {
+        Ok(runs) => {
+            let runs = filters.apply_to_runs(runs);
+            if output.print_structured_list(&runs) {
+                return;
+            }
             if runs.is_empty() {
                 println!("No runs.");
                 return;
@@ -67,112 +207,339 @@ pub async fn list(client: &HotwiredClient) {
             );
 
             for run in &runs {
-                let id = run.get("id").and_then(|v| v.as_str()).unwrap_or("-");
-                let status = run.get("status").and_then(|v| v.as_str()).unwrap_or("-");
-                let phase = run.get("phase").and_then(|v| v.as_str()).unwrap_or("-");
-                let playbook = run
-                    .get("templateName")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("-");
-                let created =
-                    format_timestamp(run.get("createdAt").and_then(|v| v.as_str()).unwrap_or("-"));
-
                 println!(
                     "{:<10} {:<12} {:<14} {:<24} {}",
-                    short_id(id),
-                    status,
-                    phase,
-                    playbook,
-                    created
+                    short_id(&run.id),
+                    run.status,
+                    run.phase.as_deref().unwrap_or("-"),
+                    run.template_name.as_deref().unwrap_or("-"),
+                    format_timestamp(run.created_at.as_deref().unwrap_or("-"))
                 );
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown error".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
 
-pub async fn show(client: &HotwiredClient, id: &str) {
+pub async fn show(client: &HotwiredClient, id: &str, with_timeline: bool, output: OutputFormat) {
     let full_id = resolve_id(client, id).await;
 
-    match client
-        .request("get_run_status", serde_json::json!({"runId": full_id}))
-        .await
-    {
-        Ok(response) if response.success => {
-            if let Some(data) = &response.data {
-                let run_id = data.get("runId").and_then(|v| v.as_str()).unwrap_or("-");
-                let status = data.get("status").and_then(|v| v.as_str()).unwrap_or("-");
-                let phase = data.get("phase").and_then(|v| v.as_str()).unwrap_or("-");
-                let playbook = data
-                    .get("templateName")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("-");
-                let has_protocol = data
-                    .get("hasProtocol")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false);
-
-                println!("Run:        {}", run_id);
-                println!("Status:     {}", status);
-                println!("Phase:      {}", phase);
-                println!("Playbook:   {}", playbook);
-                println!("Protocol:   {}", if has_protocol { "yes" } else { "no" });
-
-                if let Some(agents) = data.get("connectedAgents").and_then(|v| v.as_array()) {
-                    if !agents.is_empty() {
-                        println!();
-                        println!("Agents:");
-                        for agent in agents {
-                            let role = agent.get("roleId").and_then(|v| v.as_str()).unwrap_or("-");
-                            let session = agent
-                                .get("sessionName")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("-");
-                            let agent_type = agent
-                                .get("agentType")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("-");
-                            println!("  {:<16} {:<28} ({})", role, session, agent_type);
-                        }
-                    }
+    match client.get_run_status(&full_id).await {
+        Ok(run) => {
+            let history = if with_timeline {
+                match timeline::fetch_all_events(client, &full_id).await {
+                    Ok(events) => Some(timeline::build(&events)),
+                    Err(e) => handle_error(e),
                 }
+            } else {
+                None
+            };
+
+            let printed = match &history {
+                Some(entries) => output.print_structured(&RunWithTimeline {
+                    run: &run,
+                    timeline: entries,
+                }),
+                None => output.print_structured(&run),
+            };
+            if printed {
+                return;
             }
-        }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "run not found".into())
+
+            println!("Run:        {}", run.run_id);
+            println!("Status:     {}", run.status);
+            println!("Phase:      {}", run.phase.as_deref().unwrap_or("-"));
+            println!(
+                "Playbook:   {}",
+                run.template_name.as_deref().unwrap_or("-")
+            );
+            if let Some(reason) = &run.status_reason {
+                println!("Reason:     {}", reason);
+            }
+            println!(
+                "Protocol:   {}",
+                if run.has_protocol { "yes" } else { "no" }
             );
-            std::process::exit(1);
+
+            if !run.connected_agents.is_empty() {
+                println!();
+                println!("Agents:");
+                for agent in &run.connected_agents {
+                    println!(
+                        "  {:<16} {:<28} ({})",
+                        agent.role_id,
+                        agent.session_name.as_deref().unwrap_or("-"),
+                        agent.agent_type.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+
+            if let Some(entries) = &history {
+                println!();
+                println!("Timeline:");
+                timeline::print(entries);
+            }
         }
         Err(e) => handle_error(e),
     }
 }
 
-pub async fn remove(client: &HotwiredClient, id: &str) {
+pub async fn transition(
+    client: &HotwiredClient,
+    id: &str,
+    action: RunTransition,
+    reason: Option<String>,
+    output: OutputFormat,
+) {
     let full_id = resolve_id(client, id).await;
 
-    match client
-        .request("delete_run", serde_json::json!({"runId": full_id}))
-        .await
-    {
-        Ok(response) if response.success => {
-            println!("Removed run {}", short_id(&full_id));
+    let params = UpdateRunStateParams {
+        run_id: full_id.clone(),
+        action,
+        reason: reason.clone(),
+    };
+    if let Err(e) = client.update_run_state(&params).await {
+        handle_error(e);
+    }
+
+    if output.print_structured(&TransitionedRun {
+        run_id: full_id.clone(),
+        action,
+        status: action.target_status(),
+        reason,
+    }) {
+        return;
+    }
+    let verb = match action {
+        RunTransition::Cancel => "Cancelled",
+        RunTransition::Pause => "Paused",
+        RunTransition::Resume => "Resumed",
+        RunTransition::Archive => "Archived",
+    };
+    println!("{} run {}", verb, short_id(&full_id));
+}
+
+/// `run rm`: delete the given runs after confirmation.
+pub async fn remove(client: &HotwiredClient, ids: &[String], yes: bool, output: OutputFormat) {
+    let runs = resolve_runs(client, ids).await;
+    remove_runs(client, runs, false, yes, output).await;
+}
+
+/// `run prune`: delete runs matching `statuses` (finished ones by default)
+/// created more than `older_than` ago.
+pub async fn prune(
+    client: &HotwiredClient,
+    statuses: Vec<String>,
+    older_than: Option<Duration>,
+    dry_run: bool,
+    yes: bool,
+    output: OutputFormat,
+) {
+    let status = if statuses.is_empty() {
+        TERMINAL_RUN_STATUSES
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    } else {
+        statuses
+    };
+    let filters = ListFilters {
+        status,
+        // `parse_age` keeps ages in range; if not, prune nothing
+        until: older_than.map(|age| {
+            Utc::now()
+                .checked_sub_signed(age)
+                .unwrap_or(DateTime::<Utc>::MIN_UTC)
+        }),
+        ..Default::default()
+    };
+
+    let runs = match client.list_runs().await {
+        Ok(runs) => filters.apply_to_runs(runs),
+        Err(e) => handle_error(e),
+    };
+    remove_runs(client, runs, dry_run, yes, output).await;
+}
+
+async fn remove_runs(
+    client: &HotwiredClient,
+    runs: Vec<RunSummary>,
+    dry_run: bool,
+    yes: bool,
+    output: OutputFormat,
+) {
+    let mut doomed = Vec::with_capacity(runs.len());
+    for run in runs {
+        doomed.push(assess(client, run).await);
+    }
+
+    if doomed.is_empty() {
+        if !output.print_structured(&Removal {
+            dry_run,
+            runs: &doomed,
+            removed: &[],
+        }) {
+            println!("No runs to remove.");
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown error".into())
-            );
-            std::process::exit(1);
+        return;
+    }
+
+    if dry_run {
+        if output.print_structured(&Removal {
+            dry_run,
+            runs: &doomed,
+            removed: &[],
+        }) {
+            return;
         }
-        Err(e) => handle_error(e),
+        println!("Would remove {}:", describe(&doomed));
+        println!();
+        for line in plan_table(&doomed) {
+            println!("{}", line);
+        }
+        return;
+    }
+
+    if !yes {
+        eprintln!("About to remove {}:", describe(&doomed));
+        eprintln!();
+        for line in plan_table(&doomed) {
+            eprintln!("{}", line);
+        }
+        eprintln!();
+        if !confirm("Remove them? This cannot be undone.") {
+            eprintln!("Nothing removed.");
+            std::process::exit(exit_code::FAILURE);
+        }
+    }
+
+    // Keep going past failures so one stuck run doesn't block the rest
+    let mut removed = Vec::new();
+    let mut failure = None;
+    for run in &doomed {
+        match client.delete_run(&run.id).await {
+            Ok(()) => removed.push(run.id.clone()),
+            Err(e) => {
+                eprintln!("error: cannot remove {}: {}", short_id(&run.id), e);
+                failure.get_or_insert(exit_code::for_ipc_error(&e));
+            }
+        }
+    }
+
+    if !output.print_structured(&Removal {
+        dry_run,
+        runs: &doomed,
+        removed: &removed,
+    }) {
+        for id in &removed {
+            println!("Removed run {}", short_id(id));
+        }
+    }
+    if let Some(code) = failure {
+        std::process::exit(code);
+    }
+}
+
+/// Count what deleting `run` takes with it.
+async fn assess(client: &HotwiredClient, run: RunSummary) -> DoomedRun {
+    let artifacts = client.artifact_list(&run.id).await.ok();
+    let total = |count: fn(&ArtifactSummary) -> i64| {
+        artifacts
+            .as_ref()
+            .map(|list| list.iter().map(count).sum::<i64>())
+    };
+    DoomedRun {
+        artifacts: artifacts.as_ref().map(|list| list.len() as i64),
+        versions: total(|a| a.version_count),
+        comments: total(|a| a.comment_count),
+        id: run.id,
+        status: run.status,
+        template_name: run.template_name,
+        created_at: run.created_at,
+    }
+}
+
+/// "3 runs (5 artifacts with 12 versions, 9 comments)"
+fn describe(doomed: &[DoomedRun]) -> String {
+    let sum = |count: fn(&DoomedRun) -> Option<i64>| doomed.iter().filter_map(count).sum::<i64>();
+    let runs = match doomed.len() {
+        1 => "1 run".to_string(),
+        n => format!("{} runs", n),
+    };
+    let unknown = if doomed.iter().any(|r| r.artifacts.is_none()) {
+        ", more for runs whose artifacts couldn't be listed"
+    } else {
+        ""
+    };
+    format!(
+        "{} ({} artifacts with {} versions, {} comments{})",
+        runs,
+        sum(|r| r.artifacts),
+        sum(|r| r.versions),
+        sum(|r| r.comments),
+        unknown
+    )
+}
+
+fn plan_table(doomed: &[DoomedRun]) -> Vec<String> {
+    let count = |n: Option<i64>| n.map_or_else(|| "?".to_string(), |n| n.to_string());
+    let mut lines = vec![format!(
+        "{:<10} {:<12} {:<24} {:<20} {:>9} {:>8}",
+        "ID", "STATUS", "PLAYBOOK", "CREATED", "ARTIFACTS", "COMMENTS"
+    )];
+    for run in doomed {
+        lines.push(format!(
+            "{:<10} {:<12} {:<24} {:<20} {:>9} {:>8}",
+            short_id(&run.id),
+            run.status,
+            run.template_name.as_deref().unwrap_or("-"),
+            format_timestamp(run.created_at.as_deref().unwrap_or("-")),
+            count(run.artifacts),
+            count(run.comments)
+        ));
+    }
+    lines
+}
+
+/// Ask on the terminal; without one, only `--yes` can confirm.
+fn confirm(prompt: &str) -> bool {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        eprintln!("error: not a terminal, pass --yes to confirm");
+        std::process::exit(exit_code::USAGE);
+    }
+    eprint!("{} [y/N] ", prompt);
+    let mut answer = String::new();
+    if stdin.read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(ids: &[&str]) -> Vec<RunSummary> {
+        ids.iter()
+            .map(|id| {
+                serde_json::from_value(serde_json::json!({"id": id, "status": "active"})).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matching_prefers_exact_ids() {
+        let runs = runs(&["abc1", "abc12", "a1b2c3d4-e5f6"]);
+        let ids = |prefix: &str| -> Vec<&str> {
+            matching(&runs, prefix)
+                .iter()
+                .map(|r| r.id.as_str())
+                .collect()
+        };
+        assert_eq!(ids("abc"), ["abc1", "abc12"]);
+        assert_eq!(ids("abc1"), ["abc1"]);
+        assert_eq!(ids("a1b2c3d4e5f6"), ["a1b2c3d4-e5f6"]);
+        assert!(ids("ffff").is_empty());
     }
 }
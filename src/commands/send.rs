@@ -3,36 +3,42 @@
 //! The `send` command sends a handoff or message to another agent or the human operator.
 //! Requires an active session attached to a run.
 
-use super::{handle_error, truncate, validate};
+use super::{handle_error, truncate, validate, OutputFormat};
 use crate::ipc::HotwiredClient;
+use crate::models::HandoffParams;
+use serde::Serialize;
 
-pub async fn run(client: &HotwiredClient, to: &str, message: &str) {
+/// Structured result of `send`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Sent<'a> {
+    run_id: &'a str,
+    to: &'a str,
+}
+
+pub async fn run(client: &HotwiredClient, to: &str, message: &str, output: OutputFormat) {
     // Validate session first
     let state = validate::require_session(client).await;
 
     match client
-        .request(
-            "handoff",
-            serde_json::json!({
-                "runId": state.run_id,
-                "to": to,
-                "summary": truncate(message, 50),
-                "details": message,
-                "source": state.role_id,
-            }),
-        )
+        .handoff(&HandoffParams {
+            run_id: state.run_id.clone(),
+            to: to.to_string(),
+            summary: truncate(message, 50),
+            details: message.to_string(),
+            source: state.role_id,
+        })
         .await
     {
-        Ok(response) if response.success => {
+        Ok(()) => {
+            if output.print_structured(&Sent {
+                run_id: &state.run_id,
+                to,
+            }) {
+                return;
+            }
             println!("Sent to {}", to);
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "failed to send".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
@@ -1,5 +1,16 @@
-use super::handle_error;
+use super::filter::ListFilters;
+use super::{exit_code, handle_error, OutputFormat};
 use crate::ipc::HotwiredClient;
+use crate::models::{DeregisterSessionParams, RegisterSessionParams};
+use serde::Serialize;
+
+/// Structured result of session register/deregister/remove
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionChange<'a> {
+    session_name: &'a str,
+    action: &'a str,
+}
 
 /// Format session status for display - make it human-readable
 fn format_status(status: &str) -> &str {
@@ -13,20 +24,19 @@ fn format_status(status: &str) -> &str {
     }
 }
 
-pub async fn list(client: &HotwiredClient) {
-    match client
-        .request("list_active_sessions", serde_json::json!({}))
-        .await
-    {
-        Ok(response) if response.success => {
-            let sessions = response
-                .data
-                .as_ref()
-                .and_then(|d| d.get("sessions"))
-                .and_then(|s| s.as_array())
-                .cloned()
-                .unwrap_or_default();
-
+pub async fn list(client: &HotwiredClient, filters: &ListFilters, output: OutputFormat) {
+    match client.list_active_sessions().await {
+        Ok(sessions) => {
+            // Run-based filters need the runs sessions are attached to
+            let runs = if filters.needs_runs() {
+                client.list_runs().await.unwrap_or_else(|e| handle_error(e))
+            } else {
+                Vec::new()
+            };
+            let sessions = filters.apply_to_sessions(sessions, &runs);
+            if output.print_structured_list(&sessions) {
+                return;
+            }
             if sessions.is_empty() {
                 println!("No active sessions.");
                 return;
@@ -38,16 +48,10 @@ pub async fn list(client: &HotwiredClient) {
             );
 
             for s in &sessions {
-                let name = s.get("sessionName").and_then(|v| v.as_str()).unwrap_or("-");
-                let project = s.get("projectDir").and_then(|v| v.as_str()).unwrap_or("-");
-                let status = s
-                    .get("sessionStatus")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown");
-                let run_id = s.get("attachedRunId").and_then(|v| v.as_str());
+                let run_id = s.attached_run_id.as_deref();
                 // Only show role if attached to a run (role is meaningless without one)
                 let role = if run_id.is_some() {
-                    s.get("roleId").and_then(|v| v.as_str()).unwrap_or("-")
+                    s.role_id.as_deref().unwrap_or("-")
                 } else {
                     "-"
                 };
@@ -61,155 +65,108 @@ pub async fn list(client: &HotwiredClient) {
 
                 println!(
                     "{:<28} {:<12} {:<12} {:<14} {}",
-                    name,
-                    format_status(status),
+                    s.session_name,
+                    format_status(s.session_status.as_deref().unwrap_or("unknown")),
                     role,
                     run_display,
-                    project
+                    s.project_dir.as_deref().unwrap_or("-")
                 );
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown error".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
 
-pub async fn show(client: &HotwiredClient, name: &str) {
-    match client
-        .request("list_active_sessions", serde_json::json!({}))
-        .await
-    {
-        Ok(response) if response.success => {
-            let sessions = response
-                .data
-                .as_ref()
-                .and_then(|d| d.get("sessions"))
-                .and_then(|s| s.as_array())
-                .cloned()
-                .unwrap_or_default();
-
-            let session = sessions
-                .iter()
-                .find(|s| s.get("sessionName").and_then(|v| v.as_str()) == Some(name));
-
-            match session {
-                Some(s) => {
-                    let session_name = s.get("sessionName").and_then(|v| v.as_str()).unwrap_or("-");
-                    let project = s.get("projectDir").and_then(|v| v.as_str()).unwrap_or("-");
-                    let worktree = s
-                        .get("isWorktree")
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(false);
-                    let git_dir = s.get("gitCommonDir").and_then(|v| v.as_str());
-                    let status = s
-                        .get("sessionStatus")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown");
-                    let run_id = s.get("attachedRunId").and_then(|v| v.as_str());
-                    let role = s.get("roleId").and_then(|v| v.as_str());
-
-                    println!("Session:  {}", session_name);
-                    println!("Status:   {}", format_status(status));
-                    println!("Project:  {}", project);
-                    println!("Worktree: {}", if worktree { "yes" } else { "no" });
-                    if let Some(dir) = git_dir {
-                        println!("Git dir:  {}", dir);
-                    }
-                    if let Some(rid) = run_id {
-                        println!("Run:      {}", rid);
-                    }
-                    if let Some(r) = role {
-                        println!("Role:     {}", r);
-                    }
+pub async fn show(client: &HotwiredClient, name: &str, output: OutputFormat) {
+    match client.list_active_sessions().await {
+        Ok(sessions) => match sessions.iter().find(|s| s.session_name == name) {
+            Some(s) => {
+                if output.print_structured(s) {
+                    return;
+                }
+                println!("Session:  {}", s.session_name);
+                println!(
+                    "Status:   {}",
+                    format_status(s.session_status.as_deref().unwrap_or("unknown"))
+                );
+                println!("Project:  {}", s.project_dir.as_deref().unwrap_or("-"));
+                println!("Worktree: {}", if s.is_worktree { "yes" } else { "no" });
+                if let Some(dir) = &s.git_common_dir {
+                    println!("Git dir:  {}", dir);
+                }
+                if let Some(rid) = &s.attached_run_id {
+                    println!("Run:      {}", rid);
                 }
-                None => {
-                    eprintln!("error: no session '{}'", name);
-                    std::process::exit(1);
+                if let Some(r) = &s.role_id {
+                    println!("Role:     {}", r);
                 }
             }
-        }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown error".into())
-            );
-            std::process::exit(1);
-        }
+            None => {
+                eprintln!("error: no session '{}'", name);
+                std::process::exit(exit_code::NOT_FOUND);
+            }
+        },
         Err(e) => handle_error(e),
     }
 }
 
-pub async fn remove(client: &HotwiredClient, name: &str) {
+pub async fn remove(client: &HotwiredClient, name: &str, output: OutputFormat) {
     match client
-        .request(
-            "deregister_session",
-            serde_json::json!({"sessionName": name}),
-        )
+        .deregister_session(&DeregisterSessionParams {
+            session_name: name.to_string(),
+        })
         .await
     {
-        Ok(response) if response.success => {
+        Ok(()) => {
+            if output.print_structured(&SessionChange {
+                session_name: name,
+                action: "removed",
+            }) {
+                return;
+            }
             println!("Removed session {}", name);
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown error".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
 
-pub async fn register(client: &HotwiredClient, session: &str, project: &str) {
+pub async fn register(client: &HotwiredClient, session: &str, project: &str, output: OutputFormat) {
     match client
-        .request(
-            "register_session",
-            serde_json::json!({
-                "sessionName": session,
-                "projectDir": project
-            }),
-        )
+        .register_session(&RegisterSessionParams {
+            session_name: session.to_string(),
+            project_dir: project.to_string(),
+        })
         .await
     {
-        Ok(response) if response.success => {
+        Ok(()) => {
+            if output.print_structured(&SessionChange {
+                session_name: session,
+                action: "registered",
+            }) {
+                return;
+            }
             println!("Registered session {}", session);
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown error".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
 
-pub async fn deregister(client: &HotwiredClient, session: &str) {
+pub async fn deregister(client: &HotwiredClient, session: &str, output: OutputFormat) {
     match client
-        .request(
-            "deregister_session",
-            serde_json::json!({"sessionName": session}),
-        )
+        .deregister_session(&DeregisterSessionParams {
+            session_name: session.to_string(),
+        })
         .await
     {
-        Ok(response) if response.success => {
+        Ok(()) => {
+            if output.print_structured(&SessionChange {
+                session_name: session,
+                action: "deregistered",
+            }) {
+                return;
+            }
             println!("Deregistered session {}", session);
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response.error.unwrap_or_else(|| "unknown error".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
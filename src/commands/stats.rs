@@ -0,0 +1,473 @@
+//! Run analytics
+//!
+//! `run stats` turns a run's conversation events into numbers for tuning
+//! playbooks: how long each phase took, who hands off to whom, how often and
+//! how long agents were blocked, how many artifact versions came out of it
+//! and who completed what. Without a run ID it adds up every run matching
+//! the `run list` filters.
+//!
+//! Durations come from event timestamps. A phase lasts until the next phase
+//! change; the last one until the run's final event, or until now while the
+//! run is still live. An impediment blocks until the next resolution.
+
+use super::filter::ListFilters;
+use super::run::{resolve_in, short_id};
+use super::time::{self, format_duration};
+use super::timeline::{self, field, EntryKind};
+use super::{handle_error, OutputFormat};
+use crate::ipc::{HotwiredClient, IpcError};
+use crate::models::{ConversationEvent, RunSummary};
+use chrono::{DateTime, Duration, Utc};
+use hotwired_client::session::TERMINAL_RUN_STATUSES;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Impediments reported without a type
+const UNTYPED: &str = "unspecified";
+
+/// Numbers for one run, or summed over several
+#[derive(Debug, Default)]
+struct Stats {
+    runs: usize,
+    duration: Duration,
+    /// Phases in the order they were first reached
+    phase_order: Vec<String>,
+    /// Phase -> (runs that reached it, total time in it)
+    phases: BTreeMap<String, (usize, Duration)>,
+    handoffs: BTreeMap<(String, String), usize>,
+    /// Impediment type -> (count, time blocked)
+    impediments: BTreeMap<String, (usize, Duration)>,
+    blocked_by_role: BTreeMap<String, Duration>,
+    artifact_versions: i64,
+    completions: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    /// Stats for one run from its events. `artifact_versions` comes from
+    /// `artifact_list` when core can provide it.
+    fn of_run(
+        run: &RunSummary,
+        events: &[ConversationEvent],
+        artifact_versions: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let at = |e: &ConversationEvent| e.created_at.as_deref().and_then(time::parse_timestamp);
+        let first_event = events.iter().find_map(at);
+        let start = run
+            .created_at
+            .as_deref()
+            .and_then(time::parse_timestamp)
+            .or(first_event)
+            .unwrap_or(now);
+        let end = if TERMINAL_RUN_STATUSES.contains(&run.status.as_str()) {
+            events
+                .iter()
+                .filter_map(at)
+                .max()
+                .unwrap_or(start)
+                .max(start)
+        } else {
+            now
+        };
+
+        let mut stats = Stats {
+            runs: 1,
+            duration: end - start,
+            ..Default::default()
+        };
+        let mut phase_time: BTreeMap<String, Duration> = BTreeMap::new();
+
+        // Phase the run is in, and since when
+        let initial = events
+            .iter()
+            .find(|e| EntryKind::of(e) == EntryKind::Phase)
+            .and_then(|e| field(e, "fromPhase"))
+            .or(run.phase.as_deref())
+            .unwrap_or("start")
+            .to_string();
+        let mut phase = (initial, start);
+        stats.enter(&phase.0);
+        // (type, role, since) of impediments not yet resolved
+        let mut open: Vec<(String, String, DateTime<Utc>)> = Vec::new();
+        let mut synced_versions = 0;
+
+        for event in events {
+            let when = at(event).unwrap_or(phase.1);
+            let role = event.source.clone().unwrap_or_else(|| "?".to_string());
+            match EntryKind::of(event) {
+                EntryKind::Phase => {
+                    if let Some(to) = field(event, "toPhase") {
+                        *phase_time.entry(phase.0.clone()).or_default() += when - phase.1;
+                        phase = (to.to_string(), when);
+                        stats.enter(to);
+                    }
+                }
+                EntryKind::Handoff => {
+                    let to = field(event, "to").unwrap_or("?");
+                    *stats.handoffs.entry((role, to.to_string())).or_default() += 1;
+                }
+                EntryKind::TaskComplete => *stats.completions.entry(role).or_default() += 1,
+                EntryKind::ImpedimentRaised => {
+                    let kind = field(event, "impedimentType").unwrap_or(UNTYPED);
+                    stats.impediments.entry(kind.to_string()).or_default().0 += 1;
+                    open.push((kind.to_string(), role, when));
+                }
+                EntryKind::ImpedimentResolved => {
+                    for (kind, raised_by, since) in open.drain(..) {
+                        stats.block(kind, raised_by, when - since);
+                    }
+                }
+                EntryKind::ArtifactSync => synced_versions += 1,
+                _ => {}
+            }
+        }
+
+        *phase_time.entry(phase.0).or_default() += end - phase.1;
+        for (kind, raised_by, since) in open {
+            stats.block(kind, raised_by, end - since);
+        }
+        stats.phases = phase_time
+            .into_iter()
+            .map(|(phase, time)| (phase, (1, time.max(Duration::zero()))))
+            .collect();
+        stats.artifact_versions = artifact_versions.unwrap_or(synced_versions);
+        stats
+    }
+
+    fn enter(&mut self, phase: &str) {
+        if !self.phase_order.iter().any(|p| p == phase) {
+            self.phase_order.push(phase.to_string());
+        }
+    }
+
+    fn block(&mut self, kind: String, role: String, time: Duration) {
+        let time = time.max(Duration::zero());
+        self.impediments.entry(kind).or_default().1 += time;
+        *self.blocked_by_role.entry(role).or_default() += time;
+    }
+
+    /// Add `other`'s numbers to these.
+    fn merge(&mut self, other: Stats) {
+        self.runs += other.runs;
+        self.duration += other.duration;
+        for phase in &other.phase_order {
+            self.enter(phase);
+        }
+        for (phase, (runs, time)) in other.phases {
+            let entry = self.phases.entry(phase).or_default();
+            entry.0 += runs;
+            entry.1 += time;
+        }
+        for (pair, count) in other.handoffs {
+            *self.handoffs.entry(pair).or_default() += count;
+        }
+        for (kind, (count, time)) in other.impediments {
+            let entry = self.impediments.entry(kind).or_default();
+            entry.0 += count;
+            entry.1 += time;
+        }
+        for (role, time) in other.blocked_by_role {
+            *self.blocked_by_role.entry(role).or_default() += time;
+        }
+        self.artifact_versions += other.artifact_versions;
+        for (role, count) in other.completions {
+            *self.completions.entry(role).or_default() += count;
+        }
+    }
+
+    fn report(&self, run_id: Option<String>) -> StatsReport {
+        StatsReport {
+            run_id,
+            runs: self.runs,
+            duration_secs: self.duration.num_seconds(),
+            phases: self
+                .phase_order
+                .iter()
+                .filter_map(|phase| {
+                    let (runs, time) = self.phases.get(phase)?;
+                    Some(PhaseStat {
+                        phase: phase.clone(),
+                        runs: *runs,
+                        total_secs: time.num_seconds(),
+                        mean_secs: time.num_seconds() / (*runs).max(1) as i64,
+                    })
+                })
+                .collect(),
+            handoffs: self
+                .handoffs
+                .iter()
+                .map(|((from, to), count)| HandoffStat {
+                    from: from.clone(),
+                    to: to.clone(),
+                    count: *count,
+                })
+                .collect(),
+            impediments: self
+                .impediments
+                .iter()
+                .map(|(kind, (count, time))| ImpedimentStat {
+                    impediment_type: kind.clone(),
+                    count: *count,
+                    blocked_secs: time.num_seconds(),
+                })
+                .collect(),
+            blocked_by_role: self
+                .blocked_by_role
+                .iter()
+                .map(|(role, time)| (role.clone(), time.num_seconds()))
+                .collect(),
+            artifact_versions: self.artifact_versions,
+            completions: self.completions.clone(),
+        }
+    }
+}
+
+/// Structured result of `run stats`; durations in seconds
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsReport {
+    /// Absent for aggregates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    pub runs: usize,
+    pub duration_secs: i64,
+    pub phases: Vec<PhaseStat>,
+    pub handoffs: Vec<HandoffStat>,
+    pub impediments: Vec<ImpedimentStat>,
+    pub blocked_by_role: BTreeMap<String, i64>,
+    pub artifact_versions: i64,
+    pub completions: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseStat {
+    pub phase: String,
+    /// Runs that reached this phase
+    pub runs: usize,
+    pub total_secs: i64,
+    pub mean_secs: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffStat {
+    pub from: String,
+    pub to: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpedimentStat {
+    pub impediment_type: String,
+    pub count: usize,
+    pub blocked_secs: i64,
+}
+
+pub async fn run(
+    client: &HotwiredClient,
+    id: Option<&str>,
+    filters: &ListFilters,
+    output: OutputFormat,
+) {
+    let runs = match client.list_runs().await {
+        Ok(runs) => runs,
+        Err(e) => handle_error(e),
+    };
+    let selected = match id {
+        Some(id) => vec![resolve_in(client, runs, id).await],
+        None => filters.apply_to_runs(runs),
+    };
+
+    let now = Utc::now();
+    let mut total = Stats::default();
+    for run in &selected {
+        match run_stats(client, run, now).await {
+            Ok(stats) => total.merge(stats),
+            Err(e) => handle_error(e),
+        }
+    }
+
+    let report = total.report(id.and(selected.first().map(|r| r.id.clone())));
+    if output.print_structured(&report) {
+        return;
+    }
+    print_report(&report);
+}
+
+/// Stats of a single run as of `now`.
+pub async fn report_for(
+    client: &HotwiredClient,
+    run: &RunSummary,
+    now: DateTime<Utc>,
+) -> Result<StatsReport, IpcError> {
+    let stats = run_stats(client, run, now).await?;
+    Ok(stats.report(Some(run.id.clone())))
+}
+
+async fn run_stats(
+    client: &HotwiredClient,
+    run: &RunSummary,
+    now: DateTime<Utc>,
+) -> Result<Stats, IpcError> {
+    let events = timeline::fetch_all_events(client, &run.id).await?;
+    let versions = client
+        .artifact_list(&run.id)
+        .await
+        .ok()
+        .map(|list| list.iter().map(|a| a.version_count).sum())
+        // Cores that don't count versions report 0; count syncs instead
+        .filter(|&n: &i64| n > 0);
+    Ok(Stats::of_run(run, &events, versions, now))
+}
+
+fn print_report(report: &StatsReport) {
+    let secs = |s: i64| format_duration(Duration::seconds(s));
+    let aggregate = report.run_id.is_none();
+
+    match &report.run_id {
+        Some(id) => println!("Run:        {}", short_id(id)),
+        None => println!("Runs:       {}", report.runs),
+    }
+    println!("Duration:   {}", secs(report.duration_secs));
+    println!("Artifacts:  {} versions", report.artifact_versions);
+
+    println!();
+    println!("Phases:");
+    if report.phases.is_empty() {
+        println!("  -");
+    }
+    for phase in &report.phases {
+        if aggregate {
+            println!(
+                "  {:<20} {:>10} total  {:>10} mean over {} runs",
+                phase.phase,
+                secs(phase.total_secs),
+                secs(phase.mean_secs),
+                phase.runs
+            );
+        } else {
+            println!("  {:<20} {:>10}", phase.phase, secs(phase.total_secs));
+        }
+    }
+
+    println!();
+    println!("Handoffs:");
+    if report.handoffs.is_empty() {
+        println!("  -");
+    }
+    for handoff in &report.handoffs {
+        println!(
+            "  {:<30} {:>4}",
+            format!("{} -> {}", handoff.from, handoff.to),
+            handoff.count
+        );
+    }
+
+    println!();
+    println!("Impediments:");
+    if report.impediments.is_empty() {
+        println!("  -");
+    }
+    for impediment in &report.impediments {
+        println!(
+            "  {:<20} {:>4}  {:>10} blocked",
+            impediment.impediment_type,
+            impediment.count,
+            secs(impediment.blocked_secs)
+        );
+    }
+    for (role, blocked) in &report.blocked_by_role {
+        println!("  raised by {:<10} {:>10} blocked", role, secs(*blocked));
+    }
+
+    println!();
+    println!("Completed tasks:");
+    if report.completions.is_empty() {
+        println!("  -");
+    }
+    for (role, count) in &report.completions {
+        println!("  {:<20} {:>4}", role, count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(s: &str) -> DateTime<Utc> {
+        time::parse_timestamp(s).unwrap()
+    }
+
+    fn run(status: &str) -> RunSummary {
+        RunSummary {
+            id: "r1".to_string(),
+            status: status.to_string(),
+            phase: None,
+            template_name: None,
+            template_id: None,
+            project_path: None,
+            created_at: Some("2024-01-15T10:00:00Z".to_string()),
+        }
+    }
+
+    fn events() -> Vec<ConversationEvent> {
+        serde_json::from_value(serde_json::json!([
+            {"source": "strategist", "eventType": "handoff", "to": "builder", "createdAt": "2024-01-15T10:10:00Z"},
+            {"eventType": "phase_change", "fromPhase": "planning", "toPhase": "executing", "createdAt": "2024-01-15T10:30:00Z"},
+            {"source": "builder", "eventType": "report_impediment", "impedimentType": "technical", "createdAt": "2024-01-15T11:00:00Z"},
+            {"source": "builder", "eventType": "resolve_run_impediments", "createdAt": "2024-01-15T11:45:00Z"},
+            {"source": "builder", "eventType": "task_complete", "createdAt": "2024-01-15T12:00:00Z"},
+            {"source": "builder", "eventType": "artifact_sync", "path": "PLAN.md", "createdAt": "2024-01-15T12:00:00Z"},
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_finished_run_ends_at_last_event() {
+        let stats = Stats::of_run(
+            &run("completed"),
+            &events(),
+            None,
+            ts("2024-02-01T00:00:00Z"),
+        );
+        let report = stats.report(Some("r1".to_string()));
+        assert_eq!(report.duration_secs, 2 * 3600);
+        let phases: Vec<(&str, i64)> = report
+            .phases
+            .iter()
+            .map(|p| (p.phase.as_str(), p.total_secs))
+            .collect();
+        assert_eq!(phases, [("planning", 1800), ("executing", 5400)]);
+        assert_eq!(report.handoffs[0].count, 1);
+        assert_eq!(report.impediments[0].blocked_secs, 45 * 60);
+        assert_eq!(report.blocked_by_role["builder"], 45 * 60);
+        assert_eq!(report.completions["builder"], 1);
+        assert_eq!(report.artifact_versions, 1);
+    }
+
+    #[test]
+    fn test_live_run_and_open_impediments_run_until_now() {
+        let mut events = events();
+        events.remove(3);
+        let stats = Stats::of_run(&run("active"), &events, Some(7), ts("2024-01-15T13:00:00Z"));
+        let report = stats.report(None);
+        assert_eq!(report.duration_secs, 3 * 3600);
+        assert_eq!(report.impediments[0].blocked_secs, 2 * 3600);
+        assert_eq!(report.artifact_versions, 7);
+    }
+
+    #[test]
+    fn test_merge_averages_phases() {
+        let now = ts("2024-02-01T00:00:00Z");
+        let mut total = Stats::of_run(&run("completed"), &events(), None, now);
+        total.merge(Stats::of_run(&run("completed"), &events()[..2], None, now));
+        let report = total.report(None);
+        assert_eq!(report.runs, 2);
+        let planning = &report.phases[0];
+        assert_eq!((planning.runs, planning.total_secs), (2, 3600));
+        assert_eq!(planning.mean_secs, 1800);
+        assert_eq!(report.handoffs[0].count, 2);
+    }
+}
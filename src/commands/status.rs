@@ -2,87 +2,84 @@
 //!
 //! The `status` command shows the status of the attached run and connected agents.
 
-use super::{handle_error, validate};
+use super::{handle_error, validate, OutputFormat};
 use crate::ipc::HotwiredClient;
+use crate::models::RunStatus;
+use hotwired_client::Multiplexer;
+use serde::Serialize;
 
-pub async fn run(client: &HotwiredClient) {
+/// Structured result of `status`: who the caller is, plus the run
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusView<'a> {
+    role_id: &'a str,
+    session: &'a str,
+    run: &'a RunStatus,
+}
+
+pub async fn run(client: &HotwiredClient, output: OutputFormat) {
     // Validate session first
     let state = validate::require_session(client).await;
 
     // Get detailed run info
-    match client
-        .request(
-            "get_run_status",
-            serde_json::json!({
-                "runId": state.run_id,
-            }),
-        )
-        .await
-    {
-        Ok(response) if response.success => {
-            let data = response.data.unwrap_or_default();
-
-            let status = data.get("status").and_then(|v| v.as_str()).unwrap_or("-");
-            let phase = data.get("phase").and_then(|v| v.as_str()).unwrap_or("-");
-            let playbook = data
-                .get("templateName")
-                .and_then(|v| v.as_str())
-                .unwrap_or("-");
+    match client.get_run_status(&state.run_id).await {
+        Ok(run) => {
+            if output.print_structured(&StatusView {
+                role_id: &state.role_id,
+                session: &state.terminal.session_key,
+                run: &run,
+            }) {
+                return;
+            }
 
             // Identity block — make it unambiguous who the calling agent is
             println!("YOU ARE:  {}", state.role_id);
-            println!(
-                "Session:  {} (auto-detected from Zellij)",
-                state.zellij_session
-            );
+            match state.terminal.multiplexer {
+                Multiplexer::Explicit => println!(
+                    "Session:  {} (set via --session/HOTWIRED_SESSION)",
+                    state.terminal.session_key
+                ),
+                detected => println!(
+                    "Session:  {} (auto-detected from {})",
+                    state.terminal.session_key, detected
+                ),
+            }
             println!();
             println!("Run:      {}", state.run_id);
-            println!("Status:   {}", status);
-            println!("Phase:    {}", phase);
-            println!("Playbook: {}", playbook);
+            match &run.status_reason {
+                Some(reason) => println!("Status:   {} ({})", run.status, reason),
+                None => println!("Status:   {}", run.status),
+            }
+            println!("Phase:    {}", run.phase.as_deref().unwrap_or("-"));
+            println!("Playbook: {}", run.template_name.as_deref().unwrap_or("-"));
             println!();
 
             // Print connected agents with clear "you" marker
-            if let Some(agents) = data.get("connectedAgents").and_then(|v| v.as_array()) {
-                println!("Connected Agents:");
-                for agent in agents {
-                    let role = agent.get("roleId").and_then(|v| v.as_str()).unwrap_or("-");
-                    let is_me = role == state.role_id;
-                    if is_me {
-                        println!("  > {} (you)", role);
-                    } else {
-                        println!("  - {}", role);
-                    }
+            println!("Connected Agents:");
+            for agent in &run.connected_agents {
+                if agent.role_id == state.role_id {
+                    println!("  > {} (you)", agent.role_id);
+                } else {
+                    println!("  - {}", agent.role_id);
                 }
             }
 
+            if run.status == "paused" {
+                println!();
+                println!("RUN PAUSED: finish what you're doing and wait for it to be resumed.");
+            }
+
             // Show impediments when run is blocked
-            if let Some(impediments) = data.get("impediments").and_then(|v| v.as_array()) {
-                if !impediments.is_empty() {
-                    println!();
-                    println!("BLOCKED BY:");
-                    for imp in impediments {
-                        let source = imp.get("source").and_then(|v| v.as_str()).unwrap_or("-");
-                        let desc = imp
-                            .get("description")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("-");
-                        println!("  - [{}]: {}", source, desc);
-                    }
-                    println!();
-                    println!("To resolve: hotwired resolve \"<reason>\"");
+            if !run.impediments.is_empty() {
+                println!();
+                println!("BLOCKED BY:");
+                for imp in &run.impediments {
+                    println!("  - [{}]: {}", imp.source, imp.description);
                 }
+                println!();
+                println!("To resolve: hotwired resolve \"<reason>\"");
             }
         }
-        Ok(response) => {
-            eprintln!(
-                "error: {}",
-                response
-                    .error
-                    .unwrap_or_else(|| "failed to get status".into())
-            );
-            std::process::exit(1);
-        }
         Err(e) => handle_error(e),
     }
 }
@@ -0,0 +1,132 @@
+//! Time arguments and timestamps
+//!
+//! Flags like `--since` take either a point in time (`2024-01-15`,
+//! `2024-01-15 10:30:00`, RFC 3339) or an age relative to now (`30m`, `12h`,
+//! `7d`, `2w`). Dates without a zone are UTC, matching what core stores.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+
+/// Parse an age like `7d` (units: s, m, h, d, w). Ages reaching back
+/// further than a `DateTime` can represent are out of range.
+pub fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("'{}' is missing a unit (s, m, h, d or w)", s))?;
+    let (amount, unit) = s.split_at(split);
+    if amount.is_empty() {
+        return Err(format!("'{}' doesn't start with a number", s));
+    }
+    let out_of_range = || format!("'{}' is out of range", s);
+    let amount: i64 = amount.parse().map_err(|_| out_of_range())?;
+    let age = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => return Err(format!("unknown unit '{}' (use s, m, h, d or w)", unit)),
+    };
+    age.filter(|&age| Utc::now().checked_sub_signed(age).is_some())
+        .ok_or_else(out_of_range)
+}
+
+/// Parse a `--since`/`--until` value into an instant.
+pub fn parse_instant(s: &str) -> Result<DateTime<Utc>, String> {
+    let s = s.trim();
+    if let Some(ts) = parse_timestamp(s) {
+        return Ok(ts);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    let looks_like_age =
+        s.starts_with(|c: char| c.is_ascii_digit()) && s.ends_with(['s', 'm', 'h', 'd', 'w']);
+    match parse_age(s) {
+        Ok(age) => Utc::now()
+            .checked_sub_signed(age)
+            .ok_or_else(|| format!("'{}' is out of range", s)),
+        Err(e) if looks_like_age => Err(e),
+        Err(_) => Err(format!(
+            "'{}' is not a date (2024-01-15), timestamp or age (7d, 12h)",
+            s
+        )),
+    }
+}
+
+/// Parse a timestamp from core (RFC 3339, or a zone-less date-time as UTC).
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .map(|ts| ts.and_utc())
+}
+
+/// Human-readable duration: `45s`, `12m 5s`, `3h 20m`, `2d 4h`.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.num_seconds().max(0);
+    let (days, hours, mins, secs) = (
+        secs / 86_400,
+        secs % 86_400 / 3_600,
+        secs % 3_600 / 60,
+        secs % 60,
+    );
+    match (days, hours, mins) {
+        (0, 0, 0) => format!("{}s", secs),
+        (0, 0, _) => format!("{}m {}s", mins, secs),
+        (0, _, _) => format!("{}h {}m", hours, mins),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_age("90m"), Ok(Duration::minutes(90)));
+        assert!(parse_age("7").is_err());
+        assert!(parse_age("7y").is_err());
+        assert!(parse_age("d").is_err());
+    }
+
+    #[test]
+    fn test_huge_ages_are_out_of_range() {
+        assert_eq!(
+            parse_age("99999999999999d"),
+            Err("'99999999999999d' is out of range".to_string())
+        );
+        assert!(parse_age("99999999999999999999s").is_err());
+        // Fits a Duration, but not before now
+        assert!(parse_age("9999999999w").is_err());
+        assert_eq!(
+            parse_instant("9999999999w"),
+            Err("'9999999999w' is out of range".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_instant_accepts_dates_and_timestamps() {
+        let day = parse_instant("2024-01-15").unwrap();
+        assert_eq!(day.to_rfc3339(), "2024-01-15T00:00:00+00:00");
+        // The format `format_timestamp` prints
+        let ts = parse_instant("2024-01-15 10:30:00").unwrap();
+        assert_eq!(ts.to_rfc3339(), "2024-01-15T10:30:00+00:00");
+        assert_eq!(parse_timestamp("2024-01-15T12:30:00+02:00"), Some(ts));
+        assert!(parse_instant("last tuesday").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::seconds(45)), "45s");
+        assert_eq!(format_duration(Duration::seconds(725)), "12m 5s");
+        assert_eq!(format_duration(Duration::minutes(200)), "3h 20m");
+        assert_eq!(format_duration(Duration::hours(52)), "2d 4h");
+    }
+}
@@ -0,0 +1,387 @@
+//! A run's history, reconstructed from its conversation events
+//!
+//! Core records everything that happens in a run as conversation events:
+//! handoffs, task completions, impediments, artifact syncs and so on. This
+//! module pages through all of them and sorts each into a `EntryKind`, so
+//! `run show --timeline` (and anything else that summarizes a run) reads the
+//! same history. Event types not listed in `models::event_type` are kept as
+//! `Other`.
+
+use super::format_timestamp;
+use crate::ipc::{HotwiredClient, IpcError};
+use crate::models::{event_type, ConversationEvent, ConversationEventsParams};
+use serde::Serialize;
+
+/// Events fetched per `get_conversation_events` request
+const PAGE_SIZE: u32 = 100;
+
+/// Every event of a run, oldest first.
+pub async fn fetch_all_events(
+    client: &HotwiredClient,
+    run_id: &str,
+) -> Result<Vec<ConversationEvent>, IpcError> {
+    let mut events: Vec<ConversationEvent> = Vec::new();
+    let mut last_seq = 0;
+
+    loop {
+        let page = client
+            .get_conversation_events(&ConversationEventsParams {
+                run_id: run_id.to_string(),
+                since_sequence: Some(last_seq),
+                limit: PAGE_SIZE,
+            })
+            .await?;
+        let max_seq = page.iter().filter_map(|e| e.sequence_num).max();
+        // Skip anything core sends again from before the cursor
+        let first = last_seq == 0;
+        events.extend(
+            page.into_iter()
+                .filter(|e| first || e.sequence_num.is_none_or(|seq| seq > last_seq)),
+        );
+
+        // Core may cap pages below `limit`, so only an empty page ends the
+        // history; a page that doesn't advance the cursor would repeat forever
+        match max_seq {
+            Some(seq) if seq > last_seq => last_seq = seq,
+            _ => break,
+        }
+    }
+
+    Ok(events)
+}
+
+/// What a conversation event records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    Phase,
+    Handoff,
+    TaskComplete,
+    ImpedimentRaised,
+    ImpedimentResolved,
+    ArtifactSync,
+    Comment,
+    AgentJoined,
+    AgentLeft,
+    /// Paused, resumed, cancelled, ...
+    RunState,
+    /// Plain chat between agents
+    Message,
+    Other,
+}
+
+impl EntryKind {
+    /// Classify an event by its `eventType` (see `models::event_type`).
+    pub fn of(event: &ConversationEvent) -> Self {
+        match event.event_type.as_deref().unwrap_or(event_type::MESSAGE) {
+            event_type::MESSAGE => EntryKind::Message,
+            event_type::PHASE_CHANGE => EntryKind::Phase,
+            event_type::HANDOFF => EntryKind::Handoff,
+            event_type::TASK_COMPLETE => EntryKind::TaskComplete,
+            event_type::REPORT_IMPEDIMENT => EntryKind::ImpedimentRaised,
+            event_type::RESOLVE_IMPEDIMENTS => EntryKind::ImpedimentResolved,
+            event_type::ARTIFACT_SYNC | event_type::ARTIFACT_MOVE => EntryKind::ArtifactSync,
+            event_type::ARTIFACT_ADD_COMMENT
+            | event_type::ARTIFACT_REPLY_COMMENT
+            | event_type::ARTIFACT_RESOLVE_COMMENT => EntryKind::Comment,
+            event_type::AGENT_JOINED => EntryKind::AgentJoined,
+            event_type::AGENT_LEFT => EntryKind::AgentLeft,
+            event_type::UPDATE_RUN_STATE => EntryKind::RunState,
+            _ => EntryKind::Other,
+        }
+    }
+
+    /// Short label for tables
+    pub fn label(self) -> &'static str {
+        match self {
+            EntryKind::Phase => "phase",
+            EntryKind::Handoff => "handoff",
+            EntryKind::TaskComplete => "completed",
+            EntryKind::ImpedimentRaised => "blocked",
+            EntryKind::ImpedimentResolved => "unblocked",
+            EntryKind::ArtifactSync => "artifact",
+            EntryKind::Comment => "comment",
+            EntryKind::AgentJoined => "joined",
+            EntryKind::AgentLeft => "left",
+            EntryKind::RunState => "run",
+            EntryKind::Message => "message",
+            EntryKind::Other => "event",
+        }
+    }
+}
+
+/// String field `key` of `event`'s extra data, if not empty.
+pub fn field<'a>(event: &'a ConversationEvent, key: &str) -> Option<&'a str> {
+    event
+        .extra
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+}
+
+/// Path of the artifact an artifact or comment event is about; a move
+/// names its new path.
+pub fn artifact_path(event: &ConversationEvent) -> Option<&str> {
+    match event.event_type.as_deref() {
+        Some(event_type::ARTIFACT_MOVE) => field(event, "newPath"),
+        _ => field(event, "path"),
+    }
+}
+
+/// One line of a run's history
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+    pub sequence_num: Option<i64>,
+    pub created_at: Option<String>,
+    pub role: Option<String>,
+    pub kind: EntryKind,
+    /// Raw event type, for `Other` entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    pub detail: String,
+}
+
+impl TimelineEntry {
+    pub fn from_event(event: &ConversationEvent) -> Self {
+        let kind = EntryKind::of(event);
+        Self {
+            sequence_num: event.sequence_num,
+            created_at: event.created_at.clone(),
+            role: event.source.clone(),
+            kind,
+            event_type: (kind == EntryKind::Other)
+                .then(|| event.event_type.clone())
+                .flatten(),
+            detail: detail(kind, event),
+        }
+    }
+}
+
+/// The history `run show --timeline` prints: everything but plain chat.
+pub fn build(events: &[ConversationEvent]) -> Vec<TimelineEntry> {
+    events
+        .iter()
+        .filter(|e| EntryKind::of(e) != EntryKind::Message)
+        .map(TimelineEntry::from_event)
+        .collect()
+}
+
+/// One-line description of an event, using the fields its kind carries.
+fn detail(kind: EntryKind, event: &ConversationEvent) -> String {
+    let text = first_line(event.text());
+    let with_text = |head: String| {
+        if text.is_empty() || head.contains(text) {
+            head
+        } else {
+            format!("{}: {}", head, text)
+        }
+    };
+    match kind {
+        EntryKind::Phase => match (field(event, "fromPhase"), field(event, "toPhase")) {
+            (Some(from), Some(to)) => format!("{} -> {}", from, to),
+            (None, Some(to)) => format!("-> {}", to),
+            _ => text.to_string(),
+        },
+        EntryKind::Handoff => match field(event, "to") {
+            Some(to) => with_text(format!("-> {}", to)),
+            None => text.to_string(),
+        },
+        EntryKind::TaskComplete => {
+            let task = field(event, "taskDescription").unwrap_or(text);
+            match field(event, "outcome") {
+                Some(outcome) => format!("{} ({})", task, outcome),
+                None => task.to_string(),
+            }
+        }
+        EntryKind::ImpedimentRaised => {
+            let description = field(event, "description").unwrap_or(text);
+            match field(event, "impedimentType") {
+                Some(kind) => format!("[{}] {}", kind, description),
+                None => description.to_string(),
+            }
+        }
+        EntryKind::ArtifactSync => {
+            let version = event.extra.get("version").and_then(|v| v.as_i64());
+            match (artifact_path(event), version) {
+                (Some(path), Some(version)) => with_text(format!("{} v{}", path, version)),
+                (Some(path), None) => with_text(path.to_string()),
+                _ => text.to_string(),
+            }
+        }
+        EntryKind::Comment => {
+            // New comments name the artifact; replies and resolutions only
+            // the comment they answer
+            let body = ["comment", "message", "reply"]
+                .into_iter()
+                .find_map(|key| field(event, key))
+                .map_or(text, first_line);
+            let head = match (artifact_path(event), field(event, "commentId")) {
+                (Some(path), _) => Some(path.to_string()),
+                (None, Some(id)) => Some(format!("comment {}", id)),
+                (None, None) => None,
+            };
+            match head {
+                Some(head) if body.is_empty() => head,
+                Some(head) => format!("{}: {}", head, body),
+                None => body.to_string(),
+            }
+        }
+        EntryKind::AgentJoined | EntryKind::AgentLeft => match field(event, "sessionName") {
+            Some(session) => with_text(session.to_string()),
+            None => text.to_string(),
+        },
+        EntryKind::RunState => match (field(event, "action"), field(event, "reason")) {
+            (Some(action), Some(reason)) => format!("{}: {}", action, reason),
+            (Some(action), None) => with_text(action.to_string()),
+            _ => text.to_string(),
+        },
+        EntryKind::ImpedimentResolved => {
+            field(event, "message").map_or(text, first_line).to_string()
+        }
+        EntryKind::Message | EntryKind::Other => text.to_string(),
+    }
+}
+
+/// First line of `s`, trimmed
+pub fn first_line(s: &str) -> &str {
+    s.lines().next().unwrap_or("").trim()
+}
+
+/// Table rendering of `entries`.
+pub fn print(entries: &[TimelineEntry]) {
+    if entries.is_empty() {
+        println!("No history recorded.");
+        return;
+    }
+    for entry in entries {
+        let label = match entry.kind {
+            EntryKind::Other => entry.event_type.as_deref().unwrap_or("event"),
+            kind => kind.label(),
+        };
+        println!(
+            "{:<19}  {:<14} {:<10} {}",
+            format_timestamp(entry.created_at.as_deref().unwrap_or("-")),
+            entry.role.as_deref().unwrap_or("-"),
+            label,
+            entry.detail
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    fn event(value: serde_json::Value) -> ConversationEvent {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_kinds_match_core_event_types() {
+        let kind = |t: &str| EntryKind::of(&event(serde_json::json!({"eventType": t})));
+        assert_eq!(kind(event_type::PHASE_CHANGE), EntryKind::Phase);
+        assert_eq!(kind(event_type::TASK_COMPLETE), EntryKind::TaskComplete);
+        assert_eq!(
+            kind(event_type::RESOLVE_IMPEDIMENTS),
+            EntryKind::ImpedimentResolved
+        );
+        assert_eq!(kind(event_type::ARTIFACT_REPLY_COMMENT), EntryKind::Comment);
+        // Other spellings aren't guessed at
+        assert_eq!(kind("Task-Completed"), EntryKind::Other);
+        assert_eq!(kind("something_new"), EntryKind::Other);
+        assert_eq!(
+            EntryKind::of(&event(serde_json::json!({}))),
+            EntryKind::Message
+        );
+    }
+
+    #[test]
+    fn test_details_use_event_fields() {
+        let entries = build(&[
+            event(serde_json::json!({"eventType": "message", "content": "hi"})),
+            event(serde_json::json!({
+                "eventType": "phase_change", "fromPhase": "planning", "toPhase": "executing",
+            })),
+            event(serde_json::json!({
+                "eventType": "handoff", "to": "builder", "content": "Start on task 1\nDetails...",
+            })),
+            event(serde_json::json!({
+                "eventType": "report_impediment", "impedimentType": "technical", "description": "No API key",
+            })),
+            event(
+                serde_json::json!({"eventType": "artifact_sync", "path": "PLAN.md", "version": 3}),
+            ),
+            event(serde_json::json!({
+                "eventType": "artifact_move", "oldPath": "PLAN.md", "newPath": "docs/PLAN.md",
+            })),
+            event(serde_json::json!({
+                "eventType": "resolve_run_impediments", "message": "Added the key\nIt's in .env",
+            })),
+            event(serde_json::json!({
+                "eventType": "artifact_add_comment", "path": "PLAN.md", "comment": "Too vague",
+            })),
+            event(serde_json::json!({
+                "eventType": "artifact_reply_comment", "commentId": "c1", "message": "Fixed",
+            })),
+            event(serde_json::json!({
+                "eventType": "artifact_resolve_comment", "commentId": "c1", "resolvedBy": "strategist",
+            })),
+        ]);
+        let details: Vec<&str> = entries.iter().map(|e| e.detail.as_str()).collect();
+        assert_eq!(
+            details,
+            [
+                "planning -> executing",
+                "-> builder: Start on task 1",
+                "[technical] No API key",
+                "PLAN.md v3",
+                "docs/PLAN.md",
+                "Added the key",
+                "PLAN.md: Too vague",
+                "comment c1: Fixed",
+                "comment c1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_all_events_follows_short_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("core.sock");
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+
+        // Core caps pages at 2 events, well below the requested limit
+        let server = tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let (read, mut write) = stream.into_split();
+                let mut line = String::new();
+                BufReader::new(read).read_line(&mut line).await.unwrap();
+                let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+                let reply = match request["method"].as_str() {
+                    Some("get_conversation_events") => {
+                        let since = request["params"]["sinceSequence"].as_i64().unwrap_or(0);
+                        let events: Vec<_> = (since + 1..=(since + 2).min(5))
+                            .map(|seq| serde_json::json!({"sequenceNum": seq}))
+                            .collect();
+                        serde_json::json!({"success": true, "data": {"events": events}})
+                    }
+                    _ => serde_json::json!({"success": false, "error": "unknown method"}),
+                };
+                write
+                    .write_all(format!("{}\n", reply).as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let client = HotwiredClient::new(Some(socket.to_string_lossy().to_string()));
+        let events = fetch_all_events(&client, "run-1").await.unwrap();
+        let sequence: Vec<i64> = events.iter().filter_map(|e| e.sequence_num).collect();
+        assert_eq!(sequence, [1, 2, 3, 4, 5]);
+
+        server.abort();
+    }
+}
@@ -0,0 +1,663 @@
+//! Readable transcripts of finished (or running) runs
+//!
+//! `run transcript` pages through a run's whole conversation and renders it
+//! as one document, grouped by phase: chat, handoffs (summary up front,
+//! details folded away), completed tasks, impediments together with how they
+//! were resolved, and the artifacts each phase touched. Markdown and HTML are
+//! for sharing and review; txt is for the terminal.
+
+use super::run::{resolve_id, short_id};
+use super::timeline::{self, field, first_line, EntryKind};
+use super::{format_timestamp, handle_error, OutputFormat};
+use crate::ipc::HotwiredClient;
+use crate::models::{ConversationEvent, RunStatus};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TranscriptFormat {
+    /// Markdown
+    #[value(alias = "markdown")]
+    Md,
+    /// Standalone HTML page
+    Html,
+    /// Plain text
+    #[value(alias = "text")]
+    Txt,
+}
+
+/// A run's conversation, grouped by phase
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Transcript {
+    run_id: String,
+    status: String,
+    template_name: Option<String>,
+    events: usize,
+    phases: Vec<PhaseSection>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhaseSection {
+    phase: String,
+    /// Artifact path -> versions synced during this phase
+    artifacts: BTreeMap<String, BTreeSet<i64>>,
+    entries: Vec<Entry>,
+}
+
+impl PhaseSection {
+    fn new(phase: &str) -> Self {
+        Self {
+            phase: phase.to_string(),
+            artifacts: BTreeMap::new(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    role: String,
+    at: String,
+    #[serde(flatten)]
+    body: Body,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Body {
+    Message {
+        text: String,
+    },
+    Handoff {
+        to: Option<String>,
+        summary: String,
+        details: String,
+    },
+    TaskComplete {
+        task: String,
+        outcome: Option<String>,
+    },
+    Impediment {
+        impediment_type: Option<String>,
+        description: String,
+        resolution: Option<Resolution>,
+    },
+    Resolved {
+        text: String,
+    },
+    /// Joins, leaves, artifact and comment activity, run state changes
+    Note {
+        label: String,
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Resolution {
+    role: String,
+    at: String,
+    text: String,
+}
+
+pub async fn run(
+    client: &HotwiredClient,
+    id: &str,
+    format: TranscriptFormat,
+    output: OutputFormat,
+) {
+    let full_id = resolve_id(client, id).await;
+    let run = match client.get_run_status(&full_id).await {
+        Ok(run) => run,
+        Err(e) => handle_error(e),
+    };
+    let events = match timeline::fetch_all_events(client, &full_id).await {
+        Ok(events) => events,
+        Err(e) => handle_error(e),
+    };
+
+    let transcript = build(&run, &events);
+    if output.print_structured(&transcript) {
+        return;
+    }
+    let document = match format {
+        TranscriptFormat::Md => render_markdown(&transcript),
+        TranscriptFormat::Html => render_html(&transcript),
+        TranscriptFormat::Txt => render_text(&transcript),
+    };
+    print!("{}", document);
+}
+
+fn build(run: &RunStatus, events: &[ConversationEvent]) -> Transcript {
+    // Before the first phase change the run is in that change's "from" phase
+    let first_phase = events
+        .iter()
+        .find(|e| EntryKind::of(e) == EntryKind::Phase)
+        .map(|e| field(e, "fromPhase").unwrap_or("start"))
+        .or(run.phase.as_deref())
+        .unwrap_or("start");
+    let mut phases = vec![PhaseSection::new(first_phase)];
+    // (phase, entry) of impediments not yet resolved
+    let mut open: Vec<(usize, usize)> = Vec::new();
+
+    for event in events {
+        let kind = EntryKind::of(event);
+        let role = event.source.clone().unwrap_or_else(|| "?".to_string());
+        let at = format_timestamp(event.created_at.as_deref().unwrap_or(""));
+        let text = event.text().trim().to_string();
+
+        if kind == EntryKind::Phase {
+            if let Some(to) = field(event, "toPhase") {
+                phases.push(PhaseSection::new(to));
+            }
+            continue;
+        }
+        let current = phases.len() - 1;
+
+        if matches!(kind, EntryKind::ArtifactSync | EntryKind::Comment) {
+            if let Some(path) = timeline::artifact_path(event) {
+                let versions = phases[current]
+                    .artifacts
+                    .entry(path.to_string())
+                    .or_default();
+                if let Some(version) = event.extra.get("version").and_then(|v| v.as_i64()) {
+                    versions.insert(version);
+                }
+            }
+        }
+
+        let body = match kind {
+            EntryKind::Message => Body::Message { text },
+            EntryKind::Handoff => {
+                let summary = event
+                    .summary
+                    .clone()
+                    .or_else(|| field(event, "summary").map(String::from))
+                    .unwrap_or_else(|| first_line(&text).to_string());
+                let details = field(event, "details")
+                    .map(String::from)
+                    .unwrap_or_else(|| event.content.clone().unwrap_or_default());
+                Body::Handoff {
+                    to: field(event, "to").map(String::from),
+                    details: if details.trim() == summary.trim() {
+                        String::new()
+                    } else {
+                        details.trim().to_string()
+                    },
+                    summary,
+                }
+            }
+            EntryKind::TaskComplete => Body::TaskComplete {
+                task: field(event, "taskDescription")
+                    .map(String::from)
+                    .unwrap_or(text),
+                outcome: field(event, "outcome").map(String::from),
+            },
+            EntryKind::ImpedimentRaised => {
+                open.push((current, phases[current].entries.len()));
+                Body::Impediment {
+                    impediment_type: field(event, "impedimentType").map(String::from),
+                    description: field(event, "description")
+                        .map(String::from)
+                        .unwrap_or(text),
+                    resolution: None,
+                }
+            }
+            EntryKind::ImpedimentResolved => {
+                // Resolving clears every open impediment of the run
+                let text = field(event, "message").map(String::from).unwrap_or(text);
+                let resolution = Resolution {
+                    role: role.clone(),
+                    at: at.clone(),
+                    text: text.clone(),
+                };
+                for (phase, entry) in open.drain(..) {
+                    if let Body::Impediment { resolution: r, .. } =
+                        &mut phases[phase].entries[entry].body
+                    {
+                        *r = Some(resolution.clone());
+                    }
+                }
+                Body::Resolved { text }
+            }
+            _ => {
+                let entry = timeline::TimelineEntry::from_event(event);
+                let label = match kind {
+                    EntryKind::Other => event.event_type.clone().unwrap_or_default(),
+                    kind => kind.label().to_string(),
+                };
+                Body::Note {
+                    label,
+                    text: entry.detail,
+                }
+            }
+        };
+        phases[current].entries.push(Entry { role, at, body });
+    }
+
+    if phases.len() > 1 && phases[0].entries.is_empty() && phases[0].artifacts.is_empty() {
+        phases.remove(0);
+    }
+
+    Transcript {
+        run_id: run.run_id.clone(),
+        status: run.status.clone(),
+        template_name: run.template_name.clone(),
+        events: events.len(),
+        phases,
+    }
+}
+
+/// `PLAN.md (v1, v2), NOTES.md`
+fn artifact_list(artifacts: &BTreeMap<String, BTreeSet<i64>>, code: fn(&str) -> String) -> String {
+    artifacts
+        .iter()
+        .map(|(path, versions)| {
+            if versions.is_empty() {
+                code(path)
+            } else {
+                let versions: Vec<String> = versions.iter().map(|v| format!("v{}", v)).collect();
+                format!("{} ({})", code(path), versions.join(", "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn title(t: &Transcript) -> String {
+    format!(
+        "Run {}{}",
+        short_id(&t.run_id),
+        t.template_name
+            .as_deref()
+            .map(|name| format!(": {}", name))
+            .unwrap_or_default()
+    )
+}
+
+fn resolution_line(resolution: &Option<Resolution>) -> String {
+    match resolution {
+        Some(r) if r.text.is_empty() => format!("Resolved by {} at {}", r.role, r.at),
+        Some(r) => format!("Resolved by {} at {}: {}", r.role, r.at, r.text),
+        None => "Unresolved".to_string(),
+    }
+}
+
+// ============================================================================
+// Markdown
+// ============================================================================
+
+fn render_markdown(t: &Transcript) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", title(t));
+    let _ = writeln!(out, "- Run: `{}`", t.run_id);
+    let _ = writeln!(out, "- Status: {}", t.status);
+    let _ = writeln!(out, "- Events: {}", t.events);
+
+    for phase in &t.phases {
+        let _ = writeln!(out, "\n## Phase: {}\n", phase.phase);
+        if !phase.artifacts.is_empty() {
+            let code = |p: &str| format!("`{}`", p);
+            let _ = writeln!(
+                out,
+                "_Artifacts touched: {}_\n",
+                artifact_list(&phase.artifacts, code)
+            );
+        }
+        for entry in &phase.entries {
+            let head = format!("**{}** · {}", entry.role, entry.at);
+            match &entry.body {
+                Body::Message { text } => {
+                    let _ = writeln!(out, "{}\n\n{}\n", head, text);
+                }
+                Body::Handoff {
+                    to,
+                    summary,
+                    details,
+                } => {
+                    let to = to
+                        .as_deref()
+                        .map(|t| format!(" → {}", t))
+                        .unwrap_or_default();
+                    let _ = writeln!(out, "{} · handoff{}\n\n> {}\n", head, to, summary);
+                    if !details.is_empty() {
+                        let _ = writeln!(
+                            out,
+                            "<details><summary>Details</summary>\n\n{}\n\n</details>\n",
+                            details
+                        );
+                    }
+                }
+                Body::TaskComplete { task, outcome } => {
+                    let outcome = outcome
+                        .as_deref()
+                        .map(|o| format!(" ({})", o))
+                        .unwrap_or_default();
+                    let _ = writeln!(out, "{} · ✅ completed: {}{}\n", head, task, outcome);
+                }
+                Body::Impediment {
+                    impediment_type,
+                    description,
+                    resolution,
+                } => {
+                    let kind = impediment_type
+                        .as_deref()
+                        .map(|k| format!(" ({})", k))
+                        .unwrap_or_default();
+                    let _ = writeln!(
+                        out,
+                        "{} · ⚠️ impediment{}: {}\n\n> {}\n",
+                        head,
+                        kind,
+                        description,
+                        resolution_line(resolution)
+                    );
+                }
+                Body::Resolved { text } => {
+                    let _ = writeln!(out, "{} · impediments resolved: {}\n", head, text);
+                }
+                Body::Note { label, text } => {
+                    let _ = writeln!(out, "{} · _{}_ {}\n", head, label, text);
+                }
+            }
+        }
+    }
+    out
+}
+
+// ============================================================================
+// HTML
+// ============================================================================
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:52rem;margin:2rem auto;\
+padding:0 1rem;line-height:1.5}.meta{color:#666}.entry{margin:1rem 0}.head{color:#555;\
+font-size:.9em}pre{white-space:pre-wrap;background:#f6f6f6;padding:.5rem}\
+.impediment{border-left:3px solid #d33;padding-left:.5rem}";
+
+fn render_html(t: &Transcript) -> String {
+    let mut out = String::new();
+    let title = escape(&title(t));
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">"
+    );
+    let _ = writeln!(out, "<title>{}</title>\n<style>{}</style>", title, STYLE);
+    let _ = writeln!(out, "</head>\n<body>\n<h1>{}</h1>", title);
+    let _ = writeln!(
+        out,
+        "<p class=\"meta\">Run <code>{}</code> · {} · {} events</p>",
+        escape(&t.run_id),
+        escape(&t.status),
+        t.events
+    );
+
+    for phase in &t.phases {
+        let _ = writeln!(out, "<h2>Phase: {}</h2>", escape(&phase.phase));
+        if !phase.artifacts.is_empty() {
+            let code = |p: &str| format!("<code>{}</code>", escape(p));
+            let _ = writeln!(
+                out,
+                "<p class=\"meta\">Artifacts touched: {}</p>",
+                artifact_list(&phase.artifacts, code)
+            );
+        }
+        for entry in &phase.entries {
+            let head = format!(
+                "<div class=\"head\"><strong>{}</strong> · {}",
+                escape(&entry.role),
+                escape(&entry.at)
+            );
+            match &entry.body {
+                Body::Message { text } => {
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"entry\">{}</div><pre>{}</pre></div>",
+                        head,
+                        escape(text)
+                    );
+                }
+                Body::Handoff {
+                    to,
+                    summary,
+                    details,
+                } => {
+                    let to = to
+                        .as_deref()
+                        .map(|t| format!(" → {}", escape(t)))
+                        .unwrap_or_default();
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"entry\">{} · handoff{}</div><blockquote>{}</blockquote>",
+                        head,
+                        to,
+                        escape(summary)
+                    );
+                    if !details.is_empty() {
+                        let _ = writeln!(
+                            out,
+                            "<details><summary>Details</summary><pre>{}</pre></details>",
+                            escape(details)
+                        );
+                    }
+                    let _ = writeln!(out, "</div>");
+                }
+                Body::TaskComplete { task, outcome } => {
+                    let outcome = outcome
+                        .as_deref()
+                        .map(|o| format!(" ({})", escape(o)))
+                        .unwrap_or_default();
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"entry\">{} · completed: {}{}</div></div>",
+                        head,
+                        escape(task),
+                        outcome
+                    );
+                }
+                Body::Impediment {
+                    impediment_type,
+                    description,
+                    resolution,
+                } => {
+                    let kind = impediment_type
+                        .as_deref()
+                        .map(|k| format!(" ({})", escape(k)))
+                        .unwrap_or_default();
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"entry impediment\">{} · impediment{}</div><p>{}</p><p><em>{}</em></p></div>",
+                        head,
+                        kind,
+                        escape(description),
+                        escape(&resolution_line(resolution))
+                    );
+                }
+                Body::Resolved { text } => {
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"entry\">{} · impediments resolved: {}</div></div>",
+                        head,
+                        escape(text)
+                    );
+                }
+                Body::Note { label, text } => {
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"entry\">{} · <em>{}</em> {}</div></div>",
+                        head,
+                        escape(label),
+                        escape(text)
+                    );
+                }
+            }
+        }
+    }
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+// ============================================================================
+// Plain text
+// ============================================================================
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_text(t: &Transcript) -> String {
+    let mut out = String::new();
+    let title = title(t);
+    let _ = writeln!(out, "{}\n{}", title, "=".repeat(title.chars().count()));
+    let _ = writeln!(out, "Run {} · {} · {} events", t.run_id, t.status, t.events);
+
+    for phase in &t.phases {
+        let heading = format!("Phase: {}", phase.phase);
+        let _ = writeln!(
+            out,
+            "\n{}\n{}",
+            heading,
+            "-".repeat(heading.chars().count())
+        );
+        if !phase.artifacts.is_empty() {
+            let _ = writeln!(
+                out,
+                "Artifacts touched: {}",
+                artifact_list(&phase.artifacts, |p| p.to_string())
+            );
+        }
+        for entry in &phase.entries {
+            let head = format!("[{}] {}", entry.at, entry.role);
+            match &entry.body {
+                Body::Message { text } => {
+                    let _ = writeln!(out, "\n{}\n{}", head, indent(text));
+                }
+                Body::Handoff {
+                    to,
+                    summary,
+                    details,
+                } => {
+                    let to = to
+                        .as_deref()
+                        .map(|t| format!(" -> {}", t))
+                        .unwrap_or_default();
+                    let _ = writeln!(out, "\n{} handoff{}: {}", head, to, summary);
+                    if !details.is_empty() {
+                        let _ = writeln!(out, "{}", indent(details));
+                    }
+                }
+                Body::TaskComplete { task, outcome } => {
+                    let outcome = outcome
+                        .as_deref()
+                        .map(|o| format!(" ({})", o))
+                        .unwrap_or_default();
+                    let _ = writeln!(out, "\n{} completed: {}{}", head, task, outcome);
+                }
+                Body::Impediment {
+                    impediment_type,
+                    description,
+                    resolution,
+                } => {
+                    let kind = impediment_type
+                        .as_deref()
+                        .map(|k| format!(" ({})", k))
+                        .unwrap_or_default();
+                    let _ = writeln!(
+                        out,
+                        "\n{} IMPEDIMENT{}: {}\n    -> {}",
+                        head,
+                        kind,
+                        description,
+                        resolution_line(resolution)
+                    );
+                }
+                Body::Resolved { text } => {
+                    let _ = writeln!(out, "\n{} impediments resolved: {}", head, text);
+                }
+                Body::Note { label, text } => {
+                    let _ = writeln!(out, "\n{} {}: {}", head, label, text);
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(values: serde_json::Value) -> Vec<ConversationEvent> {
+        serde_json::from_value(values).unwrap()
+    }
+
+    fn run() -> RunStatus {
+        serde_json::from_value(serde_json::json!({"runId": "abcdef1234", "status": "completed"}))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_groups_by_phase_and_pairs_resolutions() {
+        let transcript = build(
+            &run(),
+            &events(serde_json::json!([
+                {"source": "strategist", "content": "Let's plan"},
+                {"eventType": "phase_change", "fromPhase": "planning", "toPhase": "executing"},
+                {"source": "builder", "eventType": "report_impediment", "description": "No key"},
+                {"source": "builder", "eventType": "artifact_sync", "path": "PLAN.md", "version": 2},
+                {"source": "strategist", "eventType": "resolve_run_impediments", "message": "Added"},
+            ])),
+        );
+        let phases: Vec<&str> = transcript.phases.iter().map(|p| p.phase.as_str()).collect();
+        assert_eq!(phases, ["planning", "executing"]);
+        let executing = &transcript.phases[1];
+        assert!(executing.artifacts["PLAN.md"].contains(&2));
+        match &executing.entries[0].body {
+            Body::Impediment { resolution, .. } => {
+                assert_eq!(resolution.as_ref().unwrap().text, "Added")
+            }
+            other => panic!("expected impediment, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_handoff_separates_summary_from_details() {
+        let transcript = build(
+            &run(),
+            &events(serde_json::json!([{
+                "source": "strategist", "eventType": "handoff", "to": "builder",
+                "summary": "Build the API", "content": "Endpoints:\n- GET /runs",
+            }])),
+        );
+        let markdown = render_markdown(&transcript);
+        assert!(markdown.contains("> Build the API"));
+        assert!(markdown.contains("<details><summary>Details</summary>\n\nEndpoints:"));
+        let html = render_html(&transcript);
+        assert!(html.contains("<blockquote>Build the API</blockquote>"));
+        assert!(html.contains("→ builder"));
+    }
+
+    #[test]
+    fn test_html_escapes_agent_text() {
+        let transcript = build(
+            &run(),
+            &events(serde_json::json!([{"source": "builder", "content": "<script>x</script>"}])),
+        );
+        let html = render_html(&transcript);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+}
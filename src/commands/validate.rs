@@ -1,118 +1,30 @@
 //! Session validation for workflow commands
 //!
 //! Every workflow command (except `hotwire` and `pair`) MUST validate session state first.
-//! This module provides the validation logic and user-friendly error messages.
+//! The checks live in the library (`hotwired_client::session`); this module adds
+//! the user-friendly error messages and exit codes.
 
+use super::exit_code;
 use crate::ipc::{HotwiredClient, IpcError};
+pub use hotwired_client::session::{validate_session, SessionState, ValidationError};
+use hotwired_client::TerminalContext;
 
-/// Current session state from hotwired-core
-#[derive(Debug, Clone)]
-#[allow(dead_code)] // zellij_session kept for debugging/future use
-pub struct SessionState {
-    pub zellij_session: String,
-    pub run_id: String,
-    pub role_id: String,
-    pub run_status: String,
-}
-
-/// Validation errors with user-friendly messages
-#[derive(Debug)]
-pub enum ValidationError {
-    /// Not running inside a Zellij terminal
-    NoZellijSession,
-    /// Zellij session exists but hotwired-core doesn't know about it
-    SessionNotRegistered,
-    /// Session exists but not attached to any run
-    NotAttachedToRun,
-    /// Attached run is in a terminal state (completed/failed/cancelled)
-    RunNotActive(String),
-    /// IPC error (backend not running, etc.)
-    IpcError(IpcError),
-}
-
-impl std::fmt::Display for ValidationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ValidationError::NoZellijSession => write!(f, "Not running in a Zellij session"),
-            ValidationError::SessionNotRegistered => {
-                write!(f, "Session not registered with Hotwired")
-            }
-            ValidationError::NotAttachedToRun => write!(f, "Not attached to any run"),
-            ValidationError::RunNotActive(status) => {
-                write!(f, "Run is not active (status: {})", status)
-            }
-            ValidationError::IpcError(e) => write!(f, "IPC error: {}", e),
-        }
-    }
-}
-
-impl std::error::Error for ValidationError {}
-
-/// Validate session state - call this FIRST in every command except hotwire/pair
-pub async fn validate_session(client: &HotwiredClient) -> Result<SessionState, ValidationError> {
-    // 1. Check we're in a Zellij session
-    let zellij_session =
-        std::env::var("ZELLIJ_SESSION_NAME").map_err(|_| ValidationError::NoZellijSession)?;
-
-    // 2. Query hotwired-core for session state
-    let response = client
-        .request(
-            "get_session_state",
-            serde_json::json!({"zellijSession": zellij_session}),
-        )
-        .await
-        .map_err(ValidationError::IpcError)?;
-
-    if !response.success {
-        return Err(ValidationError::SessionNotRegistered);
-    }
-
-    let data = response.data.ok_or(ValidationError::SessionNotRegistered)?;
-
-    // 3. Check if attached to a run (server returns camelCase keys)
-    let run_id = data
-        .get("attachedRunId")
-        .and_then(|v| v.as_str())
-        .filter(|s| !s.is_empty())
-        .ok_or(ValidationError::NotAttachedToRun)?
-        .to_string();
-
-    // 4. Check run status
-    let run_status = data
-        .get("runStatus")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown")
-        .to_string();
-
-    // Only reject truly terminal statuses - blocked/paused runs are still usable
-    if run_status == "completed" || run_status == "failed" || run_status == "cancelled" {
-        return Err(ValidationError::RunNotActive(run_status));
+/// Process exit code for a validation failure (see `exit_code`)
+pub fn exit_code_for(err: &ValidationError) -> i32 {
+    match err {
+        ValidationError::NoTerminalSession => exit_code::NO_SESSION,
+        ValidationError::SessionNotRegistered => exit_code::SESSION_NOT_REGISTERED,
+        ValidationError::NotAttachedToRun => exit_code::NOT_ATTACHED,
+        ValidationError::RunNotActive(_) => exit_code::RUN_NOT_ACTIVE,
+        ValidationError::IpcError(e) => exit_code::for_ipc_error(e),
     }
-
-    // 5. Get role
-    let role_id = data
-        .get("roleId")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown")
-        .to_string();
-
-    Ok(SessionState {
-        zellij_session,
-        run_id,
-        role_id,
-        run_status,
-    })
 }
 
 /// Print user-friendly error and exit
 pub fn print_validation_error(err: ValidationError) -> ! {
+    let code = exit_code_for(&err);
     match err {
-        ValidationError::NoZellijSession => {
-            eprintln!("ERROR: Not running in a Zellij session.");
-            eprintln!();
-            eprintln!("The hotwired CLI must be run from within a Hotwired-managed terminal.");
-            eprintln!("Start a terminal from the Hotwired app, or check $ZELLIJ_SESSION_NAME.");
-        }
+        ValidationError::NoTerminalSession => print_no_terminal_session(),
         ValidationError::SessionNotRegistered => {
             eprintln!("ERROR: This terminal is not registered with Hotwired.");
             eprintln!();
@@ -148,7 +60,29 @@ pub fn print_validation_error(err: ValidationError) -> ! {
             }
         }
     }
-    std::process::exit(1);
+    std::process::exit(code);
+}
+
+/// Explain which terminals the CLI can identify
+pub fn print_no_terminal_session() {
+    eprintln!("ERROR: Not running in a Zellij, tmux or WezTerm session.");
+    eprintln!();
+    eprintln!("Hotwired identifies this terminal from $ZELLIJ_SESSION_NAME,");
+    eprintln!("$TMUX_PANE (tmux) or $WEZTERM_PANE (WezTerm); none of them is set.");
+    eprintln!();
+    eprintln!("Without a multiplexer (containers, CI), claim a registered session:");
+    eprintln!("  hotwired --session <NAME> ...   or   export HOTWIRED_SESSION=<NAME>");
+}
+
+/// The terminal session to act for, or print why there is none and exit
+pub fn require_terminal(client: &HotwiredClient) -> TerminalContext {
+    match client.terminal() {
+        Some(terminal) => terminal,
+        None => {
+            print_no_terminal_session();
+            std::process::exit(exit_code::NO_SESSION);
+        }
+    }
 }
 
 /// Convenience: validate and return state, or print error and exit
@@ -164,26 +98,15 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_validation_error_display() {
-        assert_eq!(
-            ValidationError::NoZellijSession.to_string(),
-            "Not running in a Zellij session"
-        );
-        assert_eq!(
-            ValidationError::RunNotActive("completed".to_string()).to_string(),
-            "Run is not active (status: completed)"
-        );
-    }
-
-    #[test]
-    fn test_session_state_clone() {
-        let state = SessionState {
-            zellij_session: "test-session".to_string(),
-            run_id: "run-123".to_string(),
-            role_id: "strategist".to_string(),
-            run_status: "active".to_string(),
-        };
-        let cloned = state.clone();
-        assert_eq!(cloned.run_id, "run-123");
+    fn test_validation_errors_have_distinct_exit_codes() {
+        let codes = [
+            exit_code_for(&ValidationError::NoTerminalSession),
+            exit_code_for(&ValidationError::SessionNotRegistered),
+            exit_code_for(&ValidationError::NotAttachedToRun),
+            exit_code_for(&ValidationError::RunNotActive("failed".to_string())),
+        ];
+        for (i, a) in codes.iter().enumerate() {
+            assert!(codes[i + 1..].iter().all(|b| a != b));
+        }
     }
 }
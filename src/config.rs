@@ -0,0 +1,282 @@
+//! User configuration in `~/.hotwired/config.toml`
+//!
+//! A context names one hotwired-core backend (socket and token source) plus
+//! defaults for commands run against it, so switching between a stable and a
+//! dev core is `hotwired context use dev` instead of passing `-s` everywhere.
+//!
+//! ```toml
+//! current-context = "stable"
+//!
+//! [contexts.stable]
+//! socket = "~/.hotwired/hotwired.sock"
+//!
+//! [contexts.dev]
+//! socket = "~/.hotwired-dev/hotwired.sock"
+//! token-env = "HOTWIRED_DEV_TOKEN"
+//! project = "~/Code/hotwired"
+//!
+//! [contexts.dev.defaults]
+//! output = "json"
+//! timeout = 5
+//! playbook = "plan-build"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable selecting a context, overriding `current-context`
+pub const CONTEXT_ENV: &str = "HOTWIRED_CONTEXT";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("cannot read {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid config {}: {message}", path.display())]
+    Parse { path: PathBuf, message: String },
+    #[error("no context named '{name}' (available: {})", list_or_none(available))]
+    UnknownContext {
+        name: String,
+        available: Vec<String>,
+    },
+    #[error("context token-env ${0} is not set")]
+    TokenEnvUnset(String),
+}
+
+fn list_or_none(names: &[String]) -> String {
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Parsed `config.toml`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    pub current_context: Option<String>,
+    #[serde(default)]
+    pub contexts: BTreeMap<String, Context>,
+}
+
+/// One named backend
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Context {
+    /// Socket path; `~` expands to the home directory
+    pub socket: Option<String>,
+    /// Read the auth token from this file
+    pub token_file: Option<String>,
+    /// Read the auth token from this environment variable (wins over `token-file`)
+    pub token_env: Option<String>,
+    /// Project directory used when a command doesn't get one
+    pub project: Option<String>,
+    #[serde(default)]
+    pub defaults: Defaults,
+}
+
+/// Per-context defaults; command-line flags always win
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Defaults {
+    /// `table`, `json` or `ndjson`
+    pub output: Option<String>,
+    /// Request timeout in seconds (`0` disables it)
+    pub timeout: Option<f64>,
+    /// Playbook for `hotwire` when `--playbook` isn't given
+    pub playbook: Option<String>,
+}
+
+impl Config {
+    /// `~/.hotwired/config.toml`
+    pub fn path() -> Option<PathBuf> {
+        Some(dirs::home_dir()?.join(".hotwired").join("config.toml"))
+    }
+
+    /// Load the config file; a missing file is an empty config.
+    pub fn load() -> Result<Self, ConfigError> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        let raw = match std::fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(ConfigError::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        toml::from_str(&raw).map_err(|e| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message: e.message().to_string(),
+        })
+    }
+
+    /// Name of the active context: `explicit`, else `HOTWIRED_CONTEXT`, else
+    /// `current-context`.
+    pub fn active_name(&self, explicit: Option<&str>) -> Option<String> {
+        explicit
+            .map(String::from)
+            .or_else(|| std::env::var(CONTEXT_ENV).ok())
+            .filter(|name| !name.is_empty())
+            .or_else(|| self.current_context.clone())
+    }
+
+    /// The active context, if any is selected.
+    pub fn active(
+        &self,
+        explicit: Option<&str>,
+    ) -> Result<Option<(String, &Context)>, ConfigError> {
+        match self.active_name(explicit) {
+            Some(name) => {
+                let context = self.get(&name)?;
+                Ok(Some((name, context)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Look up a context by name.
+    pub fn get(&self, name: &str) -> Result<&Context, ConfigError> {
+        self.contexts
+            .get(name)
+            .ok_or_else(|| ConfigError::UnknownContext {
+                name: name.to_string(),
+                available: self.contexts.keys().cloned().collect(),
+            })
+    }
+
+    /// Make `name` the current context in the file at `path`, keeping its
+    /// comments and layout.
+    pub fn set_current(&self, path: &Path, name: &str) -> Result<(), ConfigError> {
+        self.get(name)?;
+
+        let raw = std::fs::read_to_string(path).unwrap_or_default();
+        let mut doc: toml_edit::DocumentMut =
+            raw.parse()
+                .map_err(|e: toml_edit::TomlError| ConfigError::Parse {
+                    path: path.to_path_buf(),
+                    message: e.message().to_string(),
+                })?;
+        doc["current-context"] = toml_edit::value(name);
+
+        std::fs::write(path, doc.to_string()).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+impl Context {
+    /// Socket path with `~` expanded
+    pub fn socket_path(&self) -> Option<String> {
+        self.socket.as_deref().map(expand_home)
+    }
+
+    /// Project directory with `~` expanded
+    pub fn project_dir(&self) -> Option<PathBuf> {
+        self.project
+            .as_deref()
+            .map(|p| PathBuf::from(expand_home(p)))
+    }
+
+    /// Token from the configured source; `None` when the context doesn't
+    /// configure one (the client then uses `~/.hotwired/auth_token`).
+    pub fn read_token(&self) -> Result<Option<String>, ConfigError> {
+        if let Some(var) = &self.token_env {
+            return match std::env::var(var) {
+                Ok(token) => Ok(Some(token.trim().to_string())),
+                Err(_) => Err(ConfigError::TokenEnvUnset(var.clone())),
+            };
+        }
+        if let Some(file) = &self.token_file {
+            let path = PathBuf::from(expand_home(file));
+            return std::fs::read_to_string(&path)
+                .map(|token| Some(token.trim().to_string()))
+                .map_err(|source| ConfigError::Io { path, source });
+        }
+        Ok(None)
+    }
+}
+
+/// Expand a leading `~/` to the home directory.
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+# which backend to talk to
+current-context = "stable"
+
+[contexts.stable]
+socket = "/tmp/stable.sock"
+
+[contexts.dev]
+socket = "/tmp/dev.sock"
+token-env = "HOTWIRED_TEST_DEV_TOKEN_UNSET"
+
+[contexts.dev.defaults]
+output = "json"
+timeout = 5
+"#;
+
+    #[test]
+    fn test_parse_contexts() {
+        let config: Config = toml::from_str(SAMPLE).unwrap();
+        assert_eq!(config.current_context.as_deref(), Some("stable"));
+        let dev = config.get("dev").unwrap();
+        assert_eq!(dev.socket_path().as_deref(), Some("/tmp/dev.sock"));
+        assert_eq!(dev.defaults.output.as_deref(), Some("json"));
+        assert_eq!(dev.defaults.timeout, Some(5.0));
+        assert!(matches!(
+            dev.read_token(),
+            Err(ConfigError::TokenEnvUnset(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_context_lists_available() {
+        let config: Config = toml::from_str(SAMPLE).unwrap();
+        let err = config.get("prod").unwrap_err().to_string();
+        assert_eq!(err, "no context named 'prod' (available: dev, stable)");
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let err = toml::from_str::<Config>("[contexts.x]\nsocket-path = \"/tmp/s\"\n");
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_set_current_keeps_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, SAMPLE).unwrap();
+
+        let config = Config::load_from(&path).unwrap();
+        config.set_current(&path, "dev").unwrap();
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("# which backend to talk to"));
+        let reloaded = Config::load_from(&path).unwrap();
+        assert_eq!(reloaded.current_context.as_deref(), Some("dev"));
+        assert!(config.set_current(&path, "nope").is_err());
+    }
+}
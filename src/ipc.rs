@@ -1,8 +1,105 @@
+//! Client for the hotwired-core Unix socket
+//!
+//! Requests are newline-delimited JSON: the client writes one
+//! `SocketRequest` line and core answers with one `SocketResponse` line over
+//! a fresh connection. Subscriptions keep the connection open and stream one
+//! event per line. `HotwiredClient` wraps every socket method in a typed call.
+
+use crate::cassette::Cassette;
+use crate::models::*;
+use crate::terminal::TerminalContext;
+use crate::trace::{self, Span, Tracer};
+use serde::de::{DeserializeOwned, IgnoredAny};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixStream;
+use tokio::sync::OnceCell;
+
+/// Socket protocol version this client speaks.
+///
+/// Version 0 is the pre-handshake protocol: cores that don't answer `hello`
+/// are assumed to serve exactly `LEGACY_METHODS`.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Methods every core serves, including ones that predate the handshake.
+const LEGACY_METHODS: &[&str] = &[
+    "ping",
+    "hotwire",
+    "pair",
+    "handoff",
+    "task_complete",
+    "report_impediment",
+    "resolve_run_impediments",
+    "get_protocol",
+    "get_session_state",
+    "list_active_sessions",
+    "register_session",
+    "deregister_session",
+    "hook_event",
+    "list_runs",
+    "get_run_status",
+    "delete_run",
+    "get_conversation_events",
+    "artifact_list",
+    "artifact_sync",
+    "artifact_move",
+    "artifact_add_comment",
+    "artifact_list_comments",
+    "artifact_get_comment",
+    "artifact_reply_comment",
+    "artifact_resolve_comment",
+    "artifact_list_versions",
+    "artifact_get_version",
+];
+
+/// Default limit for a single request, overridable with `--timeout` or
+/// `HOTWIRED_TIMEOUT` (seconds, `0` disables).
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Attempts made for idempotent methods when the connection itself fails.
+const MAX_ATTEMPTS: u32 = 3;
+/// Backoff before the first retry; doubles on each further attempt.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Read-only methods that are safe to resend after a failed connection.
+const IDEMPOTENT_METHODS: &[&str] = &[
+    "ping",
+    "hello",
+    "get_protocol",
+    "get_session_state",
+    "list_active_sessions",
+    "list_runs",
+    "get_run_status",
+    "get_conversation_events",
+    "artifact_list",
+    "artifact_list_comments",
+    "artifact_get_comment",
+    "artifact_list_versions",
+    "artifact_get_version",
+];
+
+/// Protocol version that introduced each post-handshake method.
+const METHOD_SINCE: &[(&str, u32)] = &[
+    ("subscribe_conversation_events", 1),
+    ("update_run_state", 1),
+    ("import_run", 1),
+];
+
+/// Protocol version a method needs, for "core too old" messages.
+fn required_protocol(method: &str) -> u32 {
+    METHOD_SINCE
+        .iter()
+        .find(|(m, _)| *m == method)
+        .map(|(_, v)| *v)
+        .unwrap_or(PROTOCOL_VERSION)
+}
 
+/// Everything that can go wrong talking to core
 #[derive(Debug, thiserror::Error)]
 pub enum IpcError {
     #[error("Hotwired backend is not running (socket not found at {0})")]
@@ -13,6 +110,88 @@ pub enum IpcError {
     RequestFailed(String),
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
+    #[error("Timed out after {}s waiting for hotwired-core", .0.as_secs_f32())]
+    Timeout(Duration),
+    /// Core answered with `success: false`
+    #[error("{message}")]
+    Backend { code: ErrorCode, message: String },
+    /// Socket could belong to another user; the token was not sent
+    #[error("refusing to send auth token to {path}: {reason}")]
+    UntrustedSocket { path: String, reason: String },
+    #[error("hotwired-core is too old for `{method}` (needs protocol v{required}, core speaks v{available})")]
+    Unsupported {
+        method: String,
+        required: u32,
+        available: u32,
+    },
+}
+
+/// Machine-readable failure class sent by core alongside `error`.
+///
+/// Cores that predate error codes send none, which parses as `Unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    NotFound,
+    Ambiguous,
+    PermissionDenied,
+    Unauthorized,
+    Conflict,
+    InvalidState,
+    InvalidParams,
+    UnknownMethod,
+    Internal,
+    Unknown,
+}
+
+impl ErrorCode {
+    pub fn parse(code: &str) -> Self {
+        match code {
+            "not_found" => ErrorCode::NotFound,
+            "ambiguous" => ErrorCode::Ambiguous,
+            "permission_denied" => ErrorCode::PermissionDenied,
+            "unauthorized" => ErrorCode::Unauthorized,
+            "conflict" => ErrorCode::Conflict,
+            "invalid_state" => ErrorCode::InvalidState,
+            "invalid_params" => ErrorCode::InvalidParams,
+            "unknown_method" => ErrorCode::UnknownMethod,
+            "internal" => ErrorCode::Internal,
+            _ => ErrorCode::Unknown,
+        }
+    }
+}
+
+/// What the connected core advertised during the handshake.
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    pub protocol_version: u32,
+    pub core_version: Option<String>,
+    methods: HashSet<String>,
+}
+
+impl Capabilities {
+    /// Assumed capabilities of a core that doesn't understand `hello`.
+    fn legacy(core_version: Option<String>) -> Self {
+        Self {
+            protocol_version: 0,
+            core_version,
+            methods: LEGACY_METHODS.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    fn from_hello(hello: HelloInfo) -> Self {
+        let mut methods: HashSet<String> = hello.methods.into_iter().collect();
+        // "hello" itself is implied by a successful handshake
+        methods.insert("hello".to_string());
+        Self {
+            protocol_version: hello.protocol_version,
+            core_version: hello.core_version,
+            methods,
+        }
+    }
+
+    pub fn supports(&self, method: &str) -> bool {
+        self.methods.contains(method)
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -25,20 +204,67 @@ struct SocketRequest {
     token: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// One response line from core, before `data` is decoded.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SocketResponse {
     pub success: bool,
     pub data: Option<serde_json::Value>,
-    #[allow(dead_code)]
     pub error: Option<String>,
+    /// Failure class, e.g. `not_found` (see `ErrorCode`)
+    #[serde(alias = "errorCode")]
+    pub code: Option<String>,
+}
+
+impl SocketResponse {
+    /// Turn the envelope into typed `data`, or `Backend` if core refused.
+    pub fn into_data<T: DeserializeOwned>(self) -> Result<T, IpcError> {
+        if !self.success {
+            return Err(self.into_error());
+        }
+        serde_json::from_value(self.data.unwrap_or_default())
+            .map_err(|e| IpcError::InvalidResponse(e.to_string()))
+    }
+
+    fn into_error(self) -> IpcError {
+        IpcError::Backend {
+            code: self
+                .code
+                .as_deref()
+                .map(ErrorCode::parse)
+                .unwrap_or(ErrorCode::Unknown),
+            message: self.error.unwrap_or_else(|| "unknown error".into()),
+        }
+    }
 }
 
+/// Async handle to hotwired-core. Cheap to create; every call opens its own
+/// connection, so one client can be shared across tasks by reference.
 pub struct HotwiredClient {
     socket_path: String,
     auth_token: Option<String>,
+    /// Explicit session identity (`--session`); see `terminal()`
+    session: Option<String>,
+    timeout: Option<Duration>,
+    /// Check socket ownership before sending the token; see `check_socket_trust`
+    verify_socket: bool,
+    tracer: Option<Arc<Tracer>>,
+    /// Recording or replaying exchanges; see `cassette`
+    cassette: Option<Arc<Cassette>>,
+    capabilities: OnceCell<Capabilities>,
+}
+
+/// A serialized request: what goes over the wire, and what the trace shows
+struct RequestLine {
+    wire: String,
+    traced: String,
 }
 
 impl HotwiredClient {
+    /// Client for `socket_path`, or `~/.hotwired/hotwired.sock` when `None`.
+    ///
+    /// Reads the auth token from `HOTWIRED_TOKEN`, else `~/.hotwired/auth_token`,
+    /// and the timeout from `HOTWIRED_TIMEOUT` (default 30s). Socket
+    /// verification is on unless `HOTWIRED_ALLOW_UNTRUSTED_SOCKET=1`.
     pub fn new(socket_path: Option<String>) -> Self {
         let socket_path = socket_path.unwrap_or_else(|| {
             dirs::home_dir()
@@ -49,43 +275,300 @@ impl HotwiredClient {
                 .to_string()
         });
 
-        let auth_token = Self::read_auth_token();
+        let auth_token = token_from_env().or_else(Self::read_auth_token);
 
         Self {
             socket_path,
             auth_token,
+            session: None,
+            timeout: Self::timeout_from_env().unwrap_or(Some(DEFAULT_TIMEOUT)),
+            verify_socket: !env_flag(ALLOW_UNTRUSTED_SOCKET_ENV),
+            tracer: None,
+            cassette: None,
+            capabilities: OnceCell::new(),
+        }
+    }
+
+    /// Override the per-request timeout; `None` waits indefinitely.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Act as this registered session instead of detecting the terminal.
+    pub fn with_session(mut self, session: Option<String>) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Send the token even to a socket that fails `check_socket_trust`.
+    ///
+    /// Only for setups where core deliberately runs as another user.
+    pub fn allow_untrusted_socket(mut self, allow: bool) -> Self {
+        if allow {
+            self.verify_socket = false;
+        }
+        self
+    }
+
+    /// Log every exchange on the wire to `tracer`; see `trace`.
+    pub fn with_tracer(mut self, tracer: Option<Tracer>) -> Self {
+        self.tracer = tracer.map(Arc::new);
+        self
+    }
+
+    /// Record every exchange to, or answer every request from, `cassette`.
+    pub fn with_cassette(mut self, cassette: Option<Cassette>) -> Self {
+        self.cassette = cassette.map(Arc::new);
+        self
+    }
+
+    /// The cassette being recorded, if any.
+    fn recorder(&self) -> Option<&Arc<Cassette>> {
+        self.cassette.as_ref().filter(|c| !c.is_replay())
+    }
+
+    /// Send this token instead of the one in `~/.hotwired/auth_token`.
+    pub fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token;
+        self
+    }
+
+    /// Whether requests carry a (non-empty) auth token.
+    pub fn has_auth_token(&self) -> bool {
+        self.auth_token.as_deref().is_some_and(|t| !t.is_empty())
+    }
+
+    /// The terminal session this client acts for: the explicit session,
+    /// else `HOTWIRED_SESSION`, else the detected multiplexer pane.
+    pub fn terminal(&self) -> Option<TerminalContext> {
+        TerminalContext::resolve(self.session.as_deref())
+    }
+
+    /// `HOTWIRED_TIMEOUT` in seconds; `0` means no timeout. An invalid
+    /// value is ignored with a warning.
+    fn timeout_from_env() -> Option<Option<Duration>> {
+        let secs: f64 = std::env::var("HOTWIRED_TIMEOUT")
+            .ok()?
+            .trim()
+            .parse()
+            .ok()?;
+        match parse_timeout_secs(secs) {
+            Ok(timeout) => Some(timeout),
+            Err(e) => {
+                eprintln!("warning: ignoring HOTWIRED_TIMEOUT: {}", e);
+                None
+            }
         }
     }
 
     fn read_auth_token() -> Option<String> {
-        let token_path = dirs::home_dir()?.join(".hotwired").join("auth_token");
+        let token_path = auth_token_path()?;
         std::fs::read_to_string(token_path)
             .ok()
             .map(|s| s.trim().to_string())
     }
 
+    /// Untyped request: send `params` as-is and return the raw response.
     pub async fn request(
         &self,
         method: &str,
         params: serde_json::Value,
     ) -> Result<SocketResponse, IpcError> {
-        if !std::path::Path::new(&self.socket_path).exists() {
-            return Err(IpcError::NotConnected(self.socket_path.clone()));
+        self.request_with_timeout(method, params, self.timeout)
+            .await
+    }
+
+    /// Like `request`, with an explicit timeout for this call only.
+    ///
+    /// Idempotent methods are retried with backoff when the connection
+    /// fails. On timeout the in-flight exchange is dropped, which closes the
+    /// socket and cancels the request from core's point of view.
+    pub async fn request_with_timeout(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Option<Duration>,
+    ) -> Result<SocketResponse, IpcError> {
+        let attempts = if IDEMPOTENT_METHODS.contains(&method) {
+            MAX_ATTEMPTS
+        } else {
+            1
+        };
+        let mut backoff = RETRY_BACKOFF;
+
+        let line = self.request_line(method, params)?;
+
+        for attempt in 1..=attempts {
+            let span = self.tracer.as_ref().map(|t| t.request(&line.traced));
+            let exchange = self.exchange(&line.wire, span.as_ref());
+            let result = match timeout {
+                Some(limit) => tokio::time::timeout(limit, exchange)
+                    .await
+                    .unwrap_or(Err(IpcError::Timeout(limit))),
+                None => exchange.await,
+            };
+            if let (Some(tracer), Some(span), Err(e)) = (&self.tracer, &span, &result) {
+                tracer.failure(span, e);
+            }
+
+            match result {
+                Err(IpcError::ConnectionFailed(_)) if attempt < attempts => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                other => return other,
+            }
         }
+        unreachable!("loop returns on the final attempt")
+    }
+
+    /// One request/response round trip over a fresh connection.
+    async fn exchange(&self, line: &str, span: Option<&Span>) -> Result<SocketResponse, IpcError> {
+        let mut reader = self.send(line).await?;
 
-        let mut stream = UnixStream::connect(&self.socket_path)
+        let mut response = String::new();
+        reader
+            .read_line(&mut response)
             .await
-            .map_err(|e| IpcError::ConnectionFailed(e.to_string()))?;
+            .map_err(|e| IpcError::RequestFailed(e.to_string()))?;
+        if let (Some(tracer), Some(span)) = (&self.tracer, span) {
+            tracer.response(span, &response);
+        }
+        if let Some(cassette) = self.recorder() {
+            cassette.record_response(line, &response);
+        }
+
+        serde_json::from_str(&response).map_err(|e| IpcError::InvalidResponse(e.to_string()))
+    }
 
+    /// Open a streaming subscription.
+    ///
+    /// Core acknowledges the request with a regular `SocketResponse` line and
+    /// then keeps the socket open, pushing one JSON event per line as they
+    /// happen. A rejected subscription surfaces as `Backend`.
+    pub async fn subscribe<P: Serialize, T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &P,
+    ) -> Result<EventStream<T>, IpcError> {
+        self.require_method(method).await?;
+        let params =
+            serde_json::to_value(params).map_err(|e| IpcError::RequestFailed(e.to_string()))?;
+
+        let request = self.request_line(method, params)?;
+        let trace = self
+            .tracer
+            .as_ref()
+            .map(|t| (Arc::clone(t), t.request(&request.traced)));
+
+        // The timeout covers connecting and the ack; the stream itself may idle
+        let handshake = async {
+            let mut reader = self.send(&request.wire).await?;
+            let mut line = String::new();
+            reader
+                .read_line(&mut line)
+                .await
+                .map_err(|e| IpcError::RequestFailed(e.to_string()))?;
+            Ok::<_, IpcError>((reader, line))
+        };
+        let handshake = match self.timeout {
+            Some(limit) => tokio::time::timeout(limit, handshake)
+                .await
+                .unwrap_or(Err(IpcError::Timeout(limit))),
+            None => handshake.await,
+        };
+        let (reader, line) = match (handshake, &trace) {
+            (Ok((reader, line)), Some((tracer, span))) => {
+                tracer.response(span, &line);
+                (reader, line)
+            }
+            (Err(e), Some((tracer, span))) => {
+                tracer.failure(span, &e);
+                return Err(e);
+            }
+            (handshake, None) => handshake?,
+        };
+        let recording = self
+            .recorder()
+            .map(|c| (Arc::clone(c), c.record_response(&request.wire, &line)));
+
+        let ack: SocketResponse =
+            serde_json::from_str(&line).map_err(|e| IpcError::InvalidResponse(e.to_string()))?;
+        if !ack.success {
+            return Err(ack.into_error());
+        }
+
+        Ok(EventStream {
+            reader,
+            trace,
+            recording,
+            _event: PhantomData,
+        })
+    }
+
+    /// Typed request: serialize `params`, send, and decode `data` as `R`.
+    ///
+    /// Methods newer than the legacy set are checked against the negotiated
+    /// capabilities first, so an old core fails with `Unsupported` instead of
+    /// an opaque backend error.
+    pub async fn call<P: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &P,
+    ) -> Result<R, IpcError> {
+        self.require_method(method).await?;
+        let params =
+            serde_json::to_value(params).map_err(|e| IpcError::RequestFailed(e.to_string()))?;
+        self.request(method, params).await?.into_data()
+    }
+
+    /// Serialize a request, plus its redacted form for the trace.
+    fn request_line(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<RequestLine, IpcError> {
         let request = SocketRequest {
             id: None,
             method: method.to_string(),
             params,
             token: self.auth_token.clone(),
         };
+        let value =
+            serde_json::to_value(&request).map_err(|e| IpcError::RequestFailed(e.to_string()))?;
+        Ok(RequestLine {
+            wire: value.to_string(),
+            traced: trace::redact(value),
+        })
+    }
 
-        let request_json =
-            serde_json::to_string(&request).map_err(|e| IpcError::RequestFailed(e.to_string()))?;
+    /// Connect, write a single request line, and hand back the read half.
+    async fn send(&self, request_json: &str) -> Result<BufReader<UnixStream>, IpcError> {
+        if let Some(cassette) = self.cassette.as_ref().filter(|c| c.is_replay()) {
+            return replay(cassette, request_json);
+        }
+
+        if !std::path::Path::new(&self.socket_path).exists() {
+            return Err(IpcError::NotConnected(self.socket_path.clone()));
+        }
+
+        // A socket planted by another user would harvest the token; connect
+        // to the path that was checked so a swapped symlink can't redirect it
+        let target = if self.auth_token.is_some() && self.verify_socket {
+            check_socket_trust(Path::new(&self.socket_path)).map_err(|reason| {
+                IpcError::UntrustedSocket {
+                    path: self.socket_path.clone(),
+                    reason,
+                }
+            })?
+        } else {
+            PathBuf::from(&self.socket_path)
+        };
+
+        let mut stream = UnixStream::connect(&target)
+            .await
+            .map_err(|e| IpcError::ConnectionFailed(e.to_string()))?;
 
         stream
             .write_all(request_json.as_bytes())
@@ -100,21 +583,653 @@ impl HotwiredClient {
             .await
             .map_err(|e| IpcError::RequestFailed(e.to_string()))?;
 
-        let mut reader = BufReader::new(&mut stream);
+        Ok(BufReader::new(stream))
+    }
+
+    /// Socket this client connects to.
+    pub fn socket_path(&self) -> &str {
+        &self.socket_path
+    }
+
+    /// Ping core; succeeds with its version when the backend is up.
+    pub async fn health_check(&self) -> Result<PingInfo, IpcError> {
+        self.call("ping", &serde_json::json!({})).await
+    }
+
+    /// Negotiate with core once per client and cache the result.
+    ///
+    /// Cores that reject `hello` predate the handshake; they're treated as
+    /// protocol v0 serving `LEGACY_METHODS`.
+    pub async fn capabilities(&self) -> Result<&Capabilities, IpcError> {
+        self.capabilities
+            .get_or_try_init(|| async {
+                let params = HelloParams {
+                    client_version: env!("CARGO_PKG_VERSION").to_string(),
+                    protocol_version: PROTOCOL_VERSION,
+                };
+                let params = serde_json::to_value(&params)
+                    .map_err(|e| IpcError::RequestFailed(e.to_string()))?;
+
+                match self
+                    .request("hello", params)
+                    .await?
+                    .into_data::<HelloInfo>()
+                {
+                    Ok(hello) => Ok(Capabilities::from_hello(hello)),
+                    Err(IpcError::Backend { .. }) | Err(IpcError::InvalidResponse(_)) => {
+                        let ping: PingInfo = self
+                            .request("ping", serde_json::json!({}))
+                            .await?
+                            .into_data()?;
+                        Ok(Capabilities::legacy(ping.version))
+                    }
+                    Err(e) => Err(e),
+                }
+            })
+            .await
+    }
+
+    /// Fail with `Unsupported` unless core advertises `method`.
+    ///
+    /// Legacy methods skip the handshake entirely.
+    pub async fn require_method(&self, method: &str) -> Result<(), IpcError> {
+        if LEGACY_METHODS.contains(&method) {
+            return Ok(());
+        }
+
+        let caps = self.capabilities().await?;
+        if caps.supports(method) {
+            Ok(())
+        } else {
+            Err(IpcError::Unsupported {
+                method: method.to_string(),
+                required: required_protocol(method),
+                available: caps.protocol_version,
+            })
+        }
+    }
+
+    // =========================================================================
+    // Typed wrappers, one per socket method
+    // =========================================================================
+
+    /// Start a new run with this terminal as its first agent.
+    pub async fn hotwire(&self, params: &HotwireParams) -> Result<HotwireResult, IpcError> {
+        self.call("hotwire", params).await
+    }
+
+    /// Attach this terminal to an existing run.
+    pub async fn pair(&self, params: &PairParams) -> Result<PairResult, IpcError> {
+        self.call("pair", params).await
+    }
+
+    /// Send a message to another participant of a run.
+    pub async fn handoff(&self, params: &HandoffParams) -> Result<(), IpcError> {
+        self.call::<_, IgnoredAny>("handoff", params).await?;
+        Ok(())
+    }
+
+    /// Mark the caller's current task as done.
+    pub async fn task_complete(
+        &self,
+        params: &TaskCompleteParams,
+    ) -> Result<Option<TaskCompleteResult>, IpcError> {
+        self.call("task_complete", params).await
+    }
+
+    /// Raise a blocker on a run.
+    pub async fn report_impediment(&self, params: &ReportImpedimentParams) -> Result<(), IpcError> {
+        self.call::<_, IgnoredAny>("report_impediment", params)
+            .await?;
+        Ok(())
+    }
+
+    /// Clear impediments the caller is allowed to resolve.
+    pub async fn resolve_run_impediments(
+        &self,
+        params: &ResolveImpedimentsParams,
+    ) -> Result<Option<ResolveImpedimentsResult>, IpcError> {
+        self.call("resolve_run_impediments", params).await
+    }
+
+    /// Fetch the playbook and role instructions for a run.
+    pub async fn get_protocol(&self, params: &ProtocolParams) -> Result<ProtocolInfo, IpcError> {
+        self.call("get_protocol", params).await
+    }
+
+    /// What core knows about a terminal session (attached run, role);
+    /// `None` when core returns no state for it.
+    pub async fn get_session_state(
+        &self,
+        params: &SessionStateParams,
+    ) -> Result<Option<SessionStateInfo>, IpcError> {
+        self.call("get_session_state", params).await
+    }
+
+    /// Every agent session core currently tracks.
+    pub async fn list_active_sessions(&self) -> Result<Vec<ActiveSession>, IpcError> {
+        let sessions: ActiveSessions = self
+            .call("list_active_sessions", &serde_json::json!({}))
+            .await?;
+        Ok(sessions.sessions)
+    }
+
+    /// Register a terminal session as an active agent.
+    pub async fn register_session(&self, params: &RegisterSessionParams) -> Result<(), IpcError> {
+        self.call::<_, IgnoredAny>("register_session", params)
+            .await?;
+        Ok(())
+    }
+
+    /// Forget a terminal session.
+    pub async fn deregister_session(
+        &self,
+        params: &DeregisterSessionParams,
+    ) -> Result<(), IpcError> {
+        self.call::<_, IgnoredAny>("deregister_session", params)
+            .await?;
+        Ok(())
+    }
+
+    /// Forward a Claude Code hook event.
+    pub async fn hook_event(&self, params: &HookEventParams) -> Result<(), IpcError> {
+        self.call::<_, IgnoredAny>("hook_event", params).await?;
+        Ok(())
+    }
+
+    /// All runs, newest first.
+    pub async fn list_runs(&self) -> Result<Vec<RunSummary>, IpcError> {
+        self.call("list_runs", &serde_json::json!({})).await
+    }
+
+    /// Full status of one run, including agents and open impediments.
+    pub async fn get_run_status(&self, run_id: &str) -> Result<RunStatus, IpcError> {
+        self.call(
+            "get_run_status",
+            &RunIdParams {
+                run_id: run_id.to_string(),
+            },
+        )
+        .await
+    }
+
+    /// Delete a run and everything attached to it.
+    pub async fn delete_run(&self, run_id: &str) -> Result<(), IpcError> {
+        self.call::<_, IgnoredAny>(
+            "delete_run",
+            &RunIdParams {
+                run_id: run_id.to_string(),
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Cancel, pause, resume or archive a run. Core rejects transitions the
+    /// run's current status doesn't allow with `invalid_state`.
+    pub async fn update_run_state(&self, params: &UpdateRunStateParams) -> Result<(), IpcError> {
+        self.call::<_, IgnoredAny>("update_run_state", params)
+            .await?;
+        Ok(())
+    }
+
+    /// One page of a run's conversation, oldest first.
+    pub async fn get_conversation_events(
+        &self,
+        params: &ConversationEventsParams,
+    ) -> Result<Vec<ConversationEvent>, IpcError> {
+        let events: ConversationEvents = self.call("get_conversation_events", params).await?;
+        Ok(events.events)
+    }
+
+    /// Hold a subscription open and receive conversation events as core emits them.
+    pub async fn subscribe_conversation_events(
+        &self,
+        params: &ConversationEventsParams,
+    ) -> Result<EventStream<ConversationEvent>, IpcError> {
+        self.subscribe("subscribe_conversation_events", params)
+            .await
+    }
+
+    /// Artifacts tracked for a run.
+    pub async fn artifact_list(&self, run_id: &str) -> Result<Vec<ArtifactSummary>, IpcError> {
+        let list: ArtifactList = self
+            .call(
+                "artifact_list",
+                &RunIdParams {
+                    run_id: run_id.to_string(),
+                },
+            )
+            .await?;
+        Ok(list.artifacts)
+    }
+
+    /// Register a file as an artifact, or snapshot a new version of it.
+    pub async fn artifact_sync(
+        &self,
+        params: &ArtifactPathParams,
+    ) -> Result<ArtifactSyncResult, IpcError> {
+        self.call("artifact_sync", params).await
+    }
+
+    /// Move an artifact (and optionally the file) keeping its comments.
+    pub async fn artifact_move(
+        &self,
+        params: &ArtifactMoveParams,
+    ) -> Result<ArtifactMoveResult, IpcError> {
+        self.call("artifact_move", params).await
+    }
+
+    /// Add a comment anchored to text in an artifact.
+    pub async fn artifact_add_comment(
+        &self,
+        params: &AddCommentParams,
+    ) -> Result<CommentRef, IpcError> {
+        self.call("artifact_add_comment", params).await
+    }
+
+    /// Comments on an artifact, filtered by status.
+    pub async fn artifact_list_comments(
+        &self,
+        params: &ListCommentsParams,
+    ) -> Result<Vec<Comment>, IpcError> {
+        let list: CommentList = self.call("artifact_list_comments", params).await?;
+        Ok(list.comments)
+    }
+
+    /// One comment and its replies.
+    pub async fn artifact_get_comment(
+        &self,
+        params: &GetCommentParams,
+    ) -> Result<CommentThread, IpcError> {
+        self.call("artifact_get_comment", params).await
+    }
+
+    /// Reply in an existing comment thread.
+    pub async fn artifact_reply_comment(
+        &self,
+        params: &ReplyCommentParams,
+    ) -> Result<CommentRef, IpcError> {
+        self.call("artifact_reply_comment", params).await
+    }
+
+    /// Resolve a comment, optionally with a closing reply.
+    pub async fn artifact_resolve_comment(
+        &self,
+        params: &ResolveCommentParams,
+    ) -> Result<(), IpcError> {
+        self.call::<_, IgnoredAny>("artifact_resolve_comment", params)
+            .await?;
+        Ok(())
+    }
+
+    /// Version history of an artifact, newest first.
+    pub async fn artifact_list_versions(
+        &self,
+        params: &ArtifactPathParams,
+    ) -> Result<Vec<ArtifactVersion>, IpcError> {
+        let list: ArtifactVersionList = self.call("artifact_list_versions", params).await?;
+        Ok(list.versions)
+    }
+
+    /// Full content of one artifact version.
+    pub async fn artifact_get_version(
+        &self,
+        params: &GetVersionParams,
+    ) -> Result<ArtifactVersionContent, IpcError> {
+        self.call("artifact_get_version", params).await
+    }
+
+    /// Recreate a run from a `run export` archive.
+    pub async fn import_run(
+        &self,
+        params: &ImportRunParams<'_>,
+    ) -> Result<ImportRunResult, IpcError> {
+        self.call("import_run", params).await
+    }
+}
+
+/// Answer `request_json` from a replayed cassette: its recorded lines are
+/// fed through a socket pair, so callers read them exactly as from core.
+fn replay(cassette: &Cassette, request_json: &str) -> Result<BufReader<UnixStream>, IpcError> {
+    let lines = cassette.answer(request_json).ok_or_else(|| {
+        let method = serde_json::from_str::<serde_json::Value>(request_json)
+            .ok()
+            .and_then(|r| r["method"].as_str().map(String::from))
+            .unwrap_or_default();
+        IpcError::RequestFailed(format!(
+            "no recorded response for `{}` with these params in {}",
+            method,
+            cassette.path().display()
+        ))
+    })?;
+
+    let (ours, mut theirs) =
+        UnixStream::pair().map_err(|e| IpcError::ConnectionFailed(e.to_string()))?;
+    let mut payload = lines.join("\n");
+    payload.push('\n');
+    // Written from a task: a long recording may not fit the socket buffer
+    tokio::spawn(async move {
+        let _ = theirs.write_all(payload.as_bytes()).await;
+    });
+    Ok(BufReader::new(ours))
+}
+
+/// Set to `1` to skip `check_socket_trust` (same as `--allow-untrusted-socket`)
+pub const ALLOW_UNTRUSTED_SOCKET_ENV: &str = "HOTWIRED_ALLOW_UNTRUSTED_SOCKET";
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|v| matches!(v.trim(), "1" | "true" | "yes"))
+}
+
+/// Check that `socket` (after following symlinks), the directory holding
+/// it, and the directory holding the path as given are owned by the current
+/// user and not writable by group or others, so no other user could have put
+/// it there or can swap it afterwards. Returns the resolved path to connect
+/// to, or the reason on failure.
+pub fn check_socket_trust(socket: &Path) -> Result<PathBuf, String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let resolved = std::fs::canonicalize(socket).map_err(|e| e.to_string())?;
+    // SAFETY: getuid has no preconditions and cannot fail
+    let uid = unsafe { libc::getuid() };
+
+    let dir = resolved.parent().unwrap_or(Path::new("/"));
+    let link_dir = match socket.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    for (what, path) in [
+        ("socket", resolved.as_path()),
+        ("socket directory", dir),
+        ("socket directory", link_dir),
+    ] {
+        let meta = std::fs::metadata(path).map_err(|e| e.to_string())?;
+        if meta.uid() != uid {
+            return Err(format!(
+                "{} {} is owned by uid {}, not you (uid {})",
+                what,
+                path.display(),
+                meta.uid(),
+                uid
+            ));
+        }
+        let mode = meta.mode() & 0o777;
+        if mode & 0o022 != 0 {
+            return Err(format!(
+                "{} {} is writable by other users (mode {:o})",
+                what,
+                path.display(),
+                mode
+            ));
+        }
+    }
+    Ok(resolved)
+}
+
+/// Environment variable whose value overrides every configured token
+pub const TOKEN_ENV: &str = "HOTWIRED_TOKEN";
+
+/// `HOTWIRED_TOKEN`, if set and non-empty
+pub fn token_from_env() -> Option<String> {
+    std::env::var(TOKEN_ENV)
+        .ok()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// Where the auth token is stored: `~/.hotwired/auth_token`
+pub fn auth_token_path() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".hotwired").join("auth_token"))
+}
+
+/// Convert a user-supplied number of seconds into a timeout; `0` disables it.
+///
+/// Negative, non-finite and unrepresentably large values are rejected.
+pub fn parse_timeout_secs(secs: f64) -> Result<Option<Duration>, String> {
+    if !secs.is_finite() || secs < 0.0 {
+        return Err(format!(
+            "invalid timeout {:?}: expected a finite number of seconds, 0 to disable",
+            secs
+        ));
+    }
+    if secs == 0.0 {
+        return Ok(None);
+    }
+    Duration::try_from_secs_f64(secs)
+        .map(Some)
+        .map_err(|_| format!("invalid timeout {:?}: too large", secs))
+}
+
+/// Newline-delimited events pushed by hotwired-core over a held-open socket.
+pub struct EventStream<T> {
+    reader: BufReader<UnixStream>,
+    trace: Option<(Arc<Tracer>, Span)>,
+    recording: Option<(Arc<Cassette>, usize)>,
+    _event: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> EventStream<T> {
+    /// Wait for the next event. Returns `Ok(None)` once core closes the stream.
+    pub async fn next_event(&mut self) -> Result<Option<T>, IpcError> {
         let mut line = String::new();
-        reader
-            .read_line(&mut line)
+        loop {
+            line.clear();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .await
+                .map_err(|e| IpcError::ConnectionFailed(e.to_string()))?;
+
+            if read == 0 {
+                return Ok(None);
+            }
+            // Blank lines are keepalives
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some((tracer, span)) = &self.trace {
+                tracer.event(span, &line);
+            }
+            if let Some((cassette, index)) = &self.recording {
+                cassette.record_event(*index, &line);
+            }
+
+            return serde_json::from_str(&line)
+                .map(Some)
+                .map_err(|e| IpcError::InvalidResponse(e.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_event_stream_skips_keepalives_and_ends_on_close() {
+        let (ours, mut theirs) = UnixStream::pair().unwrap();
+        theirs
+            .write_all(b"{\"sequenceNum\":1}\n\n{\"sequenceNum\":2}\n")
             .await
-            .map_err(|e| IpcError::RequestFailed(e.to_string()))?;
+            .unwrap();
+        drop(theirs);
 
-        serde_json::from_str(&line).map_err(|e| IpcError::InvalidResponse(e.to_string()))
+        let mut stream: EventStream<ConversationEvent> = EventStream {
+            reader: BufReader::new(ours),
+            trace: None,
+            recording: None,
+            _event: PhantomData,
+        };
+        let first = stream.next_event().await.unwrap().unwrap();
+        assert_eq!(first.sequence_num, Some(1));
+        let second = stream.next_event().await.unwrap().unwrap();
+        assert_eq!(second.sequence_num, Some(2));
+        assert!(stream.next_event().await.unwrap().is_none());
     }
 
-    pub fn socket_path(&self) -> &str {
-        &self.socket_path
+    #[test]
+    fn test_legacy_capabilities_cover_only_pre_handshake_methods() {
+        let caps = Capabilities::legacy(Some("0.1.0".into()));
+        assert_eq!(caps.protocol_version, 0);
+        assert!(caps.supports("get_run_status"));
+        assert!(!caps.supports("subscribe_conversation_events"));
+    }
+
+    #[test]
+    fn test_hello_capabilities_use_advertised_methods() {
+        let caps = Capabilities::from_hello(HelloInfo {
+            protocol_version: 1,
+            core_version: Some("0.2.0".into()),
+            methods: vec!["ping".into(), "subscribe_conversation_events".into()],
+        });
+        assert!(caps.supports("subscribe_conversation_events"));
+        assert!(caps.supports("hello"));
+        assert!(!caps.supports("get_run_status"));
+    }
+
+    #[test]
+    fn test_unsupported_error_names_required_version() {
+        let err = IpcError::Unsupported {
+            method: "subscribe_conversation_events".into(),
+            required: required_protocol("subscribe_conversation_events"),
+            available: 0,
+        };
+        assert_eq!(
+            err.to_string(),
+            "hotwired-core is too old for `subscribe_conversation_events` (needs protocol v1, core speaks v0)"
+        );
+    }
+
+    #[test]
+    fn test_socket_trust_rejects_shared_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("core.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o700)).unwrap();
+        assert_eq!(
+            check_socket_trust(&socket),
+            Ok(std::fs::canonicalize(&socket).unwrap())
+        );
+
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o1777)).unwrap();
+        let reason = check_socket_trust(&socket).unwrap_err();
+        assert!(reason.contains("socket directory"), "{}", reason);
+        assert!(reason.contains("writable by other users"), "{}", reason);
+    }
+
+    #[test]
+    fn test_socket_trust_rejects_symlink_in_shared_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let private = dir.path().join("private");
+        let shared = dir.path().join("shared");
+        std::fs::create_dir(&private).unwrap();
+        std::fs::create_dir(&shared).unwrap();
+        std::fs::set_permissions(&private, std::fs::Permissions::from_mode(0o700)).unwrap();
+        std::fs::set_permissions(&shared, std::fs::Permissions::from_mode(0o777)).unwrap();
+        let socket = private.join("core.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        let link = shared.join("core.sock");
+        std::os::unix::fs::symlink(&socket, &link).unwrap();
+
+        // The target is trusted, but anyone could swap the link
+        let reason = check_socket_trust(&link).unwrap_err();
+        assert!(reason.contains(&shared.display().to_string()), "{}", reason);
+        assert!(reason.contains("writable by other users"), "{}", reason);
+
+        std::fs::set_permissions(&shared, std::fs::Permissions::from_mode(0o700)).unwrap();
+        assert_eq!(
+            check_socket_trust(&link),
+            Ok(std::fs::canonicalize(&socket).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_timeout_secs_zero_disables() {
+        assert_eq!(parse_timeout_secs(0.0), Ok(None));
+        assert_eq!(
+            parse_timeout_secs(2.5),
+            Ok(Some(Duration::from_millis(2500)))
+        );
+    }
+
+    #[test]
+    fn test_parse_timeout_secs_rejects_invalid() {
+        for input in ["inf", "NaN", "1e30", "-1"] {
+            let secs: f64 = input.parse().unwrap();
+            assert!(parse_timeout_secs(secs).is_err(), "{}", input);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_times_out_when_core_hangs() {
+        let dir = std::env::temp_dir().join(format!("hotwired-ipc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("hung.sock");
+        let _ = std::fs::remove_file(&socket);
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+
+        // Accept and never answer
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            drop(stream);
+        });
+
+        let client = HotwiredClient::new(Some(socket.to_string_lossy().to_string()))
+            .with_timeout(Some(Duration::from_millis(50)));
+        let result = client.request("hotwire", serde_json::json!({})).await;
+        assert!(matches!(result, Err(IpcError::Timeout(_))));
+
+        server.abort();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_into_data_maps_failure_to_backend_error() {
+        let response: SocketResponse =
+            serde_json::from_str(r#"{"success":false,"error":"run not found"}"#).unwrap();
+        match response.into_data::<RunStatus>() {
+            Err(IpcError::Backend { code, message }) => {
+                assert_eq!(code, ErrorCode::Unknown);
+                assert_eq!(message, "run not found");
+            }
+            other => panic!("unexpected: {:?}", other.map(|r| r.run_id)),
+        }
+    }
+
+    #[test]
+    fn test_into_data_parses_error_code() {
+        let response: SocketResponse = serde_json::from_str(
+            r#"{"success":false,"error":"ambiguous prefix","code":"ambiguous"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            response.into_data::<RunStatus>(),
+            Err(IpcError::Backend {
+                code: ErrorCode::Ambiguous,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_error_code_parse_unknown_falls_back() {
+        assert_eq!(ErrorCode::parse("not_found"), ErrorCode::NotFound);
+        assert_eq!(ErrorCode::parse("something_new"), ErrorCode::Unknown);
     }
 
-    pub async fn health_check(&self) -> Result<SocketResponse, IpcError> {
-        self.request("ping", serde_json::json!({})).await
+    #[test]
+    fn test_into_data_reports_shape_mismatch() {
+        let response: SocketResponse =
+            serde_json::from_str(r#"{"success":true,"data":{"status":"active"}}"#).unwrap();
+        assert!(matches!(
+            response.into_data::<RunStatus>(),
+            Err(IpcError::InvalidResponse(_))
+        ));
     }
 }
@@ -0,0 +1,38 @@
+//! Rust client for Hotwired
+//!
+//! Talks to hotwired-core over its Unix socket, the same way the `hotwired`
+//! CLI does. Nothing here prints or exits; every operation returns a
+//! `Result` so it can be embedded in other tools.
+//!
+//! ```no_run
+//! use hotwired_client::{Artifacts, HotwiredClient};
+//! use std::path::Path;
+//!
+//! # async fn demo() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = HotwiredClient::new(None);
+//! for run in client.list_runs().await? {
+//!     println!("{} {}", run.id, run.status);
+//! }
+//!
+//! // Workflow operations act as the terminal's attached session
+//! let artifacts = Artifacts::for_current_session(&client).await?;
+//! artifacts.sync(Path::new("docs/PRD.md")).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod archive;
+pub mod artifacts;
+pub mod cassette;
+pub mod config;
+pub mod ipc;
+pub mod models;
+pub mod session;
+pub mod terminal;
+pub mod trace;
+
+pub use artifacts::{ArtifactError, Artifacts};
+pub use config::{Config, ConfigError, Context};
+pub use ipc::{Capabilities, ErrorCode, EventStream, HotwiredClient, IpcError};
+pub use session::{validate_session, validate_terminal, SessionState, ValidationError};
+pub use terminal::{Multiplexer, TerminalContext};
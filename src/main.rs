@@ -1,7 +1,8 @@
 mod commands;
-mod ipc;
 
 use clap::{Parser, Subcommand};
+use hotwired_client::{archive, cassette, config, ipc, models, trace, TerminalContext};
+use models::RunTransition;
 use std::path::PathBuf;
 
 /// Hotwired CLI - manage workflows, sessions, and runs
@@ -9,6 +10,7 @@ use std::path::PathBuf;
 #[command(name = "hotwired-cli")]
 #[command(about = "CLI for Hotwired multi-agent workflow orchestration")]
 #[command(disable_version_flag = true)]
+#[command(after_long_help = commands::exit_code::EXIT_CODES_HELP)]
 struct Args {
     /// Print version information (cli and backend)
     #[arg(long, short = 'V')]
@@ -19,6 +21,41 @@ struct Args {
     #[arg(long, short = 's', global = true)]
     socket_path: Option<String>,
 
+    /// Send the auth token even if the socket or its directory isn't owned by
+    /// you or is writable by others. Also $HOTWIRED_ALLOW_UNTRUSTED_SOCKET=1
+    #[arg(long, global = true)]
+    allow_untrusted_socket: bool,
+
+    /// Log every socket request (token redacted), raw response and latency
+    /// to stderr. Also $HOTWIRED_TRACE=1
+    #[arg(long, global = true)]
+    trace: bool,
+
+    /// Append the --trace log to this file instead of stderr.
+    /// Also $HOTWIRED_TRACE_FILE
+    #[arg(long, global = true, value_name = "PATH")]
+    trace_file: Option<PathBuf>,
+
+    /// Act as this registered session instead of detecting the terminal
+    /// (for containers and CI). Defaults to $HOTWIRED_SESSION
+    #[arg(long, global = true, value_name = "NAME")]
+    session: Option<String>,
+
+    /// Backend context from ~/.hotwired/config.toml.
+    /// Defaults to $HOTWIRED_CONTEXT, or current-context
+    #[arg(long, global = true, value_name = "NAME")]
+    context: Option<String>,
+
+    /// Seconds to wait for each backend request before giving up (0 = no limit).
+    /// Defaults to $HOTWIRED_TIMEOUT, the context's default, or 30
+    #[arg(long, global = true, value_name = "SECONDS")]
+    timeout: Option<f64>,
+
+    /// Output format: human-readable table, or json/ndjson for scripts.
+    /// Defaults to the context's default, or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<commands::OutputFormat>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -59,12 +96,86 @@ enum Commands {
     /// Authentication and connection status
     ///
     /// Check if the Hotwired backend is running and whether
-    /// the current user has a valid auth token configured.
+    /// the current user has a valid auth token configured, and
+    /// install, verify or remove that token. $HOTWIRED_TOKEN
+    /// overrides any stored token.
+    ///
+    /// Examples:
+    ///   hotwired-cli auth status
+    ///   pbpaste | hotwired-cli auth token set
+    ///   hotwired-cli auth verify
+    ///   hotwired-cli auth logout
     Auth {
         #[command(subcommand)]
         action: AuthAction,
     },
 
+    /// Diagnose why workflow commands fail in this terminal
+    ///
+    /// Checks the backend, core/CLI versions, terminal detection, session
+    /// registration, run attachment, auth token permissions and Claude Code
+    /// hooks, printing a fix for each failed check. Exits 1 if any check fails.
+    ///
+    /// Example output:
+    ///
+    ///   [ok  ] backend     running (v0.4.2) at ~/.hotwired/hotwired.sock
+    ///   [ok  ] terminal    tmux:default:%3 (tmux)
+    ///   [FAIL] registered  hotwired-core doesn't know session 'tmux:default:%3'
+    ///                fix: Start Claude Code in this terminal with the Hotwired hooks installed
+    Doctor,
+
+    /// Call any hotwired-core socket method directly
+    ///
+    /// Sends the params as-is and prints the full response envelope
+    /// (success, data, error, code) as JSON. Exits with the usual code for
+    /// the response's error code when core reports failure. For scripting
+    /// against core features the CLI has no subcommand for yet.
+    ///
+    /// Examples:
+    ///   hotwired-cli api list_runs
+    ///   hotwired-cli api get_run_status --params '{"runId": "a1b2c3d4-..."}'
+    ///   hotwired-cli api artifact_list --inject-session
+    ///   echo '{"runId": "..."}' | hotwired-cli api get_run_status --params -
+    Api {
+        /// Socket method name (e.g. list_runs, get_run_status)
+        method: String,
+
+        /// Params as inline JSON, @path to read a file, or - for stdin
+        #[arg(long, value_name = "JSON|@FILE|-")]
+        params: Option<String>,
+
+        /// Add runId and source (your role) from this terminal's session,
+        /// unless the params already set them
+        #[arg(long)]
+        inject_session: bool,
+    },
+
+    /// Switch between hotwired-core backends
+    ///
+    /// Contexts live in ~/.hotwired/config.toml and name a socket, a token
+    /// source (token-file or token-env), a default project and per-context
+    /// command defaults. --context or $HOTWIRED_CONTEXT override
+    /// current-context for one invocation.
+    ///
+    ///   current-context = "stable"
+    ///
+    ///   [contexts.stable]
+    ///   socket = "~/.hotwired/hotwired.sock"
+    ///
+    ///   [contexts.dev]
+    ///   socket = "~/.hotwired-dev/hotwired.sock"
+    ///   token-env = "HOTWIRED_DEV_TOKEN"
+    ///   project = "~/Code/my-project"
+    ///
+    ///   [contexts.dev.defaults]
+    ///   output = "json"
+    ///   timeout = 10
+    ///   playbook = "plan-build"
+    Context {
+        #[command(subcommand)]
+        action: ContextAction,
+    },
+
     // =========================================================================
     // WORKFLOW COMMANDS (for agents)
     // =========================================================================
@@ -425,7 +536,12 @@ enum CommentAction {
 
 #[derive(Subcommand)]
 enum RunAction {
-    /// List all runs
+    /// List runs, newest first
+    ///
+    /// Examples:
+    ///   hotwired-cli run list --status active,blocked
+    ///   hotwired-cli run list --playbook plan --since 7d
+    ///   hotwired-cli run list --project . --sort status --limit 20
     ///
     /// Example output:
     ///
@@ -433,7 +549,10 @@ enum RunAction {
     ///   a1b2c3d4   active       executing      Plan > Build             2024-01-15 10:30:00
     ///   e5f6g7h8   completed    complete       Solo Build               2024-01-14 09:15:00
     #[command(alias = "ls")]
-    List,
+    List {
+        #[command(flatten)]
+        filters: commands::filter::ListFilters,
+    },
 
     /// Show details of a run
     ///
@@ -453,15 +572,175 @@ enum RunAction {
     Show {
         /// Run ID (full UUID or short prefix)
         id: String,
+
+        /// Also print the run's history: phase changes, handoffs, completed
+        /// tasks, impediments, artifact and comment activity, agents joining
+        /// and leaving
+        #[arg(long)]
+        timeline: bool,
     },
 
-    /// Remove a run and its associated data
+    /// Remove runs and their associated data
     ///
-    /// Accepts full UUIDs or short prefixes (like git).
+    /// Accepts full UUIDs or short prefixes (like git). Every ID is resolved
+    /// before anything is deleted, and you're asked to confirm unless --yes.
+    ///
+    /// Example:
+    ///   hotwired-cli run rm a1b2 e5f6 --yes
     #[command(alias = "rm")]
     Remove {
+        /// Run IDs (full UUIDs or short prefixes)
+        #[arg(required = true)]
+        ids: Vec<String>,
+
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Remove old runs in bulk
+    ///
+    /// Only finished runs (completed, failed, cancelled, archived) are
+    /// considered unless --status says otherwise. Shows how many artifacts
+    /// and comments go with them and asks to confirm unless --yes.
+    ///
+    /// Example:
+    ///   hotwired-cli run prune --status completed --older-than 14d --dry-run
+    Prune {
+        /// Only these statuses (repeat or comma-separate)
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<String>,
+
+        /// Only runs created longer ago than this (30m, 12h, 14d, 2w)
+        #[arg(long, value_parser = commands::time::parse_age, value_name = "AGE")]
+        older_than: Option<chrono::Duration>,
+
+        /// Show what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Save a run to a self-contained archive
+    ///
+    /// The .tar.gz holds the run's metadata, its full event log, every
+    /// version of every artifact and all comment threads. It survives
+    /// `run rm` and can be restored with `run import`, here or elsewhere.
+    ///
+    /// Example:
+    ///   hotwired-cli run export a1b2 -o run.tar.gz
+    Export {
+        /// Run ID (full UUID or short prefix)
+        id: String,
+
+        /// Archive to write [default: run-<id>.tar.gz]
+        #[arg(long = "output-file", short = 'o', value_name = "PATH")]
+        file: Option<PathBuf>,
+    },
+
+    /// Restore a run from a `run export` archive
+    ///
+    /// Example:
+    ///   hotwired-cli run import run.tar.gz
+    Import {
+        /// Archive written by `run export`
+        file: PathBuf,
+    },
+
+    /// Render a run's whole conversation as a document
+    ///
+    /// Grouped by phase, with handoff summaries (details folded away),
+    /// impediments and how they were resolved, and the artifacts each phase
+    /// touched. Written to stdout.
+    ///
+    /// Example:
+    ///   hotwired-cli run transcript a1b2 --format html > run.html
+    Transcript {
+        /// Run ID (full UUID or short prefix)
+        id: String,
+
+        /// Document format
+        #[arg(long, value_enum, default_value = "md")]
+        format: commands::transcript::TranscriptFormat,
+    },
+
+    /// Durations, handoffs, impediments and completions of runs
+    ///
+    /// With an ID, reports on that run. Without one, adds up every run
+    /// matching the filters, which take the same flags as `run list`.
+    ///
+    /// Examples:
+    ///   hotwired-cli run stats a1b2
+    ///   hotwired-cli run stats --since 7d --playbook plan-build
+    Stats {
+        /// Run ID (full UUID or short prefix); omit to aggregate
+        #[arg(conflicts_with = "ListFilters")]
+        id: Option<String>,
+
+        #[command(flatten)]
+        filters: commands::filter::ListFilters,
+    },
+
+    /// Compare two runs side by side
+    ///
+    /// Shows playbook, phases reached, duration, handoffs and impediments of
+    /// each run, then a unified diff of the latest versions of artifacts
+    /// both runs have at the same path.
+    ///
+    /// Example:
+    ///   hotwired-cli run diff a1b2 e5f6
+    Diff {
+        /// First run ID (full UUID or short prefix)
+        a: String,
+
+        /// Second run ID (full UUID or short prefix)
+        b: String,
+    },
+
+    /// Cancel a run; its agents can no longer act on it
+    ///
+    /// Example:
+    ///   hotwired-cli run cancel a1b2 --reason "wrong branch"
+    Cancel {
+        /// Run ID (full UUID or short prefix)
+        id: String,
+
+        /// Why, shown to attached agents in `status`
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Pause a run; attached agents see it in `status` and hold off
+    Pause {
+        /// Run ID (full UUID or short prefix)
+        id: String,
+
+        /// Why, shown to attached agents in `status`
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Resume a paused run
+    Resume {
+        /// Run ID (full UUID or short prefix)
+        id: String,
+
+        /// Note recorded with the change
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Archive a finished run, keeping its history but closing it for good
+    Archive {
         /// Run ID (full UUID or short prefix)
         id: String,
+
+        /// Note recorded with the change
+        #[arg(long)]
+        reason: Option<String>,
     },
 }
 
@@ -469,13 +748,19 @@ enum RunAction {
 enum SessionAction {
     /// List active agent sessions
     ///
+    /// Takes the same filters as `run list`; --phase, --playbook,
+    /// --since/--until and --sort created apply to the attached run.
+    ///
     /// Example output:
     ///
     ///   SESSION                      PROJECT                                      WORKTREE
     ///   hotwired-strategist          /Users/dev/Code/my-project                   no
     ///   hotwired-builder             /Users/dev/Code/my-project                   yes
     #[command(alias = "ls")]
-    List,
+    List {
+        #[command(flatten)]
+        filters: commands::filter::ListFilters,
+    },
 
     /// Show details of a session
     ///
@@ -499,25 +784,29 @@ enum SessionAction {
     /// Register a new session with the Hotwired backend
     ///
     /// Called by Claude Code's SessionStart hook to register
-    /// this terminal as an active agent session.
+    /// this terminal as an active agent session. The session name comes from
+    /// --session, $HOTWIRED_SESSION, or the detected terminal; -s is the
+    /// global --socket-path, not the session name.
     Register {
-        /// Zellij session name
-        #[arg(long, short = 's')]
-        session: String,
-
         /// Project directory path
         #[arg(long, short = 'p')]
         project: String,
+
+        /// Session name; hooks written before the global --session pass it here
+        #[arg(long, hide = true)]
+        session: Option<String>,
     },
 
     /// Deregister a session from the Hotwired backend
     ///
     /// Called by Claude Code's SessionEnd hook to clean up
-    /// when a terminal session ends.
+    /// when a terminal session ends. The session name comes from
+    /// --session, $HOTWIRED_SESSION, or the detected terminal; -s is the
+    /// global --socket-path, not the session name.
     Deregister {
-        /// Zellij session name
-        #[arg(long, short = 's')]
-        session: String,
+        /// Session name; hooks written before the global --session pass it here
+        #[arg(long, hide = true)]
+        session: Option<String>,
     },
 }
 
@@ -536,6 +825,33 @@ enum InternalAction {
     SessionEnd,
 }
 
+#[derive(Subcommand)]
+enum ContextAction {
+    /// List configured contexts (* marks the active one)
+    ///
+    /// Example output:
+    ///
+    ///     NAME             SOCKET                                       PROJECT
+    ///   * dev              /Users/dev/.hotwired-dev/hotwired.sock       /Users/dev/Code/my-project
+    ///     stable           /Users/dev/.hotwired/hotwired.sock           -
+    #[command(alias = "ls")]
+    List,
+
+    /// Make a context the current-context
+    Use {
+        /// Context name
+        name: String,
+    },
+
+    /// Show a context's socket, token source, project and defaults
+    ///
+    /// Never prints the token itself.
+    Show {
+        /// Context name (defaults to the active context)
+        name: Option<String>,
+    },
+}
+
 #[derive(Subcommand)]
 enum AuthAction {
     /// Show connection and authentication status
@@ -554,50 +870,200 @@ enum AuthAction {
     ///   Backend:    not running
     ///   Socket:     ~/.hotwired/hotwired.sock (not found)
     ///   Auth token: not configured
+    ///
+    /// Warns on stderr when the token file is readable by other users.
     Status,
+
+    /// Manage the stored auth token
+    Token {
+        #[command(subcommand)]
+        action: TokenAction,
+    },
+
+    /// Delete the stored auth token
+    Logout,
+
+    /// Check that hotwired-core accepts the configured token
+    ///
+    /// Sends an authenticated request; exits 7 if core rejects the token.
+    Verify,
+}
+
+#[derive(Subcommand)]
+enum TokenAction {
+    /// Store a token read from stdin (written with mode 0600)
+    ///
+    /// The token goes to the active context's token-file, or
+    /// ~/.hotwired/auth_token. $HOTWIRED_TOKEN overrides any stored token.
+    ///
+    /// Examples:
+    ///   pbpaste | hotwired-cli auth token set
+    ///   hotwired-cli auth token set < token.txt
+    Set,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
+    // Context management must work even when the active context is broken
+    if let Some(Commands::Context { action }) = &args.command {
+        let output = args.output.unwrap_or_default();
+        let explicit = args.context.as_deref();
+        match action {
+            ContextAction::List => commands::context::list(explicit, output),
+            ContextAction::Use { name } => commands::context::use_context(name, output),
+            ContextAction::Show { name } => {
+                commands::context::show(name.as_deref(), explicit, output)
+            }
+        }
+        return Ok(());
+    }
+
+    // `session register/deregister -s NAME` used to name the session; now
+    // `-s` is the global --socket-path
+    if let Some(Commands::Session {
+        action: SessionAction::Register { .. } | SessionAction::Deregister { .. },
+    }) = &args.command
+    {
+        if let Some(path) = args
+            .socket_path
+            .as_deref()
+            .filter(|p| !std::path::Path::new(p).exists())
+        {
+            eprintln!("error: no socket at '{}' (-s is --socket-path)", path);
+            eprintln!("To name the session, pass --session {}", path);
+            std::process::exit(commands::exit_code::USAGE);
+        }
+    }
+
+    // Hooks are fire-and-forget: a broken config must not fail the host tool
+    let hook = matches!(args.command, Some(Commands::Internal { .. }));
+    let context = if hook {
+        commands::context::resolve_or_default(args.context.as_deref())
+    } else {
+        commands::context::resolve(args.context.as_deref())
+    };
+    // Auth and doctor diagnose a broken token source instead of failing on it
+    let lenient = hook
+        || matches!(
+            args.command,
+            Some(Commands::Auth { .. }) | Some(Commands::Doctor)
+        );
+    let mut client = context
+        .client(args.socket_path, lenient)
+        .allow_untrusted_socket(args.allow_untrusted_socket)
+        .with_tracer(commands::tracer(args.trace, args.trace_file.as_deref()))
+        .with_cassette(commands::cassette())
+        .with_session(args.session);
+    // Hooks use their own timeout, so a bad configured one can't fail them
+    if !hook {
+        if let Some(timeout) = context.timeout(args.timeout) {
+            client = client.with_timeout(timeout);
+        }
+    }
+
     if args.version {
-        commands::print_version(args.socket_path).await;
+        commands::print_version(&client).await;
         return Ok(());
     }
 
-    let client = ipc::HotwiredClient::new(args.socket_path);
+    let output = if hook {
+        commands::OutputFormat::default()
+    } else {
+        context.output(args.output)
+    };
 
     match args.command {
         // Management commands
         Some(Commands::Run { action }) => match action {
-            RunAction::List => commands::run::list(&client).await,
-            RunAction::Show { id } => commands::run::show(&client, &id).await,
-            RunAction::Remove { id } => commands::run::remove(&client, &id).await,
+            RunAction::List { filters } => commands::run::list(&client, &filters, output).await,
+            RunAction::Show { id, timeline } => {
+                commands::run::show(&client, &id, timeline, output).await
+            }
+            RunAction::Remove { ids, yes } => {
+                commands::run::remove(&client, &ids, yes, output).await
+            }
+            RunAction::Prune {
+                status,
+                older_than,
+                dry_run,
+                yes,
+            } => commands::run::prune(&client, status, older_than, dry_run, yes, output).await,
+            RunAction::Export { id, file } => {
+                commands::export::export(&client, &id, file, output).await
+            }
+            RunAction::Import { file } => commands::export::import(&client, &file, output).await,
+            RunAction::Transcript { id, format } => {
+                commands::transcript::run(&client, &id, format, output).await
+            }
+            RunAction::Stats { id, filters } => {
+                commands::stats::run(&client, id.as_deref(), &filters, output).await
+            }
+            RunAction::Diff { a, b } => commands::diff::diff(&client, &a, &b, output).await,
+            RunAction::Cancel { id, reason } => {
+                commands::run::transition(&client, &id, RunTransition::Cancel, reason, output).await
+            }
+            RunAction::Pause { id, reason } => {
+                commands::run::transition(&client, &id, RunTransition::Pause, reason, output).await
+            }
+            RunAction::Resume { id, reason } => {
+                commands::run::transition(&client, &id, RunTransition::Resume, reason, output).await
+            }
+            RunAction::Archive { id, reason } => {
+                commands::run::transition(&client, &id, RunTransition::Archive, reason, output)
+                    .await
+            }
         },
         Some(Commands::Session { action }) => match action {
-            SessionAction::List => commands::session::list(&client).await,
-            SessionAction::Show { name } => commands::session::show(&client, &name).await,
-            SessionAction::Remove { name } => commands::session::remove(&client, &name).await,
-            SessionAction::Register { session, project } => {
-                commands::session::register(&client, &session, &project).await
+            SessionAction::List { filters } => {
+                commands::session::list(&client, &filters, output).await
+            }
+            SessionAction::Show { name } => commands::session::show(&client, &name, output).await,
+            SessionAction::Remove { name } => {
+                commands::session::remove(&client, &name, output).await
+            }
+            SessionAction::Register { project, session } => {
+                let terminal = session
+                    .map(TerminalContext::explicit)
+                    .unwrap_or_else(|| commands::validate::require_terminal(&client));
+                commands::session::register(&client, &terminal.session_key, &project, output).await
             }
             SessionAction::Deregister { session } => {
-                commands::session::deregister(&client, &session).await
+                let terminal = session
+                    .map(TerminalContext::explicit)
+                    .unwrap_or_else(|| commands::validate::require_terminal(&client));
+                commands::session::deregister(&client, &terminal.session_key, output).await
             }
         },
         Some(Commands::Auth { action }) => match action {
-            AuthAction::Status => commands::auth::status(&client).await,
+            AuthAction::Status => commands::auth::status(&client, &context.context, output).await,
+            AuthAction::Token {
+                action: TokenAction::Set,
+            } => commands::auth::set_token(&context.context, output),
+            AuthAction::Logout => commands::auth::logout(&context.context, output),
+            AuthAction::Verify => commands::auth::verify(&client, &context.context, output).await,
         },
+        Some(Commands::Doctor) => commands::doctor::run(&client, &context.context, output).await,
+        Some(Commands::Api {
+            method,
+            params,
+            inject_session,
+        }) => commands::api::run(&client, &method, params.as_deref(), inject_session, output).await,
+        Some(Commands::Context { .. }) => unreachable!("handled before connecting"),
 
         // Internal commands (Claude Code hooks)
-        Some(Commands::Internal { action }) => match action {
-            InternalAction::HookEvent { event } => {
-                commands::internal::hook_event(&client, &event).await
+        Some(Commands::Internal { action }) => {
+            // Hooks must never stall Claude waiting on a hung backend
+            let client = client.with_timeout(Some(commands::internal::HOOK_TIMEOUT));
+            match action {
+                InternalAction::HookEvent { event } => {
+                    commands::internal::hook_event(&client, &event).await
+                }
+                InternalAction::SessionStart => commands::internal::session_start(&client).await,
+                InternalAction::SessionEnd => commands::internal::session_end(&client).await,
             }
-            InternalAction::SessionStart => commands::internal::session_start(&client).await,
-            InternalAction::SessionEnd => commands::internal::session_end(&client).await,
-        },
+        }
 
         // Workflow commands
         Some(Commands::Hotwire {
@@ -605,48 +1071,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             intent,
             project,
         }) => {
-            commands::hotwire::run(&client, playbook, intent, project).await;
+            let playbook = playbook.or_else(|| context.context.defaults.playbook.clone());
+            let project = project.or_else(|| context.context.project_dir());
+            commands::hotwire::run(&client, playbook, intent, project, output).await;
         }
         Some(Commands::Pair { run_id, role }) => {
-            commands::pair::run(&client, &run_id, role.as_deref()).await;
+            commands::pair::run(&client, &run_id, role.as_deref(), output).await;
         }
         Some(Commands::Send { to, message }) => {
             let msg = message.join(" ");
-            commands::send::run(&client, &to, &msg).await;
+            commands::send::run(&client, &to, &msg, output).await;
         }
         Some(Commands::Inbox { watch, since }) => {
-            commands::inbox::run(&client, watch, since).await;
+            commands::inbox::run(&client, watch, since, output).await;
         }
         Some(Commands::Complete { outcome }) => {
-            commands::complete::run(&client, outcome).await;
+            commands::complete::run(&client, outcome, output).await;
         }
         Some(Commands::Impediment {
             description,
             r#type,
             suggestion,
         }) => {
-            commands::impediment::run(&client, &description, &r#type, suggestion).await;
+            commands::impediment::run(&client, &description, &r#type, suggestion, output).await;
         }
         Some(Commands::Resolve { message }) => {
-            commands::impediment::resolve(&client, &message).await;
+            commands::impediment::resolve(&client, &message, output).await;
         }
         Some(Commands::Status) => {
-            commands::status::run(&client).await;
+            commands::status::run(&client, output).await;
         }
         Some(Commands::Protocol) => {
-            commands::protocol::run(&client).await;
+            commands::protocol::run(&client, output).await;
         }
 
         // Artifact commands
         Some(Commands::Artifact { action }) => match action {
-            ArtifactAction::List => commands::artifact::list(&client).await,
-            ArtifactAction::Sync { path } => commands::artifact::sync(&client, &path).await,
+            ArtifactAction::List => commands::artifact::list(&client, output).await,
+            ArtifactAction::Sync { path } => commands::artifact::sync(&client, &path, output).await,
             ArtifactAction::Move {
                 old_path,
                 new_path,
                 refs_only,
             } => {
-                commands::artifact::move_artifact(&client, &old_path, &new_path, refs_only).await;
+                commands::artifact::move_artifact(&client, &old_path, &new_path, refs_only, output)
+                    .await;
             }
             ArtifactAction::Comment {
                 action: comment_action,
@@ -663,6 +1132,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         &target_text,
                         &message,
                         reply_to.as_deref(),
+                        output,
                     )
                     .await;
                 }
@@ -670,26 +1140,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     comment_id,
                     message,
                 } => {
-                    commands::artifact::reply_comment(&client, &comment_id, &message).await;
+                    commands::artifact::reply_comment(&client, &comment_id, &message, output).await;
                 }
                 CommentAction::Show { comment_id } => {
-                    commands::artifact::show_comment(&client, &comment_id).await;
+                    commands::artifact::show_comment(&client, &comment_id, output).await;
                 }
                 CommentAction::List { path, status } => {
-                    commands::artifact::list_comments(&client, &path, &status).await;
+                    commands::artifact::list_comments(&client, &path, &status, output).await;
                 }
             },
             ArtifactAction::Comments { path, status } => {
-                commands::artifact::list_comments(&client, &path, &status).await;
+                commands::artifact::list_comments(&client, &path, &status, output).await;
             }
             ArtifactAction::Resolve { comment_id, reply } => {
-                commands::artifact::resolve(&client, &comment_id, reply.as_deref()).await;
+                commands::artifact::resolve(&client, &comment_id, reply.as_deref(), output).await;
             }
             ArtifactAction::Versions { path } => {
-                commands::artifact::list_versions(&client, &path).await;
+                commands::artifact::list_versions(&client, &path, output).await;
             }
             ArtifactAction::Version { path, version } => {
-                commands::artifact::get_version(&client, &path, version).await;
+                commands::artifact::get_version(&client, &path, version, output).await;
             }
         },
 
@@ -0,0 +1,682 @@
+//! Typed request/response models for the hotwired-core socket protocol
+//!
+//! Every method the CLI calls has a params struct (serialized into the
+//! request's `params`) and, where core returns data, a response struct.
+//! Core speaks camelCase on the wire, so every model uses
+//! `#[serde(rename_all = "camelCase")]` and field names stay idiomatic Rust.
+
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Shared params
+// =============================================================================
+
+/// Params for methods that only need a run: `get_run_status`, `delete_run`,
+/// `artifact_list`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunIdParams {
+    pub run_id: String,
+}
+
+// =============================================================================
+// ping
+// =============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PingInfo {
+    pub version: Option<String>,
+}
+
+// =============================================================================
+// hello (protocol handshake)
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HelloParams {
+    pub client_version: String,
+    pub protocol_version: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HelloInfo {
+    pub protocol_version: u32,
+    pub core_version: Option<String>,
+    /// Every socket method this core instance serves
+    #[serde(default)]
+    pub methods: Vec<String>,
+}
+
+// =============================================================================
+// hotwire / pair
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotwireParams {
+    /// Session key from `TerminalContext`; the wire name predates tmux and
+    /// WezTerm support, and Zellij keys are still the bare session name
+    pub zellij_session: Option<String>,
+    pub project_path: Option<String>,
+    pub suggested_playbook: Option<String>,
+    pub intent: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotwireResult {
+    /// `started` or `needs_confirmation`
+    pub status: String,
+    pub run_id: Option<String>,
+    pub role: Option<String>,
+    pub protocol: Option<String>,
+    pub pending_run_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairParams {
+    /// Session key from `TerminalContext`
+    pub zellij_session: Option<String>,
+    pub project_path: Option<String>,
+    pub run_id: String,
+    pub role_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairResult {
+    pub role: String,
+    pub protocol: Option<String>,
+}
+
+// =============================================================================
+// handoff / task_complete / impediments
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffParams {
+    pub run_id: String,
+    pub to: String,
+    pub summary: String,
+    pub details: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskCompleteParams {
+    pub run_id: String,
+    pub task_description: String,
+    pub source: String,
+    pub outcome: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskCompleteResult {
+    pub next_action: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportImpedimentParams {
+    pub run_id: String,
+    pub source: String,
+    pub impediment_type: String,
+    pub description: String,
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveImpedimentsParams {
+    pub run_id: String,
+    pub source: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveImpedimentsResult {
+    pub message: Option<String>,
+}
+
+// =============================================================================
+// get_protocol
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolParams {
+    pub run_id: String,
+    pub role: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolInfo {
+    pub run_id: String,
+    pub template_name: Option<String>,
+    pub playbook_protocol: Option<String>,
+    pub role_protocol: Option<String>,
+    pub initialization_condition: Option<String>,
+    pub capabilities: Option<RoleCapabilities>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleCapabilities {
+    #[serde(default)]
+    pub can_resolve_impediments: bool,
+}
+
+// =============================================================================
+// Sessions
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStateParams {
+    /// Session key from `TerminalContext`
+    pub zellij_session: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStateInfo {
+    pub attached_run_id: Option<String>,
+    pub run_status: Option<String>,
+    pub role_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSessions {
+    #[serde(default)]
+    pub sessions: Vec<ActiveSession>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSession {
+    pub session_name: String,
+    pub project_dir: Option<String>,
+    pub session_status: Option<String>,
+    pub attached_run_id: Option<String>,
+    pub role_id: Option<String>,
+    #[serde(default)]
+    pub is_worktree: bool,
+    pub git_common_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterSessionParams {
+    pub session_name: String,
+    pub project_dir: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeregisterSessionParams {
+    pub session_name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookEventParams {
+    pub event_name: String,
+    /// Session key from `TerminalContext`
+    pub zellij_session: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+    pub payload: serde_json::Value,
+}
+
+// =============================================================================
+// Runs
+// =============================================================================
+
+/// One entry of `list_runs` (core returns a bare array)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub id: String,
+    pub status: String,
+    pub phase: Option<String>,
+    pub template_name: Option<String>,
+    #[serde(default)]
+    pub template_id: Option<String>,
+    #[serde(default, alias = "projectDir")]
+    pub project_path: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunStatus {
+    pub run_id: String,
+    pub status: String,
+    pub phase: Option<String>,
+    pub template_name: Option<String>,
+    /// Why the run was last paused, cancelled or archived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_reason: Option<String>,
+    #[serde(default)]
+    pub has_protocol: bool,
+    #[serde(default)]
+    pub connected_agents: Vec<ConnectedAgent>,
+    #[serde(default)]
+    pub impediments: Vec<Impediment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectedAgent {
+    pub role_id: String,
+    pub session_name: Option<String>,
+    pub agent_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Impediment {
+    pub source: String,
+    pub description: String,
+    pub impediment_type: Option<String>,
+}
+
+/// Lifecycle change requested with `update_run_state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunTransition {
+    Cancel,
+    Pause,
+    Resume,
+    Archive,
+}
+
+impl RunTransition {
+    /// Status the run has once core applies the transition.
+    pub fn target_status(self) -> &'static str {
+        match self {
+            RunTransition::Cancel => "cancelled",
+            RunTransition::Pause => "paused",
+            RunTransition::Resume => "active",
+            RunTransition::Archive => "archived",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRunStateParams {
+    pub run_id: String,
+    pub action: RunTransition,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+// =============================================================================
+// Conversation events
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationEventsParams {
+    pub run_id: String,
+    pub since_sequence: Option<i64>,
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationEvents {
+    #[serde(default)]
+    pub events: Vec<ConversationEvent>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationEvent {
+    pub sequence_num: Option<i64>,
+    pub source: Option<String>,
+    pub event_type: Option<String>,
+    pub content: Option<String>,
+    pub summary: Option<String>,
+    pub created_at: Option<String>,
+    /// Event-specific fields (handoff target, impediment type, ...) kept verbatim
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// `eventType`s core records in a run's conversation.
+///
+/// Workflow requests are logged under their IPC method name, carrying the
+/// request's params as fields: a `handoff` event has `to`, `summary` and
+/// `details` like `HandoffParams`. Events core raises itself (phase changes,
+/// agents joining or leaving) have their own names. Events without a type
+/// are chat messages.
+pub mod event_type {
+    pub const MESSAGE: &str = "message";
+    pub const HANDOFF: &str = "handoff";
+    pub const TASK_COMPLETE: &str = "task_complete";
+    pub const REPORT_IMPEDIMENT: &str = "report_impediment";
+    pub const RESOLVE_IMPEDIMENTS: &str = "resolve_run_impediments";
+    pub const ARTIFACT_SYNC: &str = "artifact_sync";
+    pub const ARTIFACT_MOVE: &str = "artifact_move";
+    pub const ARTIFACT_ADD_COMMENT: &str = "artifact_add_comment";
+    pub const ARTIFACT_REPLY_COMMENT: &str = "artifact_reply_comment";
+    pub const ARTIFACT_RESOLVE_COMMENT: &str = "artifact_resolve_comment";
+    pub const UPDATE_RUN_STATE: &str = "update_run_state";
+    /// Fields `fromPhase`, `toPhase`
+    pub const PHASE_CHANGE: &str = "phase_change";
+    /// Field `sessionName`
+    pub const AGENT_JOINED: &str = "agent_joined";
+    /// Field `sessionName`
+    pub const AGENT_LEFT: &str = "agent_left";
+}
+
+impl ConversationEvent {
+    /// Display text: `content`, falling back to `summary`
+    pub fn text(&self) -> &str {
+        self.content
+            .as_deref()
+            .or(self.summary.as_deref())
+            .unwrap_or("")
+    }
+}
+
+// =============================================================================
+// Artifacts
+// =============================================================================
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactList {
+    #[serde(default)]
+    pub artifacts: Vec<ArtifactSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactSummary {
+    pub path: String,
+    /// `ok` or `missing`
+    pub status: String,
+    #[serde(default)]
+    pub comment_count: i64,
+    #[serde(default)]
+    pub version_count: i64,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactPathParams {
+    pub run_id: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactSyncResult {
+    /// `registered` or `synced`
+    pub status: String,
+    pub title: Option<String>,
+    pub version: Option<i64>,
+    #[serde(default)]
+    pub comments_relocated: i64,
+    #[serde(default)]
+    pub comments_orphaned: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactMoveParams {
+    pub run_id: String,
+    pub old_path: String,
+    pub new_path: String,
+    pub refs_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactMoveResult {
+    #[serde(default)]
+    pub comments_preserved: i64,
+    #[serde(default)]
+    pub file_moved: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddCommentParams {
+    pub run_id: String,
+    pub path: String,
+    pub target_text: String,
+    pub comment: String,
+    pub author: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_comment_id: Option<String>,
+}
+
+/// Returned by `artifact_add_comment` and `artifact_reply_comment`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentRef {
+    pub comment_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCommentsParams {
+    pub run_id: String,
+    pub path: String,
+    /// `open`, `resolved` or `all`
+    pub status_filter: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentList {
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    pub comment_id: String,
+    pub target_text: Option<String>,
+    #[serde(default)]
+    pub comment: String,
+    pub status: Option<String>,
+    pub author: Option<String>,
+    /// Set on replies: the top-level comment this belongs to
+    pub thread_id: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCommentParams {
+    pub run_id: String,
+    pub comment_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentThread {
+    pub comment: Option<Comment>,
+    #[serde(default)]
+    pub replies: Vec<Comment>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplyCommentParams {
+    pub run_id: String,
+    pub comment_id: String,
+    pub message: String,
+    pub author: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveCommentParams {
+    pub run_id: String,
+    pub comment_id: String,
+    pub resolved_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactVersionList {
+    #[serde(default)]
+    pub versions: Vec<ArtifactVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactVersion {
+    pub version: i64,
+    pub timestamp: Option<String>,
+    #[serde(default)]
+    pub lines_added: i64,
+    #[serde(default)]
+    pub lines_removed: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetVersionParams {
+    pub run_id: String,
+    pub path: String,
+    pub version: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactVersionContent {
+    pub title: Option<String>,
+    pub timestamp: Option<String>,
+    #[serde(default)]
+    pub content: String,
+}
+
+// =============================================================================
+// Run archives (run export / import_run)
+// =============================================================================
+
+/// Everything core knows about one run, as `run export` saves it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunArchive {
+    pub manifest: ArchiveManifest,
+    pub run: RunStatus,
+    /// The run's `list_runs` entry, for its creation time and project
+    pub summary: Option<RunSummary>,
+    #[serde(default)]
+    pub events: Vec<ConversationEvent>,
+    #[serde(default)]
+    pub artifacts: Vec<ArchivedArtifact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveManifest {
+    /// Always `hotwired-run`
+    pub format: String,
+    pub version: u32,
+    pub run_id: String,
+    pub exported_at: String,
+    pub cli_version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedArtifact {
+    pub path: String,
+    pub title: Option<String>,
+    /// Oldest first
+    #[serde(default)]
+    pub versions: Vec<ArchivedVersion>,
+    /// Every comment thread, open and resolved
+    #[serde(default)]
+    pub comments: Vec<CommentThread>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedVersion {
+    pub version: i64,
+    pub timestamp: Option<String>,
+    #[serde(default)]
+    pub lines_added: i64,
+    #[serde(default)]
+    pub lines_removed: i64,
+    /// Where the content lives inside an archive file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRunParams<'a> {
+    pub archive: &'a RunArchive,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRunResult {
+    /// Id of the restored run (core may assign a new one)
+    pub run_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_status_defaults_optional_collections() {
+        let status: RunStatus = serde_json::from_value(serde_json::json!({
+            "runId": "abc123",
+            "status": "active",
+        }))
+        .unwrap();
+        assert!(status.connected_agents.is_empty());
+        assert!(status.impediments.is_empty());
+        assert!(!status.has_protocol);
+    }
+
+    #[test]
+    fn test_run_summary_missing_required_field_is_an_error() {
+        let result: Result<RunSummary, _> =
+            serde_json::from_value(serde_json::json!({"status": "active"}));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_conversation_event_keeps_unknown_fields() {
+        let event: ConversationEvent = serde_json::from_value(serde_json::json!({
+            "sequenceNum": 7,
+            "eventType": "handoff",
+            "summary": "Ready for review",
+            "to": "critic",
+        }))
+        .unwrap();
+        assert_eq!(event.sequence_num, Some(7));
+        assert_eq!(event.text(), "Ready for review");
+        assert_eq!(event.extra.get("to").unwrap(), "critic");
+    }
+}
@@ -0,0 +1,147 @@
+//! Session validation
+//!
+//! Workflow operations act on behalf of the terminal they run in: core maps
+//! the terminal's session to a run and a role. `validate_session` resolves
+//! that mapping and rejects terminals that can't act (unregistered, not
+//! attached, or attached to a finished run).
+
+use crate::ipc::{ErrorCode, HotwiredClient, IpcError};
+use crate::models::SessionStateParams;
+use crate::terminal::TerminalContext;
+
+/// Run statuses that no longer accept workflow commands
+pub const TERMINAL_RUN_STATUSES: &[&str] = &["completed", "failed", "cancelled", "archived"];
+
+/// Current session state from hotwired-core
+#[derive(Debug, Clone)]
+pub struct SessionState {
+    pub terminal: TerminalContext,
+    pub run_id: String,
+    pub role_id: String,
+    pub run_status: String,
+}
+
+/// Why the current terminal can't run workflow commands
+#[derive(Debug)]
+pub enum ValidationError {
+    /// Not running inside a terminal the CLI can identify
+    NoTerminalSession,
+    /// Terminal identified, but hotwired-core doesn't know about it
+    SessionNotRegistered,
+    /// Session exists but not attached to any run
+    NotAttachedToRun,
+    /// Attached run is in a terminal state (completed/failed/cancelled/archived)
+    RunNotActive(String),
+    /// IPC error (backend not running, etc.)
+    IpcError(IpcError),
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::NoTerminalSession => {
+                write!(f, "Not running in a Zellij, tmux or WezTerm session")
+            }
+            ValidationError::SessionNotRegistered => {
+                write!(f, "Session not registered with Hotwired")
+            }
+            ValidationError::NotAttachedToRun => write!(f, "Not attached to any run"),
+            ValidationError::RunNotActive(status) => {
+                write!(f, "Run is not active (status: {})", status)
+            }
+            ValidationError::IpcError(e) => write!(f, "IPC error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Resolve the run and role this terminal acts as.
+///
+/// Call this before any workflow operation except `hotwire`/`pair`, which
+/// are how a terminal gets attached in the first place.
+pub async fn validate_session(client: &HotwiredClient) -> Result<SessionState, ValidationError> {
+    // 1. Identify the terminal we're running in (or the session claimed explicitly)
+    let terminal = client
+        .terminal()
+        .ok_or(ValidationError::NoTerminalSession)?;
+    validate_terminal(client, terminal).await
+}
+
+/// Like `validate_session`, for a terminal the caller already identified.
+pub async fn validate_terminal(
+    client: &HotwiredClient,
+    terminal: TerminalContext,
+) -> Result<SessionState, ValidationError> {
+    // 2. Query hotwired-core for session state
+    let data = match client
+        .get_session_state(&SessionStateParams {
+            zellij_session: terminal.session_key.clone(),
+        })
+        .await
+    {
+        Ok(Some(data)) => data,
+        // Core returns nothing, or not_found, for sessions it doesn't know
+        // (cores without error codes fail with no code at all); anything
+        // else, including a reply that doesn't decode, is an error
+        Ok(None)
+        | Err(IpcError::Backend {
+            code: ErrorCode::NotFound | ErrorCode::Unknown,
+            ..
+        }) => return Err(ValidationError::SessionNotRegistered),
+        Err(e) => return Err(ValidationError::IpcError(e)),
+    };
+
+    // 3. Check if attached to a run
+    let run_id = data
+        .attached_run_id
+        .filter(|s| !s.is_empty())
+        .ok_or(ValidationError::NotAttachedToRun)?;
+
+    // 4. Check run status
+    let run_status = data.run_status.unwrap_or_else(|| "unknown".to_string());
+
+    // Only reject truly terminal statuses - blocked/paused runs are still usable
+    if TERMINAL_RUN_STATUSES.contains(&run_status.as_str()) {
+        return Err(ValidationError::RunNotActive(run_status));
+    }
+
+    // 5. Get role
+    let role_id = data.role_id.unwrap_or_else(|| "unknown".to_string());
+
+    Ok(SessionState {
+        terminal,
+        run_id,
+        role_id,
+        run_status,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_error_display() {
+        assert_eq!(
+            ValidationError::NoTerminalSession.to_string(),
+            "Not running in a Zellij, tmux or WezTerm session"
+        );
+        assert_eq!(
+            ValidationError::RunNotActive("completed".to_string()).to_string(),
+            "Run is not active (status: completed)"
+        );
+    }
+
+    #[test]
+    fn test_session_state_clone() {
+        let state = SessionState {
+            terminal: TerminalContext::explicit("test-session"),
+            run_id: "run-123".to_string(),
+            role_id: "strategist".to_string(),
+            run_status: "active".to_string(),
+        };
+        let cloned = state.clone();
+        assert_eq!(cloned.run_id, "run-123");
+    }
+}
@@ -0,0 +1,170 @@
+//! Terminal identity
+//!
+//! Core tracks agents by a session key: the terminal a CLI invocation runs
+//! in. Zellij sessions use their name (what core has always stored); tmux
+//! and WezTerm panes get a prefixed key built from the pane ID, which stays
+//! stable for the lifetime of the pane.
+//!
+//! Containers and CI jobs have no multiplexer; they claim a registered
+//! session directly with `--session` or `HOTWIRED_SESSION`.
+
+use std::fmt;
+
+/// Terminal environment a session key was derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    Zellij,
+    Tmux,
+    WezTerm,
+    /// Identifier supplied via `--session`/`HOTWIRED_SESSION` rather than detected
+    Explicit,
+}
+
+impl fmt::Display for Multiplexer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Multiplexer::Zellij => "Zellij",
+            Multiplexer::Tmux => "tmux",
+            Multiplexer::WezTerm => "WezTerm",
+            Multiplexer::Explicit => "--session/HOTWIRED_SESSION",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Environment variable naming the session explicitly
+pub const SESSION_ENV: &str = "HOTWIRED_SESSION";
+
+/// The terminal this process runs in, and the key core knows it by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalContext {
+    pub multiplexer: Multiplexer,
+    /// Stable identifier sent to core wherever it expects a session name
+    pub session_key: String,
+}
+
+impl TerminalContext {
+    /// Use `id` as the session key, bypassing detection.
+    pub fn explicit(id: impl Into<String>) -> Self {
+        Self {
+            multiplexer: Multiplexer::Explicit,
+            session_key: id.into(),
+        }
+    }
+
+    /// `session` if given, otherwise whatever `detect` finds.
+    pub fn resolve(session: Option<&str>) -> Option<Self> {
+        match session.filter(|s| !s.is_empty()) {
+            Some(id) => Some(Self::explicit(id)),
+            None => Self::detect(),
+        }
+    }
+
+    /// Identify the terminal from the process environment.
+    ///
+    /// `HOTWIRED_SESSION` overrides detection. Otherwise Zellij wins over
+    /// tmux, and tmux over WezTerm, so a multiplexer nested inside a WezTerm
+    /// pane identifies by its own session.
+    pub fn detect() -> Option<Self> {
+        Self::detect_from(|name| std::env::var(name).ok())
+    }
+
+    fn detect_from(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let var = |name: &str| var(name).filter(|v| !v.is_empty());
+
+        if let Some(id) = var(SESSION_ENV) {
+            return Some(Self::explicit(id));
+        }
+
+        if let Some(name) = var("ZELLIJ_SESSION_NAME") {
+            return Some(Self {
+                multiplexer: Multiplexer::Zellij,
+                session_key: name,
+            });
+        }
+
+        if let (Some(tmux), Some(pane)) = (var("TMUX"), var("TMUX_PANE")) {
+            // TMUX is "<socket path>,<server pid>,<session index>"; the socket
+            // name separates independent servers (`tmux -L work`)
+            let socket = tmux.split(',').next().unwrap_or_default();
+            let server = socket.rsplit('/').next().unwrap_or("default");
+            return Some(Self {
+                multiplexer: Multiplexer::Tmux,
+                session_key: format!("tmux:{}:{}", server, pane),
+            });
+        }
+
+        if let Some(pane) = var("WEZTERM_PANE") {
+            return Some(Self {
+                multiplexer: Multiplexer::WezTerm,
+                session_key: format!("wezterm:{}", pane),
+            });
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect(vars: &[(&str, &str)]) -> Option<TerminalContext> {
+        let env: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        TerminalContext::detect_from(|name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn test_zellij_uses_session_name() {
+        let ctx = detect(&[("ZELLIJ_SESSION_NAME", "hotwired-builder")]).unwrap();
+        assert_eq!(ctx.multiplexer, Multiplexer::Zellij);
+        assert_eq!(ctx.session_key, "hotwired-builder");
+    }
+
+    #[test]
+    fn test_tmux_keys_on_server_and_pane() {
+        let ctx = detect(&[
+            ("TMUX", "/tmp/tmux-501/work,4242,0"),
+            ("TMUX_PANE", "%7"),
+            ("WEZTERM_PANE", "3"),
+        ])
+        .unwrap();
+        assert_eq!(ctx.multiplexer, Multiplexer::Tmux);
+        assert_eq!(ctx.session_key, "tmux:work:%7");
+    }
+
+    #[test]
+    fn test_wezterm_pane() {
+        let ctx = detect(&[("WEZTERM_PANE", "3")]).unwrap();
+        assert_eq!(ctx.session_key, "wezterm:3");
+    }
+
+    #[test]
+    fn test_session_env_overrides_detection() {
+        let ctx = detect(&[
+            ("HOTWIRED_SESSION", "ci-builder"),
+            ("ZELLIJ_SESSION_NAME", "hotwired-builder"),
+        ])
+        .unwrap();
+        assert_eq!(ctx, TerminalContext::explicit("ci-builder"));
+    }
+
+    #[test]
+    fn test_resolve_prefers_explicit_session() {
+        let ctx = TerminalContext::resolve(Some("from-flag")).unwrap();
+        assert_eq!(ctx.multiplexer, Multiplexer::Explicit);
+        assert_eq!(ctx.session_key, "from-flag");
+    }
+
+    #[test]
+    fn test_plain_shell_has_no_context() {
+        assert_eq!(detect(&[]), None);
+        assert_eq!(detect(&[("ZELLIJ_SESSION_NAME", "")]), None);
+        // TMUX without a pane (e.g. a detached `tmux run-shell`) can't be keyed
+        assert_eq!(detect(&[("TMUX", "/tmp/tmux-501/default,1,0")]), None);
+    }
+}
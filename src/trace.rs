@@ -0,0 +1,191 @@
+//! Wire tracing
+//!
+//! With `--trace` or `HOTWIRED_TRACE=1` the client logs every request line it
+//! writes (token redacted), every raw line core answers with, and how long
+//! each exchange took. Output goes to stderr, or appends to the file named
+//! by `--trace-file`/`HOTWIRED_TRACE_FILE`, so a trace can be attached to a
+//! bug report against hotwired-core, including for the `internal` hooks
+//! whose output Claude Code swallows.
+//!
+//! ```text
+//! [trace #1] -> {"method":"list_runs","params":{},"token":"<redacted>"}
+//! [trace #1] <- 3.2ms {"success":true,"data":[]}
+//! ```
+
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Enables tracing to stderr when set to `1`
+pub const TRACE_ENV: &str = "HOTWIRED_TRACE";
+/// Enables tracing, appending to this file
+pub const TRACE_FILE_ENV: &str = "HOTWIRED_TRACE_FILE";
+
+/// Replaces the token in traced requests
+pub const REDACTED: &str = "<redacted>";
+
+/// Destination for wire trace lines, shared by every request of a client
+pub struct Tracer {
+    out: Mutex<Box<dyn Write + Send>>,
+    next_id: AtomicU64,
+}
+
+impl Tracer {
+    pub fn stderr() -> Self {
+        Self::new(Box::new(std::io::stderr()))
+    }
+
+    /// Append to `path`, creating it if needed. A new file is private to the
+    /// user: payloads are logged in full, only the token is redacted.
+    pub fn to_file(path: &Path) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .mode(0o600)
+            .open(path)?;
+        Ok(Self::new(Box::new(file)))
+    }
+
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out: Mutex::new(out),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Tracer requested by `HOTWIRED_TRACE_FILE` or `HOTWIRED_TRACE`, if any.
+    pub fn from_env() -> std::io::Result<Option<Self>> {
+        if let Some(path) = std::env::var_os(TRACE_FILE_ENV).filter(|p| !p.is_empty()) {
+            return Self::to_file(Path::new(&path)).map(Some);
+        }
+        let enabled =
+            std::env::var(TRACE_ENV).is_ok_and(|v| matches!(v.trim(), "1" | "true" | "yes"));
+        Ok(enabled.then(Self::stderr))
+    }
+
+    /// Log an outgoing request line and start timing its exchange.
+    pub fn request(&self, line: &str) -> Span {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.write(id, &format!("-> {}", line));
+        Span {
+            id,
+            start: Instant::now(),
+        }
+    }
+
+    /// Log a raw line core sent in answer to `span`.
+    pub fn response(&self, span: &Span, line: &str) {
+        self.write(
+            span.id,
+            &format!("<- {} {}", span.elapsed(), line.trim_end()),
+        );
+    }
+
+    /// Log a line pushed on a held-open subscription.
+    pub fn event(&self, span: &Span, line: &str) {
+        self.write(
+            span.id,
+            &format!("<~ {} {}", span.elapsed(), line.trim_end()),
+        );
+    }
+
+    /// Log why `span` ended without a response.
+    pub fn failure(&self, span: &Span, error: &dyn std::fmt::Display) {
+        self.write(span.id, &format!("!! {} {}", span.elapsed(), error));
+    }
+
+    fn write(&self, id: u64, message: &str) {
+        // Tracing must never break the command it observes
+        if let Ok(mut out) = self.out.lock() {
+            let _ = writeln!(out, "[trace #{}] {}", id, message);
+            let _ = out.flush();
+        }
+    }
+}
+
+/// One traced exchange
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    id: u64,
+    start: Instant,
+}
+
+impl Span {
+    fn elapsed(&self) -> String {
+        format!("{:.1}ms", self.start.elapsed().as_secs_f64() * 1000.0)
+    }
+}
+
+/// `request` serialized for the trace, with its token replaced by `REDACTED`.
+pub fn redact(mut request: serde_json::Value) -> String {
+    if let Some(token) = request.get_mut("token") {
+        if !token.is_null() {
+            *token = serde_json::Value::String(REDACTED.to_string());
+        }
+    }
+    request.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Writer that keeps everything in a shared buffer
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_redact_replaces_token_only() {
+        let line = redact(serde_json::json!({
+            "method": "ping",
+            "params": {"token": "not-the-auth-token"},
+            "token": "secret",
+        }));
+        assert!(!line.contains("\"secret\""));
+        assert!(line.contains(REDACTED));
+        assert!(line.contains("not-the-auth-token"));
+    }
+
+    #[test]
+    fn test_spans_number_exchanges() {
+        let buffer = Buffer::default();
+        let tracer = Tracer::new(Box::new(buffer.clone()));
+        let first = tracer.request("{\"method\":\"ping\"}");
+        let second = tracer.request("{\"method\":\"hello\"}");
+        tracer.response(&second, "{\"success\":true}\n");
+        tracer.failure(&first, &"connection reset");
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("[trace #1] -> "));
+        assert!(lines[2].starts_with("[trace #2] <- "));
+        assert!(lines[2].ends_with("ms {\"success\":true}"));
+        assert!(lines[3].starts_with("[trace #1] !! "));
+    }
+
+    #[test]
+    fn test_trace_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.log");
+        Tracer::to_file(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,2373 @@
+//! End-to-end tests: the real `hotwired` binary against a mock hotwired-core
+//!
+//! Each subcommand is exercised for the happy path, a `success: false`
+//! response, a malformed response and a dropped connection, checking both
+//! what the CLI prints and the exact params it sends.
+
+mod support;
+
+use serde_json::json;
+use support::{stderr, stdout, MockCore, Reply};
+
+const RUN_ID: &str = "a1b2c3d4-e5f6-7890-abcd-ef1234567890";
+
+fn attached() -> MockCore {
+    let core = MockCore::start();
+    core.attach("builder", RUN_ID);
+    core
+}
+
+fn exit_code(output: &std::process::Output) -> i32 {
+    output.status.code().expect("terminated by signal")
+}
+
+// ============================================================================
+// CONNECTION FAILURES
+// ============================================================================
+
+#[test]
+fn test_backend_not_running_exits_not_connected() {
+    let core = MockCore::start();
+    std::fs::remove_file(core.socket_path()).unwrap();
+    let out = core.run(&["run", "list"]);
+    assert_eq!(exit_code(&out), 3);
+    assert!(stderr(&out).contains("not connected"));
+}
+
+#[test]
+fn test_malformed_response_exits_protocol_error() {
+    let core = MockCore::start();
+    core.on("list_runs", Reply::Raw("{not json".into()));
+    let out = core.run(&["run", "list"]);
+    assert_eq!(exit_code(&out), 12);
+}
+
+#[test]
+fn test_wrong_shape_response_exits_protocol_error() {
+    let core = MockCore::start();
+    core.on("list_runs", Reply::Ok(json!({"runs": "nope"})));
+    let out = core.run(&["run", "list"]);
+    assert_eq!(exit_code(&out), 12);
+}
+
+#[test]
+fn test_disconnect_mid_request_fails() {
+    let core = MockCore::start();
+    core.on("get_run_status", Reply::Disconnect);
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    let out = core.run(&["run", "show", "a1b2"]);
+    assert!(!out.status.success());
+    assert_eq!(stdout(&out), "");
+}
+
+#[test]
+fn test_hung_backend_times_out() {
+    let core = MockCore::start();
+    core.on("list_runs", Reply::Hang);
+    let out = core.run(&["--timeout", "0.3", "run", "list"]);
+    assert_eq!(exit_code(&out), 4);
+    assert!(stderr(&out).contains("Timed out"));
+}
+
+#[test]
+fn test_trace_logs_wire_with_token_redacted() {
+    let core = MockCore::start();
+    write_token(&core, 0o600);
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    let out = core.run(&["--trace", "run", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let trace = stderr(&out);
+    assert!(trace.contains("[trace #1] -> "));
+    assert!(trace.contains("\"method\":\"list_runs\""));
+    assert!(trace.contains("\"token\":\"<redacted>\""));
+    assert!(!trace.contains("secret"));
+    // Raw response line, with latency
+    assert!(trace.contains(&format!("ms {{\"data\":[{{\"id\":\"{}\"", RUN_ID)));
+    // stdout stays clean
+    assert!(!stdout(&out).contains("[trace"));
+}
+
+#[test]
+fn test_trace_env_to_file_covers_hooks_and_failures() {
+    let core = MockCore::start();
+    let log = core.home().join("trace.log");
+    core.on("hook_event", Reply::Hang);
+    let out = core
+        .cli()
+        .env("HOTWIRED_TRACE_FILE", &log)
+        .args(["internal", "hook-event", "stop"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(stderr(&out).is_empty(), "{}", stderr(&out));
+
+    let trace = std::fs::read_to_string(&log).unwrap();
+    assert!(trace.contains("\"method\":\"hook_event\""));
+    assert!(trace.contains("!! "));
+    assert!(trace.contains("Timed out"));
+}
+
+#[test]
+fn test_record_then_replay_without_backend() {
+    let core = MockCore::start();
+    write_token(&core, 0o600);
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({"runId": RUN_ID, "status": "active", "phase": "executing"})),
+    );
+    let cassette = core.home().join("cassette.json");
+
+    let recorded = core
+        .cli()
+        .env("HOTWIRED_RECORD", &cassette)
+        .args(["run", "show", "a1b2"])
+        .output()
+        .unwrap();
+    assert!(recorded.status.success(), "{}", stderr(&recorded));
+    let saved = std::fs::read_to_string(&cassette).unwrap();
+    assert!(saved.contains("\"get_run_status\""));
+    assert!(!saved.contains("secret"));
+
+    // No backend at all from here on
+    std::fs::remove_file(core.socket_path()).unwrap();
+    let replayed = core
+        .cli()
+        .env("HOTWIRED_REPLAY", &cassette)
+        .args(["run", "show", "a1b2"])
+        .output()
+        .unwrap();
+    assert!(replayed.status.success(), "{}", stderr(&replayed));
+    assert_eq!(stdout(&replayed), stdout(&recorded));
+
+    // Requests the cassette never saw fail clearly
+    let missing = core
+        .cli()
+        .env("HOTWIRED_REPLAY", &cassette)
+        .args(["session", "list"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&missing), 1);
+    assert!(stderr(&missing).contains("no recorded response for `list_active_sessions`"));
+}
+
+#[test]
+fn test_replay_reproduces_malformed_response() {
+    let core = MockCore::start();
+    core.on("list_runs", Reply::Raw("not json".into()));
+    let cassette = core.home().join("cassette.json");
+    let out = core
+        .cli()
+        .env("HOTWIRED_RECORD", &cassette)
+        .args(["run", "list"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 12);
+
+    std::fs::remove_file(core.socket_path()).unwrap();
+    let out = core
+        .cli()
+        .env("HOTWIRED_REPLAY", &cassette)
+        .args(["run", "list"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 12);
+}
+
+#[test]
+fn test_replay_missing_cassette_fails() {
+    let core = MockCore::start();
+    let out = core
+        .cli()
+        .env("HOTWIRED_REPLAY", core.home().join("nope.json"))
+        .args(["run", "list"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 1);
+    assert!(stderr(&out).contains("cannot read cassette"));
+}
+
+// ============================================================================
+// RUN
+// ============================================================================
+
+#[test]
+fn test_run_list() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{
+            "id": RUN_ID,
+            "status": "active",
+            "phase": "executing",
+            "templateName": "Plan > Build",
+            "createdAt": "2024-01-15T10:30:00Z",
+        }])),
+    );
+    let out = core.run(&["run", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let text = stdout(&out);
+    assert!(text.contains("a1b2c3d4"));
+    assert!(text.contains("executing"));
+    assert!(text.contains("Plan > Build"));
+}
+
+#[test]
+fn test_run_list_json_output() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    let out = core.run(&["--output", "json", "run", "list"]);
+    let parsed: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(parsed[0]["id"], RUN_ID);
+}
+
+#[test]
+fn test_run_list_backend_failure() {
+    let core = MockCore::start();
+    core.on("list_runs", Reply::fail("database locked"));
+    let out = core.run(&["run", "list"]);
+    assert_eq!(exit_code(&out), 1);
+    assert!(stderr(&out).contains("database locked"));
+}
+
+fn many_runs_reply() -> Reply {
+    Reply::Ok(json!([
+        {"id": "11111111", "status": "completed", "phase": "complete", "templateName": "Solo Build", "createdAt": "2024-01-01T00:00:00Z"},
+        {"id": "22222222", "status": "active", "phase": "executing", "templateName": "Plan > Build", "createdAt": "2024-03-01T00:00:00Z"},
+        {"id": "33333333", "status": "blocked", "phase": "planning", "templateName": "Plan > Build", "createdAt": "2024-02-01T00:00:00Z"},
+    ]))
+}
+
+fn listed_ids(out: &std::process::Output) -> Vec<String> {
+    let parsed: serde_json::Value = serde_json::from_str(&stdout(out)).unwrap();
+    parsed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_run_list_filters_and_sorts() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+
+    let out = core.run(&["--output", "json", "run", "list"]);
+    assert_eq!(listed_ids(&out), ["22222222", "33333333", "11111111"]);
+
+    let out = core.run(&[
+        "--output",
+        "json",
+        "run",
+        "list",
+        "--status",
+        "active,completed",
+    ]);
+    assert_eq!(listed_ids(&out), ["22222222", "11111111"]);
+
+    let out = core.run(&[
+        "--output",
+        "json",
+        "run",
+        "list",
+        "--playbook",
+        "plan",
+        "--phase",
+        "planning",
+    ]);
+    assert_eq!(listed_ids(&out), ["33333333"]);
+
+    let out = core.run(&[
+        "--output",
+        "json",
+        "run",
+        "list",
+        "--since",
+        "2024-01-15",
+        "--until",
+        "2024-02-15",
+    ]);
+    assert_eq!(listed_ids(&out), ["33333333"]);
+}
+
+#[test]
+fn test_run_list_pages() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+    let out = core.run(&[
+        "--output", "json", "run", "list", "--offset", "1", "--limit", "1",
+    ]);
+    assert_eq!(listed_ids(&out), ["33333333"]);
+}
+
+#[test]
+fn test_run_list_rejects_bad_since() {
+    let core = MockCore::start();
+    let out = core.run(&["run", "list", "--since", "last tuesday"]);
+    assert_eq!(exit_code(&out), 2);
+    assert!(stderr(&out).contains("not a date"));
+    assert!(core.requests().is_empty());
+}
+
+#[test]
+fn test_run_show_resolves_prefix() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({
+            "runId": RUN_ID,
+            "status": "active",
+            "phase": "executing",
+            "hasProtocol": true,
+            "connectedAgents": [{"roleId": "builder", "sessionName": "hw-builder", "agentType": "claude"}],
+        })),
+    );
+    let out = core.run(&["run", "show", "a1b2"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("hw-builder"));
+    assert_eq!(core.only_params("get_run_status"), json!({"runId": RUN_ID}));
+}
+
+fn run_history_reply() -> Reply {
+    Reply::Ok(json!({"events": [
+        {"sequenceNum": 1, "source": "strategist", "eventType": "agent_joined", "createdAt": "2024-01-15T10:00:00Z"},
+        {"sequenceNum": 2, "source": "strategist", "eventType": "message", "content": "thinking out loud", "createdAt": "2024-01-15T10:01:00Z"},
+        {"sequenceNum": 3, "source": "strategist", "eventType": "phase_change", "fromPhase": "planning", "toPhase": "executing", "createdAt": "2024-01-15T10:05:00Z"},
+        {"sequenceNum": 4, "source": "strategist", "eventType": "handoff", "to": "builder", "content": "Start on task 1", "createdAt": "2024-01-15T10:06:00Z"},
+        {"sequenceNum": 5, "source": "builder", "eventType": "report_impediment", "impedimentType": "technical", "description": "No API key", "createdAt": "2024-01-15T10:20:00Z"},
+        {"sequenceNum": 6, "source": "builder", "eventType": "resolve_run_impediments", "message": "Key added", "createdAt": "2024-01-15T10:40:00Z"},
+        {"sequenceNum": 7, "source": "builder", "eventType": "artifact_sync", "path": "PLAN.md", "version": 2, "createdAt": "2024-01-15T10:45:00Z"},
+        {"sequenceNum": 8, "source": "builder", "eventType": "task_complete", "taskDescription": "Task 1", "outcome": "success", "createdAt": "2024-01-15T11:00:00Z"},
+    ]}))
+}
+
+#[test]
+fn test_run_show_timeline() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({"runId": RUN_ID, "status": "active"})),
+    );
+    core.on("get_conversation_events", run_history_reply());
+    let out = core.run(&["run", "show", "a1b2", "--timeline"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let text = stdout(&out);
+    assert!(text.contains("Timeline:"));
+    assert!(text.contains("2024-01-15 10:05:00"));
+    assert!(text.contains("planning -> executing"));
+    assert!(text.contains("-> builder: Start on task 1"));
+    assert!(text.contains("[technical] No API key"));
+    assert!(text.contains("Key added"));
+    assert!(text.contains("PLAN.md v2"));
+    assert!(text.contains("Task 1 (success)"));
+    assert!(!text.contains("thinking out loud"));
+    // Paging continues until a page no longer advances the cursor
+    let pages = core.params_for("get_conversation_events");
+    assert_eq!(
+        pages[0],
+        json!({"runId": RUN_ID, "sinceSequence": 0, "limit": 100})
+    );
+    assert_eq!(pages.len(), 2);
+}
+
+#[test]
+fn test_run_show_timeline_json_output() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({"runId": RUN_ID, "status": "active"})),
+    );
+    core.on("get_conversation_events", run_history_reply());
+    let out = core.run(&["--output", "json", "run", "show", "a1b2", "--timeline"]);
+    let parsed: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(parsed["runId"], RUN_ID);
+    assert_eq!(parsed["timeline"].as_array().unwrap().len(), 7);
+    assert_eq!(parsed["timeline"][1]["kind"], "phase");
+    assert_eq!(parsed["timeline"][1]["role"], "strategist");
+}
+
+fn script_exportable_run(core: &MockCore) {
+    core.on(
+        "list_runs",
+        Reply::Ok(
+            json!([{"id": RUN_ID, "status": "completed", "createdAt": "2024-01-15T10:00:00Z"}]),
+        ),
+    );
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({"runId": RUN_ID, "status": "completed", "phase": "complete"})),
+    );
+    core.on("get_conversation_events", run_history_reply());
+    core.on(
+        "artifact_list",
+        Reply::Ok(
+            json!({"artifacts": [{"path": "docs/PLAN.md", "status": "ok", "title": "Plan"}]}),
+        ),
+    );
+    core.on(
+        "artifact_list_versions",
+        Reply::Ok(json!({"versions": [{"version": 2}, {"version": 1}]})),
+    );
+    core.on(
+        "artifact_get_version",
+        Reply::Ok(json!({"content": "# Plan\n"})),
+    );
+    core.on(
+        "artifact_list_comments",
+        Reply::Ok(json!({"comments": [
+            {"commentId": "c1", "comment": "Why?"},
+            {"commentId": "c2", "comment": "Because", "threadId": "c1"},
+        ]})),
+    );
+    core.on(
+        "artifact_get_comment",
+        Reply::Ok(json!({
+            "comment": {"commentId": "c1", "comment": "Why?"},
+            "replies": [{"commentId": "c2", "comment": "Because", "threadId": "c1"}],
+        })),
+    );
+}
+
+#[test]
+fn test_run_export_then_import() {
+    let core = MockCore::start();
+    script_exportable_run(&core);
+    let path = core.home().join("run.tar.gz");
+    let path_arg = path.to_str().unwrap();
+
+    let out = core.run(&["run", "export", "a1b2", "-o", path_arg]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("8 events, 1 artifacts (2 versions), 1 comment threads"));
+    let versions: Vec<serde_json::Value> = core
+        .params_for("artifact_get_version")
+        .into_iter()
+        .map(|p| p["version"].clone())
+        .collect();
+    assert_eq!(versions, [json!(1), json!(2)]);
+    assert_eq!(core.params_for("artifact_get_comment").len(), 1);
+    assert_eq!(
+        core.only_params("artifact_list_comments")["statusFilter"],
+        "all"
+    );
+
+    let target = MockCore::start();
+    target.on("import_run", Reply::Ok(json!({"runId": "ffffffff-new"})));
+    let out = target.run(&["run", "import", path_arg]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Imported run a1b2c3d4 as ffffffff"));
+    let archive = &target.only_params("import_run")["archive"];
+    assert_eq!(archive["manifest"]["format"], "hotwired-run");
+    assert_eq!(archive["run"]["runId"], RUN_ID);
+    assert_eq!(archive["summary"]["createdAt"], "2024-01-15T10:00:00Z");
+    assert_eq!(archive["events"].as_array().unwrap().len(), 8);
+    assert_eq!(
+        archive["artifacts"][0]["versions"][1]["content"],
+        "# Plan\n"
+    );
+    assert_eq!(
+        archive["artifacts"][0]["comments"][0]["replies"][0]["comment"],
+        "Because"
+    );
+}
+
+#[test]
+fn test_run_import_rejects_other_files() {
+    let core = MockCore::start();
+    let path = core.home().join("notes.tar.gz");
+    std::fs::write(&path, "hello").unwrap();
+    let out = core.run(&["run", "import", path.to_str().unwrap()]);
+    assert_eq!(exit_code(&out), 10);
+    assert!(stderr(&out).contains("not a valid run archive"));
+    assert!(core.params_for("import_run").is_empty());
+
+    let out = core.run(&["run", "import", "/nonexistent/run.tar.gz"]);
+    assert_eq!(exit_code(&out), 5);
+}
+
+#[test]
+fn test_run_transcript_markdown() {
+    let core = MockCore::start();
+    script_exportable_run(&core);
+    let out = core.run(&["run", "transcript", "a1b2"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let text = stdout(&out);
+    assert!(text.starts_with("# Run a1b2c3d4"));
+    assert!(text.contains("## Phase: planning"));
+    assert!(text.contains("## Phase: executing"));
+    assert!(text.contains("thinking out loud"));
+    assert!(text.contains("handoff → builder"));
+    assert!(text.contains("impediment (technical): No API key"));
+    assert!(text.contains("Resolved by builder at 2024-01-15 10:40:00: Key added"));
+    assert!(text.contains("_Artifacts touched: `PLAN.md` (v2)_"));
+}
+
+#[test]
+fn test_run_transcript_html_and_txt() {
+    let core = MockCore::start();
+    script_exportable_run(&core);
+    let out = core.run(&["run", "transcript", "a1b2", "--format", "html"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).starts_with("<!DOCTYPE html>"));
+    assert!(stdout(&out).contains("<h2>Phase: executing</h2>"));
+
+    let out = core.run(&["run", "transcript", "a1b2", "--format", "txt"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("IMPEDIMENT (technical): No API key"));
+}
+
+#[test]
+fn test_run_stats_single_run() {
+    let core = MockCore::start();
+    script_exportable_run(&core);
+    let out = core.run(&["--output", "json", "run", "stats", "a1b2"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let stats: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(stats["runId"], RUN_ID);
+    assert_eq!(stats["durationSecs"], 3600);
+    assert_eq!(stats["phases"][0]["phase"], "planning");
+    assert_eq!(stats["phases"][0]["totalSecs"], 300);
+    assert_eq!(stats["phases"][1]["totalSecs"], 3300);
+    assert_eq!(stats["handoffs"][0]["from"], "strategist");
+    assert_eq!(stats["handoffs"][0]["to"], "builder");
+    assert_eq!(stats["impediments"][0]["impedimentType"], "technical");
+    assert_eq!(stats["impediments"][0]["blockedSecs"], 1200);
+    assert_eq!(stats["blockedByRole"]["builder"], 1200);
+    assert_eq!(stats["artifactVersions"], 1);
+    assert_eq!(stats["completions"]["builder"], 1);
+    // One run list serves both the ID lookup and the report
+    assert_eq!(core.params_for("list_runs").len(), 1);
+
+    let out = core.run(&["run", "stats", "a1b2"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let text = stdout(&out);
+    assert!(text.contains("Duration:   1h 0m"));
+    assert!(text.contains("strategist -> builder"));
+    assert!(text.contains("20m 0s blocked"));
+}
+
+#[test]
+fn test_run_stats_rejects_filters_with_an_id() {
+    let core = MockCore::start();
+    let out = core.run(&["run", "stats", "a1b2", "--status", "active"]);
+    assert_eq!(exit_code(&out), 2);
+    assert!(core.requests().is_empty());
+}
+
+#[test]
+fn test_run_stats_aggregates_filtered_runs() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+    core.on("get_conversation_events", run_history_reply());
+    core.on("artifact_list", Reply::Ok(json!({"artifacts": []})));
+    let out = core.run(&[
+        "--output",
+        "json",
+        "run",
+        "stats",
+        "--playbook",
+        "Plan > Build",
+        "--status",
+        "blocked,completed",
+    ]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let stats: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert!(stats.get("runId").is_none());
+    assert_eq!(stats["runs"], 1);
+    assert_eq!(stats["handoffs"][0]["count"], 1);
+
+    let out = core.run(&["--output", "json", "run", "stats"]);
+    let stats: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(stats["runs"], 3);
+    assert_eq!(stats["completions"]["builder"], 3);
+    assert_eq!(stats["impediments"][0]["count"], 3);
+}
+
+#[test]
+fn test_run_diff_compares_runs_and_artifacts() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+    core.on("get_conversation_events", run_history_reply());
+    core.on(
+        "artifact_list",
+        Reply::Ok(json!({"artifacts": [{"path": "docs/PLAN.md", "status": "ok"}]})),
+    );
+    core.on(
+        "artifact_list_versions",
+        Reply::Ok(json!({"versions": [{"version": 1}, {"version": 3}]})),
+    );
+    core.on(
+        "artifact_get_version",
+        Reply::Ok(json!({"content": "# Plan\n"})),
+    );
+
+    let out = core.run(&["--output", "json", "run", "diff", "1111", "2222"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let diff: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(diff["a"]["runId"], "11111111");
+    assert_eq!(diff["a"]["playbook"], "Solo Build");
+    assert_eq!(diff["b"]["playbook"], "Plan > Build");
+    assert_eq!(diff["a"]["phases"], json!(["planning", "executing"]));
+    assert_eq!(diff["a"]["handoffs"], 1);
+    assert_eq!(diff["a"]["impediments"], 1);
+    assert_eq!(diff["artifacts"][0]["path"], "docs/PLAN.md");
+    assert_eq!(diff["artifacts"][0]["change"], "same");
+    assert_eq!(diff["artifacts"][0]["aVersion"], 3);
+    let fetched: Vec<String> = core
+        .params_for("artifact_get_version")
+        .iter()
+        .map(|p| format!("{} v{}", p["runId"].as_str().unwrap(), p["version"]))
+        .collect();
+    assert_eq!(fetched, ["11111111 v3", "22222222 v3"]);
+
+    let out = core.run(&["run", "diff", "1111", "2222"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let text = stdout(&out);
+    assert!(text.contains("Solo Build"));
+    assert!(text.contains("planning > executing"));
+    assert!(text.contains("docs/PLAN.md"));
+}
+
+#[test]
+fn test_run_diff_unknown_run_exits_not_found() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+    let out = core.run(&["run", "diff", "1111", "ffff"]);
+    assert_eq!(exit_code(&out), 5);
+}
+
+#[test]
+fn test_run_show_unknown_prefix_exits_not_found() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    let out = core.run(&["run", "show", "ffff"]);
+    assert_eq!(exit_code(&out), 5);
+}
+
+#[test]
+fn test_run_show_ambiguous_prefix() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([
+            {"id": "abc111", "status": "active"},
+            {"id": "abc222", "status": "active"},
+        ])),
+    );
+    let out = core.run(&["run", "show", "abc"]);
+    assert_eq!(exit_code(&out), 6);
+}
+
+#[test]
+fn test_run_remove() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "completed"}])),
+    );
+    core.on("delete_run", Reply::Ok(json!(null)));
+    let out = core.run(&["run", "rm", "a1b2", "--yes"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(core.only_params("delete_run"), json!({"runId": RUN_ID}));
+}
+
+#[test]
+fn test_run_remove_needs_confirmation() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "completed"}])),
+    );
+    core.on("delete_run", Reply::Ok(json!(null)));
+    let out = core.run(&["run", "rm", "a1b2"]);
+    assert_eq!(exit_code(&out), 2);
+    assert!(stderr(&out).contains("About to remove 1 run"));
+    assert!(stderr(&out).contains("--yes"));
+    assert!(core.params_for("delete_run").is_empty());
+}
+
+#[test]
+fn test_run_remove_several() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+    core.on("delete_run", Reply::Ok(json!(null)));
+    let out = core.run(&[
+        "--output", "json", "run", "rm", "1111", "3333", "11111111", "-y",
+    ]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let parsed: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(parsed["removed"], json!(["11111111", "33333333"]));
+    assert_eq!(core.params_for("delete_run").len(), 2);
+}
+
+#[test]
+fn test_run_remove_bad_prefix_removes_nothing() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+    core.on("delete_run", Reply::Ok(json!(null)));
+    let out = core.run(&["run", "rm", "1111", "9999", "--yes"]);
+    assert_eq!(exit_code(&out), 5);
+    assert!(stderr(&out).contains("no run matching '9999'"));
+    assert!(core.params_for("delete_run").is_empty());
+}
+
+#[test]
+fn test_run_remove_full_id_missing_from_list() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({"runId": RUN_ID, "status": "completed"})),
+    );
+    core.on("delete_run", Reply::Ok(json!(null)));
+    let out = core.run(&["--output", "json", "run", "rm", RUN_ID, "--yes"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(core.only_params("delete_run")["runId"], RUN_ID);
+}
+
+#[test]
+fn test_run_remove_dashless_full_id_uses_listed_id() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "completed"}])),
+    );
+    core.on("delete_run", Reply::Ok(json!(null)));
+    let dashless = RUN_ID.replace('-', "");
+    let out = core.run(&["--output", "json", "run", "rm", &dashless, "--yes"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(core.only_params("delete_run")["runId"], RUN_ID);
+    assert!(core.params_for("get_run_status").is_empty());
+}
+
+fn artifacts_reply() -> Reply {
+    Reply::Ok(json!({"artifacts": [
+        {"path": "PLAN.md", "status": "ok", "commentCount": 3, "versionCount": 4},
+        {"path": "NOTES.md", "status": "ok", "commentCount": 1, "versionCount": 1},
+    ]}))
+}
+
+#[test]
+fn test_run_prune_dry_run_counts_losses() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+    core.on("artifact_list", artifacts_reply());
+    core.on("delete_run", Reply::Ok(json!(null)));
+    let out = core.run(&["run", "prune", "--older-than", "14d", "--dry-run"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let text = stdout(&out);
+    // Only the completed run: active and blocked runs are never pruned by default
+    assert!(text.contains("Would remove 1 run (2 artifacts with 5 versions, 4 comments)"));
+    assert!(text.contains("11111111"));
+    assert!(!text.contains("33333333"));
+    assert!(core.params_for("delete_run").is_empty());
+}
+
+#[test]
+fn test_run_prune_removes_matching_runs() {
+    let core = MockCore::start();
+    core.on("list_runs", many_runs_reply());
+    core.on("artifact_list", Reply::fail("no artifacts"));
+    core.on("delete_run", Reply::Ok(json!(null)));
+    let out = core.run(&["run", "prune", "--status", "completed,blocked", "--yes"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let deleted: Vec<serde_json::Value> = core
+        .params_for("delete_run")
+        .into_iter()
+        .map(|p| p["runId"].clone())
+        .collect();
+    assert_eq!(deleted, [json!("33333333"), json!("11111111")]);
+}
+
+#[test]
+fn test_run_remove_conflict_code() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    core.on(
+        "delete_run",
+        Reply::fail_with_code("run is still active", "invalid_state"),
+    );
+    let out = core.run(&["run", "rm", "a1b2", "--yes"]);
+    assert_eq!(exit_code(&out), 9);
+    assert!(stderr(&out).contains("run is still active"));
+}
+
+#[test]
+fn test_run_pause_with_reason() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    core.on("update_run_state", Reply::Ok(json!(null)));
+    let out = core.run(&["run", "pause", "a1b2", "--reason", "waiting on review"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Paused run a1b2c3d4"));
+    assert_eq!(
+        core.only_params("update_run_state"),
+        json!({"runId": RUN_ID, "action": "pause", "reason": "waiting on review"})
+    );
+}
+
+#[test]
+fn test_run_cancel_json_output() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    core.on("update_run_state", Reply::Ok(json!(null)));
+    let out = core.run(&["--output", "json", "run", "cancel", "a1b2"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let parsed: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(parsed["status"], "cancelled");
+    assert_eq!(
+        core.only_params("update_run_state"),
+        json!({"runId": RUN_ID, "action": "cancel"})
+    );
+}
+
+#[test]
+fn test_run_resume_invalid_state() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    core.on(
+        "update_run_state",
+        Reply::fail_with_code("run is not paused", "invalid_state"),
+    );
+    let out = core.run(&["run", "resume", "a1b2"]);
+    assert_eq!(exit_code(&out), 9);
+    assert!(stderr(&out).contains("run is not paused"));
+}
+
+#[test]
+fn test_run_archive_unsupported_by_old_core() {
+    let core = MockCore::start();
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "completed"}])),
+    );
+    core.on(
+        "hello",
+        Reply::Ok(json!({"protocolVersion": 1, "methods": []})),
+    );
+    let out = core.run(&["run", "archive", "a1b2"]);
+    assert_eq!(exit_code(&out), 11);
+    assert!(core.params_for("update_run_state").is_empty());
+}
+
+// ============================================================================
+// SESSION
+// ============================================================================
+
+fn sessions_reply() -> Reply {
+    Reply::Ok(json!({"sessions": [{
+        "sessionName": "hw-builder",
+        "projectDir": "/tmp/project",
+        "isWorktree": true,
+        "attachedRunId": RUN_ID,
+        "roleId": "builder",
+    }]}))
+}
+
+#[test]
+fn test_session_list() {
+    let core = MockCore::start();
+    core.on("list_active_sessions", sessions_reply());
+    let out = core.run(&["session", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("hw-builder"));
+    assert!(stdout(&out).contains("/tmp/project"));
+}
+
+#[test]
+fn test_session_list_ndjson_output() {
+    let core = MockCore::start();
+    core.on("list_active_sessions", sessions_reply());
+    let out = core.run(&["--output", "ndjson", "session", "list"]);
+    let lines: Vec<String> = stdout(&out).lines().map(String::from).collect();
+    assert_eq!(lines.len(), 1);
+    let parsed: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(parsed["sessionName"], "hw-builder");
+}
+
+#[test]
+fn test_session_list_filters_by_attached_run() {
+    let core = MockCore::start();
+    core.on("list_active_sessions", sessions_reply());
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active", "phase": "executing"}])),
+    );
+
+    let out = core.run(&[
+        "--output",
+        "json",
+        "session",
+        "list",
+        "--phase",
+        "executing",
+    ]);
+    assert_eq!(listed_sessions(&out), ["hw-builder"]);
+
+    let out = core.run(&["--output", "json", "session", "list", "--phase", "planning"]);
+    assert!(listed_sessions(&out).is_empty());
+}
+
+#[test]
+fn test_session_list_without_run_filters_skips_list_runs() {
+    let core = MockCore::start();
+    core.on("list_active_sessions", sessions_reply());
+    let out = core.run(&["--output", "json", "session", "list", "--project", "/tmp"]);
+    assert_eq!(listed_sessions(&out), ["hw-builder"]);
+    assert!(core.params_for("list_runs").is_empty());
+
+    let out = core.run(&[
+        "--output",
+        "json",
+        "session",
+        "list",
+        "--project",
+        "/elsewhere",
+    ]);
+    assert!(listed_sessions(&out).is_empty());
+}
+
+fn listed_sessions(out: &std::process::Output) -> Vec<String> {
+    let parsed: serde_json::Value = serde_json::from_str(&stdout(out)).unwrap();
+    parsed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["sessionName"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_session_show() {
+    let core = MockCore::start();
+    core.on("list_active_sessions", sessions_reply());
+    let out = core.run(&["session", "show", "hw-builder"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Worktree: yes"));
+    assert!(stdout(&out).contains("builder"));
+}
+
+#[test]
+fn test_session_show_missing() {
+    let core = MockCore::start();
+    core.on("list_active_sessions", sessions_reply());
+    let out = core.run(&["session", "show", "nope"]);
+    assert!(!out.status.success());
+    assert!(stderr(&out).contains("no session 'nope'"));
+}
+
+#[test]
+fn test_session_remove() {
+    let core = MockCore::start();
+    core.on("deregister_session", Reply::Ok(json!(null)));
+    let out = core.run(&["session", "rm", "hw-builder"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(
+        core.only_params("deregister_session"),
+        json!({"sessionName": "hw-builder"})
+    );
+}
+
+#[test]
+fn test_session_register_and_deregister() {
+    let core = MockCore::start();
+    core.on("register_session", Reply::Ok(json!(null)));
+    core.on("deregister_session", Reply::Ok(json!(null)));
+
+    let out = core.run(&[
+        "session",
+        "register",
+        "--session",
+        "hw-builder",
+        "-p",
+        "/tmp/project",
+    ]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(
+        core.only_params("register_session"),
+        json!({"sessionName": "hw-builder", "projectDir": "/tmp/project"})
+    );
+
+    let out = core.run(&["session", "deregister", "--session", "hw-builder"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+}
+
+#[test]
+fn test_session_register_legacy_short_flag_points_to_session() {
+    let core = MockCore::start();
+    // `-s` used to name the session; it is now the global --socket-path
+    let out = core
+        .cli_without_socket()
+        .args(["session", "register", "-s", "hw-builder", "-p", "/tmp"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 2);
+    assert!(
+        stderr(&out).contains("--session hw-builder"),
+        "{}",
+        stderr(&out)
+    );
+    assert!(core.requests().is_empty());
+}
+
+#[test]
+fn test_session_register_failure() {
+    let core = MockCore::start();
+    core.on(
+        "register_session",
+        Reply::fail_with_code("already registered", "conflict"),
+    );
+    let out = core.run(&["session", "register", "--session", "x", "-p", "/tmp"]);
+    assert_eq!(exit_code(&out), 8);
+}
+
+// ============================================================================
+// AUTH
+// ============================================================================
+
+#[test]
+fn test_auth_status_connected() {
+    let core = MockCore::start();
+    let out = core.run(&["auth", "status"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("9.9.9"));
+    assert!(stdout(&out).contains("not configured"));
+}
+
+#[test]
+fn test_auth_status_json() {
+    let core = MockCore::start();
+    let out = core.run(&["--output", "json", "auth", "status"]);
+    let parsed: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(parsed["backendVersion"], "9.9.9");
+}
+
+#[test]
+fn test_auth_token_set_writes_private_file() {
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    let core = MockCore::start();
+    let mut child = core
+        .cli()
+        .args(["auth", "token", "set"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"  fresh-token\n")
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let path = core.home().join(".hotwired/auth_token");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "fresh-token\n");
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode, 0o600);
+
+    // Later requests carry the stored token
+    core.on("list_runs", Reply::Ok(json!([])));
+    core.run(&["run", "list"]);
+    assert_eq!(core.requests().last().unwrap()["token"], "fresh-token");
+}
+
+#[test]
+fn test_auth_token_set_rejects_empty_stdin() {
+    let core = MockCore::start();
+    let out = core
+        .cli()
+        .args(["auth", "token", "set"])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 10);
+    assert!(!core.home().join(".hotwired/auth_token").exists());
+}
+
+#[test]
+fn test_auth_logout() {
+    let core = MockCore::start();
+    write_token(&core, 0o600);
+    let out = core.run(&["auth", "logout"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(!core.home().join(".hotwired/auth_token").exists());
+
+    let out = core.run(&["auth", "logout"]);
+    assert!(out.status.success());
+    assert!(stdout(&out).contains("No token"));
+}
+
+#[test]
+fn test_token_env_overrides_file() {
+    let core = MockCore::start();
+    write_token(&core, 0o600);
+    core.on("list_runs", Reply::Ok(json!([])));
+    let out = core
+        .cli()
+        .env("HOTWIRED_TOKEN", "from-env")
+        .args(["run", "list"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(core.requests().last().unwrap()["token"], "from-env");
+}
+
+#[test]
+fn test_auth_status_warns_on_exposed_token() {
+    let core = MockCore::start();
+    write_token(&core, 0o644);
+    let out = core.run(&["--output", "json", "auth", "status"]);
+    assert!(out.status.success());
+    assert!(stderr(&out).contains("chmod 600"));
+    let parsed: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(parsed["authToken"], "configured");
+    assert_eq!(parsed["tokenExposed"], true);
+}
+
+#[test]
+fn test_auth_verify() {
+    let core = MockCore::start();
+    write_token(&core, 0o600);
+    core.on("list_runs", Reply::Ok(json!([])));
+    let out = core.run(&["auth", "verify"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Token accepted"));
+    assert_eq!(core.requests().last().unwrap()["token"], "secret");
+
+    core.on(
+        "list_runs",
+        Reply::fail_with_code("invalid token", "unauthorized"),
+    );
+    let out = core.run(&["auth", "verify"]);
+    assert_eq!(exit_code(&out), 7);
+    assert!(stdout(&out).contains("Token rejected"));
+    assert!(stdout(&out).contains("invalid token"));
+}
+
+#[test]
+fn test_auth_verify_without_token() {
+    let core = MockCore::start();
+    // A core that doesn't enforce auth accepts anything
+    core.on("list_runs", Reply::Ok(json!([])));
+    let out = core.run(&["auth", "verify"]);
+    assert_eq!(exit_code(&out), 7);
+    assert!(stdout(&out).contains("No token configured"));
+    assert!(!stdout(&out).contains("accepted"));
+    assert!(core.requests().is_empty());
+}
+
+#[test]
+fn test_token_withheld_from_untrusted_socket() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let core = MockCore::start();
+    write_token(&core, 0o600);
+    core.on("list_runs", Reply::Ok(json!([])));
+    // Anyone could have created a socket in a world-writable directory
+    std::fs::set_permissions(core.home(), std::fs::Permissions::from_mode(0o777)).unwrap();
+
+    let out = core.run(&["run", "list"]);
+    assert_eq!(exit_code(&out), 7);
+    assert!(stderr(&out).contains("refusing to send auth token"));
+    assert!(stderr(&out).contains("--allow-untrusted-socket"));
+    assert!(core.requests().is_empty());
+
+    let out = core.run(&["--allow-untrusted-socket", "run", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let out = core
+        .cli()
+        .env("HOTWIRED_ALLOW_UNTRUSTED_SOCKET", "1")
+        .args(["run", "list"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(core.params_for("list_runs").len(), 2);
+}
+
+#[test]
+fn test_untrusted_socket_without_token_still_works() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let core = MockCore::start();
+    core.on("list_runs", Reply::Ok(json!([])));
+    std::fs::set_permissions(core.home(), std::fs::Permissions::from_mode(0o777)).unwrap();
+    let out = core.run(&["run", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+}
+
+// ============================================================================
+// WORKFLOW COMMANDS
+// ============================================================================
+
+#[test]
+fn test_workflow_command_outside_any_multiplexer() {
+    let core = attached();
+    let out = core
+        .cli()
+        .env_remove("ZELLIJ_SESSION_NAME")
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 20);
+}
+
+#[test]
+fn test_pair_from_tmux_pane() {
+    let core = MockCore::start();
+    core.on("pair", Reply::Ok(json!({"role": "builder"})));
+    let out = core
+        .cli()
+        .env_remove("ZELLIJ_SESSION_NAME")
+        .env("TMUX", "/tmp/tmux-501/default,4242,0")
+        .env("TMUX_PANE", "%3")
+        .args(["pair", RUN_ID])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(core.only_params("pair")["zellijSession"], "tmux:default:%3");
+}
+
+#[test]
+fn test_status_from_wezterm_pane() {
+    let core = MockCore::start();
+    core.attach("builder", RUN_ID);
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({"runId": RUN_ID, "status": "active"})),
+    );
+    let out = core
+        .cli()
+        .env_remove("ZELLIJ_SESSION_NAME")
+        .env("WEZTERM_PANE", "12")
+        .arg("status")
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("wezterm:12 (auto-detected from WezTerm)"));
+    assert_eq!(
+        core.only_params("get_session_state")["zellijSession"],
+        "wezterm:12"
+    );
+}
+
+#[test]
+fn test_pair_outside_any_multiplexer() {
+    let core = MockCore::start();
+    let out = core
+        .cli()
+        .env_remove("ZELLIJ_SESSION_NAME")
+        .args(["pair", RUN_ID])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 20);
+    assert!(stderr(&out).contains("tmux"));
+    assert!(core.params_for("pair").is_empty());
+}
+
+#[test]
+fn test_session_flag_claims_identity_without_multiplexer() {
+    let core = MockCore::start();
+    core.attach("builder", RUN_ID);
+    core.on("handoff", Reply::Ok(json!(null)));
+    let out = core
+        .cli()
+        .env_remove("ZELLIJ_SESSION_NAME")
+        .args(["--session", "ci-builder", "send", "--to", "human", "done"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(
+        core.only_params("get_session_state")["zellijSession"],
+        "ci-builder"
+    );
+}
+
+#[test]
+fn test_session_flag_beats_env_and_multiplexer() {
+    let core = MockCore::start();
+    core.on("pair", Reply::Ok(json!({"role": "builder"})));
+    let out = core
+        .cli()
+        .env("HOTWIRED_SESSION", "from-env")
+        .args(["pair", RUN_ID, "--session", "from-flag"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(core.only_params("pair")["zellijSession"], "from-flag");
+}
+
+#[test]
+fn test_session_env_for_hooks_and_hotwire() {
+    let core = MockCore::start();
+    core.on("register_session", Reply::Ok(json!(null)));
+    core.on("hook_event", Reply::Ok(json!(null)));
+    core.on(
+        "hotwire",
+        Reply::Ok(json!({"status": "started", "runId": RUN_ID})),
+    );
+
+    for args in [&["internal", "session-start"][..], &["hotwire"][..]] {
+        let out = core
+            .cli()
+            .env_remove("ZELLIJ_SESSION_NAME")
+            .env("HOTWIRED_SESSION", "container-1")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{}", stderr(&out));
+    }
+
+    assert_eq!(
+        core.only_params("register_session")["sessionName"],
+        "container-1"
+    );
+    assert_eq!(core.only_params("hotwire")["zellijSession"], "container-1");
+}
+
+#[test]
+fn test_workflow_command_unregistered_session() {
+    let core = MockCore::start();
+    core.on(
+        "get_session_state",
+        Reply::fail_with_code("unknown session", "not_found"),
+    );
+    let out = core.run(&["status"]);
+    assert_eq!(exit_code(&out), 21);
+}
+
+#[test]
+fn test_workflow_command_session_state_shapes() {
+    let core = MockCore::start();
+    core.on("get_session_state", Reply::Ok(json!(null)));
+    let out = core.run(&["status"]);
+    assert_eq!(exit_code(&out), 21);
+
+    // Cores that predate error codes fail unknown sessions with no code
+    core.on("get_session_state", Reply::fail("unknown session"));
+    let out = core.run(&["status"]);
+    assert_eq!(exit_code(&out), 21);
+
+    // A reply that doesn't decode is a protocol error, not "not registered"
+    core.on("get_session_state", Reply::Ok(json!({"attachedRunId": 42})));
+    let out = core.run(&["status"]);
+    assert_eq!(exit_code(&out), 12);
+}
+
+#[test]
+fn test_workflow_command_not_attached() {
+    let core = MockCore::start();
+    core.on("get_session_state", Reply::Ok(json!({})));
+    let out = core.run(&["inbox"]);
+    assert_eq!(exit_code(&out), 22);
+}
+
+#[test]
+fn test_workflow_command_run_finished() {
+    let core = MockCore::start();
+    core.on(
+        "get_session_state",
+        Reply::Ok(json!({"attachedRunId": RUN_ID, "runStatus": "completed", "roleId": "builder"})),
+    );
+    let out = core.run(&["complete"]);
+    assert_eq!(exit_code(&out), 23);
+}
+
+#[test]
+fn test_hotwire() {
+    let core = MockCore::start();
+    core.on(
+        "hotwire",
+        Reply::Ok(json!({
+            "status": "started",
+            "runId": RUN_ID,
+            "role": "strategist",
+            "protocol": "# Protocol\nDo the thing",
+        })),
+    );
+    let out = core.run(&["hotwire", "--playbook", "plan-build", "--intent", "Ship it"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Do the thing"));
+
+    let params = core.only_params("hotwire");
+    assert_eq!(params["zellijSession"], "test-session");
+    assert_eq!(params["suggestedPlaybook"], "plan-build");
+    assert_eq!(params["intent"], "Ship it");
+}
+
+#[test]
+fn test_hotwire_failure() {
+    let core = MockCore::start();
+    core.on("hotwire", Reply::fail("no such playbook"));
+    let out = core.run(&["hotwire", "--playbook", "nope"]);
+    assert!(!out.status.success());
+    assert!(stderr(&out).contains("no such playbook"));
+}
+
+#[test]
+fn test_pair() {
+    let core = MockCore::start();
+    core.on(
+        "pair",
+        Reply::Ok(json!({"role": "builder", "protocol": "Build things"})),
+    );
+    let out = core.run(&["pair", RUN_ID, "--role", "builder"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Build things"));
+
+    let params = core.only_params("pair");
+    assert_eq!(params["runId"], RUN_ID);
+    assert_eq!(params["roleId"], "builder");
+    assert_eq!(params["zellijSession"], "test-session");
+}
+
+#[test]
+fn test_pair_malformed_response() {
+    let core = MockCore::start();
+    core.on("pair", Reply::Ok(json!({"protocol": 42})));
+    let out = core.run(&["pair", RUN_ID]);
+    assert_eq!(exit_code(&out), 12);
+}
+
+#[test]
+fn test_send() {
+    let core = attached();
+    core.on("handoff", Reply::Ok(json!(null)));
+    let out = core.run(&["send", "--to", "human", "Need", "a", "decision"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let params = core.only_params("handoff");
+    assert_eq!(params["runId"], RUN_ID);
+    assert_eq!(params["to"], "human");
+    assert_eq!(params["source"], "builder");
+    assert_eq!(params["details"], "Need a decision");
+}
+
+#[test]
+fn test_send_failure() {
+    let core = attached();
+    core.on(
+        "handoff",
+        Reply::fail_with_code("role may not message human", "permission_denied"),
+    );
+    let out = core.run(&["send", "--to", "human", "hi"]);
+    assert_eq!(exit_code(&out), 7);
+}
+
+#[test]
+fn test_inbox() {
+    let core = attached();
+    core.on(
+        "get_conversation_events",
+        Reply::Ok(json!({"events": [
+            {"sequenceNum": 7, "source": "strategist", "eventType": "handoff", "content": "Start on task 1"},
+        ]})),
+    );
+    let out = core.run(&["inbox", "--since", "5"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Start on task 1"));
+
+    let params = core.only_params("get_conversation_events");
+    assert_eq!(params["runId"], RUN_ID);
+    assert_eq!(params["sinceSequence"], 5);
+}
+
+#[test]
+fn test_inbox_empty() {
+    let core = attached();
+    core.on("get_conversation_events", Reply::Ok(json!({"events": []})));
+    let out = core.run(&["inbox"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("No new messages."));
+}
+
+#[test]
+fn test_inbox_watch_streams_pushed_events() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    let core = attached();
+    core.on(
+        "subscribe_conversation_events",
+        Reply::Stream(vec![
+            json!({"sequenceNum": 1, "source": "strategist", "content": "first"}),
+            json!({"sequenceNum": 2, "source": "strategist", "content": "second"}),
+        ]),
+    );
+    // After the stream closes the CLI resubscribes; don't replay anything
+    let mut child = core
+        .cli()
+        .args(["--output", "ndjson", "inbox", "--watch"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let first: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    let second: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(first["content"], "first");
+    assert_eq!(second["content"], "second");
+}
+
+#[test]
+fn test_complete() {
+    let core = attached();
+    core.on(
+        "task_complete",
+        Reply::Ok(json!({"nextAction": "Wait for review"})),
+    );
+    let out = core.run(&["complete", "--outcome", "All tests pass"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Wait for review"));
+
+    let params = core.only_params("task_complete");
+    assert_eq!(params["outcome"], "All tests pass");
+    assert_eq!(params["source"], "builder");
+}
+
+#[test]
+fn test_complete_disconnect() {
+    let core = attached();
+    core.on("task_complete", Reply::Disconnect);
+    let out = core.run(&["complete"]);
+    assert!(!out.status.success());
+}
+
+#[test]
+fn test_impediment() {
+    let core = attached();
+    core.on("report_impediment", Reply::Ok(json!(null)));
+    let out = core.run(&[
+        "impediment",
+        "No DB access",
+        "--type",
+        "access",
+        "--suggestion",
+        "Grant creds",
+    ]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let params = core.only_params("report_impediment");
+    assert_eq!(params["impedimentType"], "access");
+    assert_eq!(params["description"], "No DB access");
+    assert_eq!(params["suggestion"], "Grant creds");
+}
+
+#[test]
+fn test_resolve() {
+    let core = attached();
+    core.on(
+        "resolve_run_impediments",
+        Reply::Ok(json!({"message": "Resolved 1 impediment"})),
+    );
+    let out = core.run(&["resolve", "Creds granted"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(
+        core.only_params("resolve_run_impediments")["message"],
+        "Creds granted"
+    );
+}
+
+#[test]
+fn test_resolve_not_permitted() {
+    let core = attached();
+    core.on(
+        "resolve_run_impediments",
+        Reply::fail_with_code("not your impediment", "permission_denied"),
+    );
+    let out = core.run(&["resolve", "done"]);
+    assert_eq!(exit_code(&out), 7);
+}
+
+#[test]
+fn test_status() {
+    let core = attached();
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({
+            "runId": RUN_ID,
+            "status": "active",
+            "phase": "executing",
+            "templateName": "Plan > Build",
+            "connectedAgents": [{"roleId": "builder"}, {"roleId": "strategist"}],
+            "impediments": [{"source": "builder", "description": "Blocked on API keys"}],
+        })),
+    );
+    let out = core.run(&["status"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let text = stdout(&out);
+    assert!(text.contains("executing"));
+    assert!(text.contains("strategist"));
+    assert!(text.contains("Blocked on API keys"));
+}
+
+#[test]
+fn test_status_shows_paused_run() {
+    let core = attached();
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({
+            "runId": RUN_ID,
+            "status": "paused",
+            "statusReason": "waiting on review",
+        })),
+    );
+    let out = core.run(&["status"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let text = stdout(&out);
+    assert!(text.contains("paused (waiting on review)"));
+    assert!(text.contains("RUN PAUSED"));
+}
+
+#[test]
+fn test_status_malformed() {
+    let core = attached();
+    core.on("get_run_status", Reply::Raw("garbage".into()));
+    let out = core.run(&["status"]);
+    assert_eq!(exit_code(&out), 12);
+}
+
+#[test]
+fn test_protocol() {
+    let core = attached();
+    core.on(
+        "get_protocol",
+        Reply::Ok(json!({
+            "runId": RUN_ID,
+            "playbookProtocol": "Playbook rules",
+            "roleProtocol": "Builder rules",
+        })),
+    );
+    let out = core.run(&["protocol"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Builder rules"));
+    assert_eq!(
+        core.only_params("get_protocol"),
+        json!({"runId": RUN_ID, "role": "builder"})
+    );
+}
+
+// ============================================================================
+// INTERNAL (HOOKS)
+// ============================================================================
+
+#[test]
+fn test_internal_hook_event() {
+    let core = MockCore::start();
+    core.on("hook_event", Reply::Ok(json!(null)));
+    let out = core.run(&["internal", "hook-event", "stop"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let params = core.only_params("hook_event");
+    assert_eq!(params["eventName"], "stop");
+    assert_eq!(params["zellijSession"], "test-session");
+}
+
+#[test]
+fn test_internal_hook_event_never_fails_the_hook() {
+    let core = MockCore::start();
+    core.on("hook_event", Reply::Disconnect);
+    let out = core.run(&["internal", "hook-event", "stop"]);
+    assert!(out.status.success());
+}
+
+#[test]
+fn test_internal_session_start_and_end() {
+    let core = MockCore::start();
+    core.on("register_session", Reply::Ok(json!(null)));
+    core.on("deregister_session", Reply::Ok(json!(null)));
+
+    let out = core
+        .cli()
+        .env("CLAUDE_PROJECT_DIR", "/tmp/project")
+        .args(["internal", "session-start"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(
+        core.only_params("register_session"),
+        json!({"sessionName": "test-session", "projectDir": "/tmp/project"})
+    );
+
+    let out = core.run(&["internal", "session-end"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(
+        core.only_params("deregister_session"),
+        json!({"sessionName": "test-session"})
+    );
+}
+
+#[test]
+fn test_internal_hooks_survive_broken_config() {
+    let core = MockCore::start();
+    core.on("register_session", Reply::Ok(json!(null)));
+    core.on("deregister_session", Reply::Ok(json!(null)));
+
+    // The dev context reads its token from DEV_TOKEN, which isn't set
+    write_config(&core, "dev");
+    let out = core.run(&["internal", "session-start"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(core.params_for("register_session").len(), 1);
+
+    std::fs::write(
+        core.home().join(".hotwired/config.toml"),
+        "current-context = [",
+    )
+    .unwrap();
+    let out = core.run(&["internal", "session-end"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stderr(&out).contains("using the default context"));
+    assert_eq!(core.params_for("deregister_session").len(), 1);
+
+    // Regular commands still refuse a broken config
+    let out = core.run(&["run", "list"]);
+    assert!(!out.status.success());
+}
+
+// ============================================================================
+// ARTIFACTS
+// ============================================================================
+
+fn write_doc(core: &MockCore, rel: &str) {
+    let path = core.home().join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, "# PRD\n\nauth flow\n").unwrap();
+}
+
+#[test]
+fn test_artifact_list() {
+    let core = attached();
+    core.on(
+        "artifact_list",
+        Reply::Ok(json!({"artifacts": [{
+            "path": "docs/PRD.md",
+            "status": "ok",
+            "commentCount": 3,
+            "versionCount": 5,
+            "title": "Product Requirements",
+        }]})),
+    );
+    let out = core.run(&["artifact", "ls"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("docs/PRD.md"));
+    assert!(stdout(&out).contains("Product Requirements"));
+}
+
+#[test]
+fn test_artifact_sync() {
+    let core = attached();
+    write_doc(&core, "docs/PRD.md");
+    core.on(
+        "artifact_sync",
+        Reply::Ok(json!({"status": "created", "title": "PRD", "version": 1})),
+    );
+    let out = core.run(&["artifact", "sync", "docs/PRD.md"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(
+        core.only_params("artifact_sync"),
+        json!({"runId": RUN_ID, "path": "docs/PRD.md"})
+    );
+}
+
+#[test]
+fn test_artifact_sync_missing_file_never_calls_core() {
+    let core = attached();
+    let out = core.run(&["artifact", "sync", "docs/missing.md"]);
+    assert!(!out.status.success());
+    assert!(core.params_for("artifact_sync").is_empty());
+}
+
+#[test]
+fn test_artifact_move_refs_only() {
+    let core = attached();
+    write_doc(&core, "docs/new.md");
+    core.on(
+        "artifact_move",
+        Reply::Ok(json!({"commentsPreserved": 2, "fileMoved": false})),
+    );
+    let out = core.run(&[
+        "artifact",
+        "mv",
+        "docs/old.md",
+        "docs/new.md",
+        "--refs-only",
+    ]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let params = core.only_params("artifact_move");
+    assert_eq!(params["oldPath"], "docs/old.md");
+    assert_eq!(params["newPath"], "docs/new.md");
+    assert_eq!(params["refsOnly"], true);
+}
+
+#[test]
+fn test_artifact_move_unsynced_hint() {
+    let core = attached();
+    write_doc(&core, "docs/new.md");
+    core.on(
+        "artifact_move",
+        Reply::fail_with_code("artifact not registered", "not_found"),
+    );
+    let out = core.run(&[
+        "artifact",
+        "mv",
+        "docs/old.md",
+        "docs/new.md",
+        "--refs-only",
+    ]);
+    assert_eq!(exit_code(&out), 5);
+    assert!(stderr(&out).contains("artifact sync"));
+}
+
+#[test]
+fn test_artifact_comment_add() {
+    let core = attached();
+    core.on(
+        "artifact_add_comment",
+        Reply::Ok(json!({"commentId": "cmt_abc"})),
+    );
+    let out = core.run(&[
+        "artifact",
+        "comment",
+        "add",
+        "docs/PRD.md",
+        "auth flow",
+        "Consider OAuth",
+        "--reply-to",
+        "cmt_parent",
+    ]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("cmt_abc"));
+
+    let params = core.only_params("artifact_add_comment");
+    assert_eq!(params["targetText"], "auth flow");
+    assert_eq!(params["comment"], "Consider OAuth");
+    assert_eq!(params["author"], "builder");
+    assert_eq!(params["parentCommentId"], "cmt_parent");
+}
+
+#[test]
+fn test_artifact_comment_add_anchor_missing() {
+    let core = attached();
+    core.on(
+        "artifact_add_comment",
+        Reply::fail_with_code("target text not found", "invalid_params"),
+    );
+    let out = core.run(&["artifact", "comment", "add", "docs/PRD.md", "zzz", "hm"]);
+    assert_eq!(exit_code(&out), 10);
+}
+
+#[test]
+fn test_artifact_comment_reply() {
+    let core = attached();
+    core.on(
+        "artifact_reply_comment",
+        Reply::Ok(json!({"commentId": "cmt_reply"})),
+    );
+    let out = core.run(&["artifact", "comment", "reply", "cmt_abc", "Agreed"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let params = core.only_params("artifact_reply_comment");
+    assert_eq!(params["commentId"], "cmt_abc");
+    assert_eq!(params["message"], "Agreed");
+}
+
+#[test]
+fn test_artifact_comment_show() {
+    let core = attached();
+    core.on(
+        "artifact_get_comment",
+        Reply::Ok(json!({
+            "comment": {"commentId": "cmt_abc", "comment": "Consider OAuth", "author": "strategist"},
+            "replies": [{"commentId": "cmt_r1", "comment": "Will do", "author": "builder"}],
+        })),
+    );
+    let out = core.run(&["artifact", "comment", "show", "cmt_abc"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Consider OAuth"));
+    assert!(stdout(&out).contains("Will do"));
+}
+
+#[test]
+fn test_artifact_comment_list_and_shorthand() {
+    let core = attached();
+    core.on(
+        "artifact_list_comments",
+        Reply::Ok(json!({"comments": [
+            {"commentId": "cmt_abc", "targetText": "auth flow", "comment": "Consider OAuth", "status": "open"},
+        ]})),
+    );
+    let out = core.run(&["artifact", "comment", "list", "docs/PRD.md"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("cmt_abc"));
+
+    let out = core.run(&["artifact", "comments", "docs/PRD.md", "--status", "all"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let params = core.params_for("artifact_list_comments");
+    assert_eq!(params[0]["statusFilter"], "open");
+    assert_eq!(params[1]["statusFilter"], "all");
+}
+
+#[test]
+fn test_artifact_resolve() {
+    let core = attached();
+    core.on("artifact_resolve_comment", Reply::Ok(json!(null)));
+    let out = core.run(&["artifact", "resolve", "cmt_abc", "--reply", "Fixed"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let params = core.only_params("artifact_resolve_comment");
+    assert_eq!(params["resolvedBy"], "builder");
+    assert_eq!(params["reply"], "Fixed");
+}
+
+#[test]
+fn test_artifact_versions() {
+    let core = attached();
+    core.on(
+        "artifact_list_versions",
+        Reply::Ok(json!({"versions": [
+            {"version": 2, "timestamp": "2024-01-15T13:15:00Z", "linesAdded": 120, "linesRemoved": 5},
+            {"version": 1, "timestamp": "2024-01-15T10:00:00Z"},
+        ]})),
+    );
+    let out = core.run(&["artifact", "versions", "docs/PRD.md"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("+120 -5"));
+}
+
+#[test]
+fn test_artifact_version() {
+    let core = attached();
+    core.on(
+        "artifact_get_version",
+        Reply::Ok(json!({"title": "PRD", "content": "# PRD v2 body"})),
+    );
+    let out = core.run(&["artifact", "version", "docs/PRD.md", "2"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("# PRD v2 body"));
+    assert_eq!(core.only_params("artifact_get_version")["version"], 2);
+}
+
+#[test]
+fn test_artifact_version_not_found() {
+    let core = attached();
+    core.on(
+        "artifact_get_version",
+        Reply::fail_with_code("no version 9", "not_found"),
+    );
+    let out = core.run(&["artifact", "version", "docs/PRD.md", "9"]);
+    assert_eq!(exit_code(&out), 5);
+    assert!(stderr(&out).contains("no version 9"));
+}
+
+// ============================================================================
+// DOCTOR
+// ============================================================================
+
+fn install_hooks(core: &MockCore) {
+    let dir = core.home().join(".claude");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("settings.json"),
+        json!({"hooks": {
+            "SessionStart": [{"hooks": [{"type": "command", "command": "hotwired internal session-start"}]}],
+            "SessionEnd": [{"hooks": [{"type": "command", "command": "hotwired internal session-end"}]}],
+        }})
+        .to_string(),
+    )
+    .unwrap();
+}
+
+fn write_token(core: &MockCore, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = core.home().join(".hotwired");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("auth_token");
+    std::fs::write(&path, "secret\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[test]
+fn test_doctor_all_green() {
+    let core = attached();
+    install_hooks(&core);
+    write_token(&core, 0o600);
+    core.on(
+        "get_run_status",
+        Reply::Ok(json!({"runId": RUN_ID, "status": "active"})),
+    );
+    let out = core.run(&["doctor"]);
+    assert!(out.status.success(), "{}", stdout(&out));
+    assert!(stdout(&out).contains("All checks passed."));
+}
+
+#[test]
+fn test_doctor_reports_fixes() {
+    let core = MockCore::start();
+    write_token(&core, 0o644);
+    core.on(
+        "get_session_state",
+        Reply::fail_with_code("unknown session", "not_found"),
+    );
+    let out = core.run(&["--output", "json", "doctor"]);
+    assert_eq!(exit_code(&out), 1);
+
+    let report: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(report["healthy"], false);
+    let check = |name: &str| {
+        report["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["name"] == name)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(check("backend")["outcome"], "ok");
+    assert_eq!(check("registered")["outcome"], "fail");
+    assert!(check("registered")["fix"]
+        .as_str()
+        .unwrap()
+        .contains("session register"));
+    assert_eq!(check("run")["outcome"], "skip");
+    assert!(check("token")["fix"]
+        .as_str()
+        .unwrap()
+        .contains("chmod 600"));
+    assert!(check("hooks")["fix"]
+        .as_str()
+        .unwrap()
+        .contains("internal session-start"));
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+#[test]
+fn test_api_prints_full_response() {
+    let core = MockCore::start();
+    core.on("list_templates", Reply::Ok(json!([{"id": "plan-build"}])));
+    let out = core.run(&["api", "list_templates", "--params", r#"{"all": true}"#]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let response: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(response["success"], true);
+    assert_eq!(response["data"][0]["id"], "plan-build");
+    assert_eq!(core.only_params("list_templates"), json!({"all": true}));
+}
+
+#[test]
+fn test_api_failure_maps_error_code() {
+    let core = MockCore::start();
+    core.on(
+        "get_run_status",
+        Reply::fail_with_code("no such run", "not_found"),
+    );
+    let out = core.run(&["api", "get_run_status"]);
+    assert_eq!(exit_code(&out), 5);
+    let response: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(response["error"], "no such run");
+    assert_eq!(response["code"], "not_found");
+}
+
+#[test]
+fn test_api_inject_session_and_params_file() {
+    let core = attached();
+    core.on("artifact_list", Reply::Ok(json!([])));
+    std::fs::write(core.home().join("params.json"), r#"{"source": "human"}"#).unwrap();
+    let out = core.run(&[
+        "api",
+        "artifact_list",
+        "--params",
+        "@params.json",
+        "--inject-session",
+    ]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(
+        core.only_params("artifact_list"),
+        json!({"runId": RUN_ID, "source": "human"})
+    );
+}
+
+#[test]
+fn test_api_params_from_stdin() {
+    use std::io::Write;
+
+    let core = MockCore::start();
+    core.on("ping", Reply::Ok(json!({"version": "9.9.9"})));
+    let mut child = core
+        .cli()
+        .args(["--output", "ndjson", "api", "ping", "--params", "-"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(br#"{"echo": 1}"#)
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(stdout(&out).lines().count(), 1);
+    assert_eq!(core.only_params("ping"), json!({"echo": 1}));
+}
+
+#[test]
+fn test_api_rejects_invalid_json() {
+    let core = MockCore::start();
+    let out = core.run(&["api", "ping", "--params", "{oops"]);
+    assert_eq!(exit_code(&out), 2);
+    assert!(stderr(&out).contains("not valid JSON"));
+    assert!(core.requests().is_empty());
+}
+
+// ============================================================================
+// CONTEXTS
+// ============================================================================
+
+/// Write `~/.hotwired/config.toml` with a `dev` context on the mock's socket
+/// and a `stable` context on a socket nobody listens on.
+fn write_config(core: &MockCore, current: &str) {
+    let dir = core.home().join(".hotwired");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("config.toml"),
+        format!(
+            r#"# backends
+current-context = "{}"
+
+[contexts.dev]
+socket = "{}"
+token-env = "DEV_TOKEN"
+project = "~/app"
+
+[contexts.dev.defaults]
+output = "json"
+playbook = "plan-build"
+
+[contexts.stable]
+socket = "~/missing.sock"
+"#,
+            current,
+            core.socket_path().display()
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_doctor_checks_the_active_token_source() {
+    let core = MockCore::start();
+    write_config(&core, "dev");
+    let token_check = |out: &std::process::Output| {
+        let report: serde_json::Value = serde_json::from_str(&stdout(out)).unwrap();
+        report["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["name"] == "token")
+            .unwrap()
+            .clone()
+    };
+
+    let out = core
+        .cli_without_socket()
+        .env("DEV_TOKEN", "dev-secret")
+        .arg("doctor")
+        .output()
+        .unwrap();
+    let check = token_check(&out);
+    assert_eq!(check["outcome"], "ok");
+    assert_eq!(check["detail"], "env $DEV_TOKEN");
+
+    let out = core.cli_without_socket().arg("doctor").output().unwrap();
+    let check = token_check(&out);
+    assert_eq!(check["outcome"], "warn");
+    assert_eq!(check["detail"], "$DEV_TOKEN is not set");
+
+    let out = core
+        .cli_without_socket()
+        .env("HOTWIRED_TOKEN", "from-env")
+        .arg("doctor")
+        .output()
+        .unwrap();
+    assert_eq!(token_check(&out)["detail"], "env $HOTWIRED_TOKEN");
+}
+
+#[test]
+fn test_context_supplies_socket_token_and_defaults() {
+    let core = MockCore::start();
+    write_config(&core, "dev");
+    core.on(
+        "list_runs",
+        Reply::Ok(json!([{"id": RUN_ID, "status": "active"}])),
+    );
+    let out = core
+        .cli_without_socket()
+        .env("DEV_TOKEN", "dev-secret")
+        .args(["run", "list"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    // Default output from the context is json
+    let parsed: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(parsed[0]["id"], RUN_ID);
+    let request = core
+        .requests()
+        .into_iter()
+        .find(|r| r["method"] == "list_runs")
+        .unwrap();
+    assert_eq!(request["token"], "dev-secret");
+}
+
+#[test]
+fn test_context_defaults_apply_to_hotwire() {
+    let core = MockCore::start();
+    write_config(&core, "dev");
+    core.on(
+        "hotwire",
+        Reply::Ok(json!({"status": "started", "runId": RUN_ID, "role": "strategist"})),
+    );
+    let out = core
+        .cli_without_socket()
+        .env("DEV_TOKEN", "dev-secret")
+        .args(["--output", "table", "hotwire", "--intent", "x"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert!(stdout(&out).contains("Run started"));
+
+    let params = core.only_params("hotwire");
+    assert_eq!(params["suggestedPlaybook"], "plan-build");
+    assert_eq!(
+        params["projectPath"],
+        core.home().join("app").to_string_lossy().as_ref()
+    );
+}
+
+#[test]
+fn test_context_flag_and_env_override_current() {
+    let core = MockCore::start();
+    write_config(&core, "dev");
+
+    let out = core
+        .cli_without_socket()
+        .args(["--context", "stable", "run", "list"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 3);
+
+    let out = core
+        .cli_without_socket()
+        .env("HOTWIRED_CONTEXT", "stable")
+        .args(["run", "list"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 3);
+}
+
+#[test]
+fn test_unknown_context_exits_not_found() {
+    let core = MockCore::start();
+    write_config(&core, "dev");
+    let out = core.run(&["--context", "prod", "run", "list"]);
+    assert_eq!(exit_code(&out), 5);
+    assert!(stderr(&out).contains("available: dev, stable"));
+    assert!(core.requests().is_empty());
+}
+
+#[test]
+fn test_context_unset_token_env_fails() {
+    let core = MockCore::start();
+    write_config(&core, "dev");
+    let out = core
+        .cli_without_socket()
+        .args(["run", "list"])
+        .output()
+        .unwrap();
+    assert_eq!(exit_code(&out), 1);
+    assert!(stderr(&out).contains("DEV_TOKEN"));
+}
+
+#[test]
+fn test_context_list_use_show() {
+    let core = MockCore::start();
+    write_config(&core, "dev");
+
+    let out = core.run(&["context", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let text = stdout(&out);
+    assert!(text.contains("* dev"));
+    assert!(text.contains("  stable"));
+
+    let out = core.run(&["context", "use", "stable"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let config = std::fs::read_to_string(core.home().join(".hotwired/config.toml")).unwrap();
+    assert!(config.contains("current-context = \"stable\""));
+    assert!(config.contains("# backends"));
+
+    let out = core.run(&["context", "use", "prod"]);
+    assert_eq!(exit_code(&out), 5);
+
+    let out = core
+        .cli()
+        .env("DEV_TOKEN", "dev-secret")
+        .args(["--output", "json", "context", "show", "dev"])
+        .output()
+        .unwrap();
+    let shown: serde_json::Value = serde_json::from_str(&stdout(&out)).unwrap();
+    assert_eq!(shown["active"], false);
+    assert_eq!(shown["tokenSource"], "env $DEV_TOKEN");
+    assert_eq!(shown["defaults"]["playbook"], "plan-build");
+    assert!(!stdout(&out).contains("dev-secret"));
+}
@@ -0,0 +1,236 @@
+//! In-process stand-in for hotwired-core
+//!
+//! `MockCore` listens on a Unix socket in a temp dir and speaks the same
+//! newline-delimited JSON protocol as `src/ipc.rs`: one request line in, one
+//! response line out. Tests script replies per method and inspect the
+//! requests the CLI actually sent.
+
+#![allow(dead_code)] // each test binary uses a different subset
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// How the mock answers a method
+#[derive(Clone)]
+pub enum Reply {
+    /// `{"success": true, "data": ...}`
+    Ok(Value),
+    /// `{"success": false, "error": ..., "code": ...}`
+    Fail { error: String, code: Option<String> },
+    /// Write this line verbatim (for malformed responses)
+    Raw(String),
+    /// Close the connection without answering
+    Disconnect,
+    /// Never answer; hold the connection open
+    Hang,
+    /// Ack, then push each event as its own line, then close
+    Stream(Vec<Value>),
+}
+
+impl Reply {
+    pub fn fail(error: &str) -> Self {
+        Reply::Fail {
+            error: error.to_string(),
+            code: None,
+        }
+    }
+
+    pub fn fail_with_code(error: &str, code: &str) -> Self {
+        Reply::Fail {
+            error: error.to_string(),
+            code: Some(code.to_string()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    replies: HashMap<String, Reply>,
+    requests: Vec<Value>,
+}
+
+pub struct MockCore {
+    dir: TempDir,
+    socket: PathBuf,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockCore {
+    /// Start listening. The handshake (`hello`) advertises every method
+    /// unless a test overrides it.
+    pub fn start() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("hotwired.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let accept_state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let state = Arc::clone(&accept_state);
+                std::thread::spawn(move || serve(stream, state));
+            }
+        });
+
+        let core = Self { dir, socket, state };
+        core.on("ping", Reply::Ok(json!({"version": "9.9.9"})));
+        core.on(
+            "hello",
+            Reply::Ok(json!({
+                "protocolVersion": 1,
+                "coreVersion": "9.9.9",
+                "methods": ["subscribe_conversation_events", "update_run_state", "import_run"],
+            })),
+        );
+        core
+    }
+
+    /// Answer every future `method` request with `reply`.
+    pub fn on(&self, method: &str, reply: Reply) {
+        self.state
+            .lock()
+            .unwrap()
+            .replies
+            .insert(method.to_string(), reply);
+    }
+
+    /// Script `get_session_state` so workflow commands see an attached session.
+    pub fn attach(&self, role: &str, run_id: &str) {
+        self.on(
+            "get_session_state",
+            Reply::Ok(json!({
+                "attachedRunId": run_id,
+                "runStatus": "active",
+                "roleId": role,
+            })),
+        );
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<Value> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// The `params` of every request for `method`.
+    pub fn params_for(&self, method: &str) -> Vec<Value> {
+        self.requests()
+            .into_iter()
+            .filter(|r| r["method"] == method)
+            .map(|r| r["params"].clone())
+            .collect()
+    }
+
+    /// The single request for `method`; panics if there were none or several.
+    pub fn only_params(&self, method: &str) -> Value {
+        let mut params = self.params_for(method);
+        assert_eq!(
+            params.len(),
+            1,
+            "expected exactly one `{}` request, got {:?}",
+            method,
+            self.requests()
+        );
+        params.remove(0)
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket
+    }
+
+    /// Scratch directory that doubles as `$HOME` for the CLI.
+    pub fn home(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// The CLI binary, pointed at this mock with an isolated environment.
+    pub fn cli(&self) -> Command {
+        let mut cmd = self.cli_without_socket();
+        cmd.arg("--socket-path").arg(&self.socket);
+        cmd
+    }
+
+    /// Like `cli`, but the socket comes from the CLI's own resolution
+    /// (config contexts, defaults) instead of `--socket-path`.
+    pub fn cli_without_socket(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_hotwired"));
+        cmd.env_clear()
+            .env("PATH", std::env::var("PATH").unwrap_or_default())
+            .env("HOME", self.home())
+            .env("ZELLIJ_SESSION_NAME", "test-session")
+            .env("HOTWIRED_TIMEOUT", "2")
+            .current_dir(self.home());
+        cmd
+    }
+
+    /// Run the CLI with `args` and return its output.
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.cli().args(args).output().unwrap()
+    }
+}
+
+fn serve(stream: UnixStream, state: Arc<Mutex<State>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut line = String::new();
+    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+        return;
+    }
+
+    let request: Value = match serde_json::from_str(&line) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+
+    let reply = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(request);
+        state.replies.get(&method).cloned()
+    };
+
+    let mut stream = stream;
+    let mut write_line = |value: &str| {
+        let _ = stream.write_all(value.as_bytes());
+        let _ = stream.write_all(b"\n");
+    };
+
+    match reply {
+        Some(Reply::Ok(data)) => write_line(&json!({"success": true, "data": data}).to_string()),
+        Some(Reply::Fail { error, code }) => {
+            write_line(&json!({"success": false, "error": error, "code": code}).to_string())
+        }
+        Some(Reply::Raw(raw)) => write_line(&raw),
+        Some(Reply::Disconnect) => {}
+        Some(Reply::Hang) => std::thread::sleep(std::time::Duration::from_secs(30)),
+        Some(Reply::Stream(events)) => {
+            write_line(&json!({"success": true}).to_string());
+            for event in events {
+                write_line(&event.to_string());
+            }
+        }
+        None => write_line(
+            &json!({
+                "success": false,
+                "error": format!("unscripted method {}", method),
+                "code": "unknown_method",
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Captured stdout as a string
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Captured stderr as a string
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}